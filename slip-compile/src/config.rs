@@ -0,0 +1,98 @@
+//! The configuration of a project, read from a JSON file.  This controls
+//! which diagnostics are emitted, the same as the command line does:
+//!
+//! ```json
+//! {
+//!     "level": "warning",
+//!     "allow": ["unused-import"],
+//!     "warn": ["type-trace.possible"],
+//!     "deny": ["unused-local"],
+//!     "error-limit": 20
+//! }
+//! ```
+//!
+//! Every field is optional.  The names are the short names of diagnostics,
+//! as given to `-A`, `-W`, and `-D`; a group name (e.g. `type-trace`) names
+//! every diagnostic in the group.  The `error-limit` is the number of errors
+//! after which compilation stops.  The command line wins over the
+//! configuration, and the attributes in the source win over both.
+
+use crate::diag::{Level, Name};
+use crate::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// The file that the configuration is read from, if one isn't given
+/// explicitly.  This is looked for in the current directory.
+pub const FILE: &str = "slip.json";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    level: Option<Level>,
+    error_limit: Option<usize>,
+    allow: Vec<String>,
+    warn: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl Config {
+    pub fn read(path: &Path) -> Result<Config, Error> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// The level that emissions have to be at or above to be active, if
+    /// it's set.
+    pub fn level(&self) -> Option<Level> {
+        self.level
+    }
+
+    /// The number of errors after which compilation stops, if it's set.
+    pub fn error_limit(&self) -> Option<usize> {
+        self.error_limit
+    }
+
+    /// The levels that diagnostics are set to.  Each action is applied in
+    /// turn - `allow`, then `warn`, then `deny` - so a diagnostic given to
+    /// more than one ends up with the level of the last.
+    pub fn overrides(&self) -> Result<Vec<(Name, Level)>, Error> {
+        let actions = [
+            (Level::Never, &self.allow),
+            (Level::Warning, &self.warn),
+            (Level::Error, &self.deny),
+        ];
+        let mut overrides = vec![];
+        for (level, names) in actions.iter() {
+            for short in names.iter() {
+                let names = Name::lookup(short)?;
+                overrides.extend(names.into_iter().map(|name| (name, *level)));
+            }
+        }
+        Ok(overrides)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_config_overrides() {
+        let config = serde_json::from_str::<Config>(
+            r#"{ "level": "debug", "allow": ["type-trace"], "deny": ["type-trace.possible"] }"#,
+        )
+        .unwrap();
+        assert_eq!(config.level(), Some(Level::Debug));
+        let overrides = config.overrides().unwrap();
+        assert_eq!(overrides.len(), 4);
+        assert_eq!(overrides[0], (Name::TypeTrace, Level::Never));
+        assert_eq!(overrides[3], (Name::PossibleType, Level::Error));
+
+        let config = serde_json::from_str::<Config>(r#"{ "warn": ["type-trac"] }"#).unwrap();
+        let error = config.overrides().unwrap_err().to_string();
+        assert!(error.starts_with("unknown diagnostic `type-trac`, expected one of:"));
+        assert!(error.contains("type-trace.possible"));
+        assert!(serde_json::from_str::<Config>(r#"{ "level": "loud" }"#).is_err());
+    }
+}
@@ -1,14 +1,19 @@
-use super::{Level, Name, Source, Span};
+use super::snippet::{self, Mark};
+use super::{Level, Name, SourceMap, Span};
 use std::borrow::Cow;
 use std::io::{Result as IoResult, Write};
-use term::Terminal;
+use term::{color, Terminal};
 
 #[derive(Debug, Clone)]
 /// A single emission caused by a diagnostic.  This is the materialization
 /// of a diagnostic.  This can - and should - always be constructed
 /// regardless of whether or not the emission should be outputted to the
 /// terminal, as we keep track of them.
-pub(super) struct Emission {
+///
+/// Besides its primary span and message, an emission can carry labelled
+/// secondary spans (e.g. where an item was previously defined), notes, and
+/// help text; these are all emitted together with it.
+pub struct Emission {
     /// The name of the mission.  This can be used to determine the default
     /// level of the emission.
     name: Name,
@@ -18,107 +23,151 @@ pub(super) struct Emission {
     span: Span,
     /// The message given for emission.  This can contain debug information.
     message: Cow<'static, str>,
+    /// Other areas related to the emission, each with a message describing
+    /// how it's related.
+    labels: Vec<Label>,
+    /// Extra information about the emission.
+    notes: Vec<Cow<'static, str>>,
+    /// Suggestions on how to fix what caused the emission.
+    help: Vec<Cow<'static, str>>,
+    /// Replacements for parts of the source that would fix what caused the
+    /// emission.
+    suggestions: Vec<Suggestion>,
+}
+
+/// A suggested replacement of the source within a span.  An insertion is a
+/// replacement of an empty span.
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    span: Span,
+    replacement: Cow<'static, str>,
+    message: Cow<'static, str>,
+    applicability: Applicability,
+}
+
+/// How confident we are that a suggestion is correct, and so whether or not
+/// it can be applied without the user looking at it first.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Applicability {
+    /// The suggestion is definitely what the user meant, and can be applied
+    /// automatically, e.g. with `--fix`.
+    MachineApplicable,
+    /// The suggestion may be what the user meant, but it might not be, or
+    /// it might not compile.
+    MaybeIncorrect,
+}
+
+/// A secondary span of an emission, along with a message describing how it
+/// relates to the emission.
+#[derive(Debug, Clone)]
+pub struct Label {
+    span: Span,
+    message: Cow<'static, str>,
 }
 
 impl Emission {
     /// Constructs a new emission from the given information.  The message
     /// here can be a static string slice, or an owned string, as both are
-    /// acceptable.
-    pub fn new<T: Into<Cow<'static, str>>>(
-        name: Name,
-        level: Level,
-        span: Span,
-        message: T,
-    ) -> Emission {
+    /// acceptable.  The emission starts at the default level for its name;
+    /// the diagnostics update it to the overridden level when it's reported.
+    pub fn new<T: Into<Cow<'static, str>>>(name: Name, span: Span, message: T) -> Emission {
         Emission {
             name,
-            level,
+            level: name.level(),
             span,
             message: message.into(),
+            labels: vec![],
+            notes: vec![],
+            help: vec![],
+            suggestions: vec![],
         }
     }
 
-    /// This emits out to a terminal, with the given file, if it exists.  This
-    /// provides the feedback to the user, if requested.  This should not
-    /// error unless there is an underlying issue with the IO object.
-    pub(super) fn emit<T, TO>(&self, source: Option<&Source>, mut out: &mut T) -> IoResult<()>
-    where
-        TO: Write,
-        T: Terminal<Output = TO> + Send + ?Sized,
-    {
-        use term::color;
-
-        let start_line = self.span.start().line();
-        let end_line = self.span.end().line();
-        // This computes the number of base 10 characters required to fully
-        // represent the end_line; and since end_line > start_line, it will
-        // always be enough to represent the start line, too.
-        let count = f64::from(end_line as u32).log10().trunc() as usize + 1;
-        // We want to get the lines around the area that had the error.  So...
-        let lines = source
-            .as_ref()
-            // We'll try to get the content of the file, if it exists.
-            .and_then(|f| f.content())
-            // If it doesn't, just use an empty string.
-            .unwrap_or("")
-            // Enumerate the lines,
-            .lines()
-            .enumerate()
-            // Skipping until the start_line - 4,
-            .skip(start_line.saturating_sub(4))
-            // taking everything between the start and end lines, plus some
-            // extra, for context.
-            .take(end_line - start_line + 8);
-
-        out.fg(color::BRIGHT_BLUE).unwrap();
-
-        // Write the first line.  It'll write up to the count number of spaces,
-        // giving enough room to make it line up later.
-        writeln!(&mut out, "{:1$}> ", "", count + 1)?;
+    /// Adds a labelled secondary span to the emission.
+    pub fn with_label(mut self, span: Span, message: impl Into<Cow<'static, str>>) -> Emission {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
 
-        out.reset().unwrap();
+    /// Adds a note to the emission.
+    pub fn with_note(mut self, message: impl Into<Cow<'static, str>>) -> Emission {
+        self.notes.push(message.into());
+        self
+    }
 
-        for (num, line) in lines {
-            let lino = num + 1;
+    /// Adds help text to the emission.
+    pub fn with_help(mut self, message: impl Into<Cow<'static, str>>) -> Emission {
+        self.help.push(message.into());
+        self
+    }
 
-            out.fg(color::BRIGHT_BLUE).unwrap();
+    /// Adds a suggested replacement to the emission.  The message should
+    /// describe the replacement, e.g. "insert a `;`".
+    pub fn with_suggestion(
+        mut self,
+        span: Span,
+        replacement: impl Into<Cow<'static, str>>,
+        message: impl Into<Cow<'static, str>>,
+        applicability: Applicability,
+    ) -> Emission {
+        self.suggestions.push(Suggestion {
+            span,
+            replacement: replacement.into(),
+            message: message.into(),
+            applicability,
+        });
+        self
+    }
 
-            if lino >= start_line && lino <= end_line {
-                // we're definitely in between the start and end here.  So we
-                // actually want to output the line numbers.
-                write!(&mut out, "{:1$} | ", lino, count + 1)?;
-            } else {
-                // If we're not in range, just output empty space.
-                write!(&mut out, "{:1$}| ", "", count + 1)?;
-            }
+    pub fn name(&self) -> Name {
+        self.name
+    }
 
-            out.reset().unwrap();
+    pub fn level(&self) -> Level {
+        self.level
+    }
 
-            // Now, actually write the line from the file.  No colouring or
-            // anything.  We don't need it.
-            writeln!(&mut out, "{}", line)?;
-        }
-        let scolumn = self.span.start().column();
-        let ecolumn = self.span.end().column();
-        let ncolumns = if ecolumn <= scolumn {
-            1
-        } else {
-            ecolumn - scolumn
-        };
+    pub(super) fn set_level(&mut self, level: Level) {
+        self.level = level;
+    }
 
-        out.fg(color::BRIGHT_YELLOW).unwrap();
-        // Mark out the columns.  Yay!
-        writeln!(
-            &mut out,
-            "{:lineoff$}>{:scol$}{:^<repeat$}",
-            "",
-            "",
-            "",
-            repeat = ncolumns,
-            lineoff = count + 1,
-            scol = scolumn,
-        )?;
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn notes(&self) -> impl Iterator<Item = &str> {
+        self.notes.iter().map(AsRef::as_ref)
+    }
+
+    pub fn help(&self) -> impl Iterator<Item = &str> {
+        self.help.iter().map(AsRef::as_ref)
+    }
 
+    pub fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+
+    /// This emits out to a terminal, with the sources of the spans, if they
+    /// exist.  This provides the feedback to the user, if requested.  This
+    /// should not error unless there is an underlying issue with the IO
+    /// object.  See [`super::snippet`] for how the spans are shown.
+    pub(super) fn emit<T, TO>(&self, sources: &SourceMap<'_>, mut out: &mut T) -> IoResult<()>
+    where
+        TO: Write,
+        T: Terminal<Output = TO> + Send + ?Sized,
+    {
         let color = match self.level {
             Level::Panic => color::BRIGHT_MAGENTA,
             Level::Error => color::BRIGHT_RED,
@@ -127,12 +176,72 @@ impl Emission {
         };
 
         out.fg(color).unwrap();
+        write!(&mut out, "{}[{}]", self.level, self.name.code())?;
+        out.reset().unwrap();
+        writeln!(&mut out, ": {}", self.message)?;
 
-        writeln!(&mut out, "{}: {}", self.level, self.message)?;
+        let primary = Mark {
+            span: self.span,
+            label: None,
+            primary: true,
+        };
+        let marks = Some(primary)
+            .into_iter()
+            .chain(self.labels.iter().map(|label| Mark {
+                span: label.span,
+                label: Some(&label.message),
+                primary: false,
+            }))
+            .collect::<Vec<_>>();
+        // The notes and help are indented to line up with the line numbers
+        // of the snippets, so this is worked out across all of them.
+        let gutter = snippet::gutter(&marks);
+        snippet::render(out, sources, &marks, gutter, color)?;
+
+        for (kind, message) in self
+            .notes
+            .iter()
+            .map(|note| ("note", note))
+            .chain(self.help.iter().map(|help| ("help", help)))
+            .chain(self.suggestions.iter().map(|s| ("help", &s.message)))
+        {
+            out.fg(color::BRIGHT_BLUE).unwrap();
+            write!(&mut out, "{:1$} = ", "", gutter)?;
+            out.reset().unwrap();
+            writeln!(&mut out, "{}: {}", kind, message)?;
+        }
 
-        out.reset().unwrap();
+        writeln!(&mut out)?;
         out.flush().unwrap();
 
         Ok(())
     }
 }
+
+impl Suggestion {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn applicability(&self) -> Applicability {
+        self.applicability
+    }
+}
+
+impl Label {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
@@ -0,0 +1,124 @@
+//! Finding and applying suggestions.  Suggestions for misspelled names are
+//! found by edit distance, with [`closest()`]; suggestions that are
+//! machine-applicable can then be applied to the source they're in, with
+//! [`apply()`].
+
+use super::emission::Suggestion;
+use std::cmp::min;
+
+/// The edit distance between the two strings; that is, the number of
+/// characters that have to be inserted, removed, or replaced, or pairs of
+/// adjacent characters that have to be swapped, to turn one into the other.
+/// Each part of the strings is only edited once (this is the "optimal string
+/// alignment" distance).
+pub fn distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // The distances between the prefixes of the strings; `rows[i][j]` is
+    // the distance between the first `i` characters of `a` and the first
+    // `j` characters of `b`.
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut best = min(
+                rows[i - 1][j - 1] + cost,
+                min(rows[i - 1][j], rows[i][j - 1]) + 1,
+            );
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = min(best, rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Finds the candidate whose name is closest to the given name, if any are
+/// close enough to be a plausible misspelling of it - within a third of the
+/// length of the name (but at least one).  Ties go to the first candidate.
+pub fn closest<T>(
+    name: &str,
+    candidates: impl IntoIterator<Item = T>,
+    key: impl Fn(&T) -> &str,
+) -> Option<T> {
+    let limit = std::cmp::max(name.chars().count() / 3, 1);
+    candidates
+        .into_iter()
+        .map(|candidate| (distance(name, key(&candidate)), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Applies the given suggestions to the content of a source.  The
+/// suggestions are applied in order of where they are in the content; if
+/// a suggestion overlaps one that came before it, it is skipped, as is any
+/// suggestion that's identical to one that was already applied.
+pub(super) fn apply(content: &str, mut suggestions: Vec<&Suggestion>) -> String {
+    suggestions.sort_by_key(|s| (s.span().start().offset(), s.span().end().offset()));
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut previous: Option<&Suggestion> = None;
+
+    for suggestion in suggestions {
+        let start = suggestion.span().start().offset();
+        let end = suggestion.span().end().offset();
+        let repeated = previous.is_some_and(|p| {
+            p.span() == suggestion.span() && p.replacement() == suggestion.replacement()
+        });
+        if repeated || start < last || end > content.len() {
+            continue;
+        }
+
+        result.push_str(&content[last..start]);
+        result.push_str(suggestion.replacement());
+        last = end;
+        previous = Some(suggestion);
+    }
+
+    result.push_str(&content[last..]);
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::diag::{Applicability, Emission, Name, Position, Span};
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("List", "List"), 0);
+        assert_eq!(distance("Lsit", "List"), 1);
+        assert_eq!(distance("Piont", "Point"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("Lis", "List"), 1);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(closest("Lst", vec!["Map", "List"], |c| c), Some("List"));
+        assert_eq!(closest("Lst", vec!["Map", "Array"], |c| c), None);
+    }
+
+    #[test]
+    fn test_apply() {
+        let at = |offset| Position::new(offset, 1, offset + 1);
+        let insert = |offset, text: &'static str| {
+            Emission::new(Name::UnexpectedToken, Span::default(), "").with_suggestion(
+                Span::new(at(offset), at(offset), None),
+                text,
+                "",
+                Applicability::MachineApplicable,
+            )
+        };
+        let emissions = [insert(5, ";"), insert(12, ";"), insert(5, ";")];
+        let suggestions = emissions.iter().flat_map(Emission::suggestions).collect();
+        assert_eq!(apply("let a let b }", suggestions), "let a; let b ;}");
+    }
+}
@@ -0,0 +1,189 @@
+//! Writes emissions out as JSON Lines - one JSON object per line - for
+//! tools (e.g. editors, or CI) to consume.  Each emission is written as a
+//! single object, with its labels, notes, and help kept within it:
+//!
+//! ```json
+//! {"name":"type-redefinition","code":"S0029","level":"error",
+//!  "message":"item A already defined",
+//!  "span":{"source":"main.slip","start":{"offset":20,"line":2,"column":1},
+//!  "end":{"offset":39,"line":2,"column":20}},
+//!  "labels":[{"span":{...},"message":"previous definition here"}],
+//!  "notes":[],"help":[],"suggestions":[]}
+//! ```
+//!
+//! (though without the line breaks).  The `code` is the stable code of the
+//! diagnostic, which `slip explain` takes.  The `source` is the name of the
+//! source (the path, for files), or `null` if the span isn't in any source;
+//! unlike the text, the locations don't take `#line` directives into account,
+//! so that they always point into the source itself.  Each
+//! of the suggestions has a `span`, the `replacement` for that span, a
+//! `message`, and an `applicability` of either `machine-applicable` or
+//! `maybe-incorrect`.
+//!
+//! Errors that stop the compile without being emissions (e.g. a dependency
+//! that can't be read) are written as a record with only a `level` of
+//! `panic` and a `message`, so that the output is still all JSON.
+
+use super::emission::{Applicability, Emission, Label, Suggestion};
+use super::{Level, Position, Source, SourceMap, Span};
+use std::io::{Result as IoResult, Write};
+
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    name: &'static str,
+    code: &'static str,
+    level: Level,
+    message: &'a str,
+    span: Location<'a>,
+    labels: Vec<LabelRecord<'a>>,
+    notes: Vec<&'a str>,
+    help: Vec<&'a str>,
+    suggestions: Vec<SuggestionRecord<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct FatalRecord<'a> {
+    level: Level,
+    message: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct LabelRecord<'a> {
+    span: Location<'a>,
+    message: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct SuggestionRecord<'a> {
+    span: Location<'a>,
+    replacement: &'a str,
+    message: &'a str,
+    applicability: Applicability,
+}
+
+#[derive(Debug, Serialize)]
+struct Location<'a> {
+    source: Option<&'a str>,
+    start: Position,
+    end: Position,
+}
+
+impl<'a> Record<'a> {
+    fn new(emission: &'a Emission, sources: &'a SourceMap<'_>) -> Record<'a> {
+        Record {
+            name: emission.name().short(),
+            code: emission.name().code(),
+            level: emission.level(),
+            message: emission.message(),
+            span: Location::new(emission.span(), sources),
+            labels: emission
+                .labels()
+                .iter()
+                .map(|label| LabelRecord::new(label, sources))
+                .collect(),
+            notes: emission.notes().collect(),
+            help: emission.help().collect(),
+            suggestions: emission
+                .suggestions()
+                .iter()
+                .map(|suggestion| SuggestionRecord::new(suggestion, sources))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> LabelRecord<'a> {
+    fn new(label: &'a Label, sources: &'a SourceMap<'_>) -> LabelRecord<'a> {
+        LabelRecord {
+            span: Location::new(label.span(), sources),
+            message: label.message(),
+        }
+    }
+}
+
+impl<'a> SuggestionRecord<'a> {
+    fn new(suggestion: &'a Suggestion, sources: &'a SourceMap<'_>) -> SuggestionRecord<'a> {
+        SuggestionRecord {
+            span: Location::new(suggestion.span(), sources),
+            replacement: suggestion.replacement(),
+            message: suggestion.message(),
+            applicability: suggestion.applicability(),
+        }
+    }
+}
+
+impl<'a> Location<'a> {
+    fn new(span: Span, sources: &'a SourceMap<'_>) -> Location<'a> {
+        let source = span.source().and_then(|s| sources.get(s)).map(Source::name);
+        Location {
+            source,
+            start: span.start(),
+            end: span.end(),
+        }
+    }
+}
+
+/// Writes the emission out as a single line of JSON.
+pub(super) fn emit(
+    emission: &Emission,
+    sources: &SourceMap<'_>,
+    mut out: impl Write,
+) -> IoResult<()> {
+    let record = Record::new(emission, sources);
+    serde_json::to_writer(&mut out, &record)?;
+    writeln!(out)?;
+    out.flush()
+}
+
+/// Writes the fatal error out as a single line of JSON.
+pub(super) fn fatal(message: &str, mut out: impl Write) -> IoResult<()> {
+    let record = FatalRecord {
+        level: Level::Panic,
+        message,
+    };
+    serde_json::to_writer(&mut out, &record)?;
+    writeln!(out)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::diag::Name;
+
+    #[test]
+    fn test_json_record() {
+        let mut sources = SourceMap::new();
+        let id = sources.push("main.slip", None::<&str>);
+        let span = Span::new(Position::new(4, 1, 5), Position::new(5, 1, 6), Some(id));
+        let emission = Emission::new(Name::TypeRedefinition, span, "item A already defined")
+            .with_label(span, "previous definition here")
+            .with_help("rename one of the items");
+
+        let mut out = vec![];
+        emit(&emission, &sources, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 1);
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(value["name"], "type-redefinition");
+        assert_eq!(value["code"], "S0029");
+        assert_eq!(value["level"], "error");
+        assert_eq!(value["span"]["source"], "main.slip");
+        assert_eq!(value["span"]["start"]["column"], 5);
+        assert_eq!(value["labels"][0]["message"], "previous definition here");
+        assert_eq!(value["labels"][0]["span"]["source"], "main.slip");
+        assert_eq!(value["notes"].as_array().unwrap().len(), 0);
+        assert_eq!(value["help"][0], "rename one of the items");
+    }
+
+    #[test]
+    fn test_json_fatal() {
+        let mut out = vec![];
+        fatal("std.typal: invalid module", &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 1);
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(value["level"], "panic");
+        assert_eq!(value["message"], "std.typal: invalid module");
+    }
+}
@@ -1,6 +1,9 @@
+use crate::error::Error;
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Level {
     Never,
     All,
@@ -18,6 +21,54 @@ impl Default for Level {
     }
 }
 
+impl Level {
+    /// Every level, from lowest to highest.
+    pub fn all() -> &'static [Level] {
+        &[
+            Level::Never,
+            Level::All,
+            Level::Debug,
+            Level::Info,
+            Level::Warning,
+            Level::Error,
+            Level::Panic,
+            Level::Off,
+        ]
+    }
+
+    /// The level that the given action sets diagnostics to.  The actions
+    /// are the same whether they're given in the source, as attributes, or
+    /// outside of it: `allow` silences a diagnostic, `warn` makes it a
+    /// warning, and `deny` makes it an error.
+    pub fn from_action(action: &str) -> Option<Level> {
+        match action {
+            "allow" => Some(Level::Never),
+            "warn" => Some(Level::Warning),
+            "deny" => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    fn from_str(level: &str) -> Result<Level, Error> {
+        Level::all()
+            .iter()
+            .cloned()
+            .find(|known| known.to_string() == level)
+            .ok_or_else(|| Error::UnknownLevelError {
+                level: level.to_string(),
+                expected: Level::all()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            })
+    }
+}
+
 impl fmt::Display for Level {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -13,35 +13,40 @@
 //! correct file, taking up the space of only 8 bytes.  So far so good.
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::Result as IoResult;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 mod emission;
+mod fix;
+mod json;
 mod level;
 mod name;
 mod output;
 mod overrides;
+mod sink;
+mod snippet;
 mod source;
 mod span;
 
+pub use self::emission::{Applicability, Emission, Label, Suggestion};
+pub use self::fix::{closest, distance};
 pub use self::level::Level;
 pub use self::name::Name;
-pub use self::source::{Source, SourceId};
+pub use self::output::Format;
+pub use self::sink::{CollectSink, DiagnosticSink, JsonSink, MemorySink, PlainSink, TerminalSink};
+pub use self::source::{Source, SourceId, SourceMap};
 pub use self::span::{Position, SourceSpan, Span};
 
-use self::emission::Emission;
 use self::overrides::Overrides;
 
 /// The diagnostic information related to a compilation.  This includes
 /// information about file sources and errors.
 #[derive(Debug)]
 pub struct Diagnostics<'c> {
-    /// A mapping from file source ids to the actual sources themselves.
-    sources: HashMap<SourceId, Source<'c>>,
-
-    /// This contains the next source id.  Since we need to make sure
-    /// that this is unique across all files, we'll just use a counter.
-    next: usize,
+    /// The sources themselves, which the source ids refer to.
+    sources: SourceMap<'c>,
 
     /// The currently active level.  Anything emitted at or above this
     /// level will be reported.
@@ -55,6 +60,26 @@ pub struct Diagnostics<'c> {
     /// diagnostics information.  This can be compiled into a large
     /// list at the end and emitted then, or serialized.
     emissions: Vec<Emission>,
+
+    /// Where the emissions go once they're emitted; every active emission
+    /// is given to each of them.
+    sinks: Vec<Box<dyn DiagnosticSink>>,
+
+    /// The name, span, and message of every emission so far.  An emission
+    /// that's identical to an earlier one in all three is dropped, as it
+    /// says nothing new (e.g. the same unknown type used in many places
+    /// that are processed separately).
+    seen: HashSet<(Name, Span, String)>,
+
+    /// The number of active emissions that are errors (or worse), and the
+    /// number that are warnings; see [`counts()`].  These are kept as the
+    /// emissions are reported, so that checking the limit stays cheap.
+    errors: usize,
+    warnings: usize,
+
+    /// The number of active errors after which nothing more is emitted,
+    /// and compilation should stop; see [`exceeded()`].
+    limit: Option<usize>,
 }
 
 impl<'c> Diagnostics<'c> {
@@ -64,26 +89,80 @@ impl<'c> Diagnostics<'c> {
         Diagnostics::default()
     }
 
-    /// Creates a new source, and returns the proper reference to that
-    /// source.  This allows us to create information about the source
+    /// Sets how emissions are written out to stderr, replacing any sinks.
+    /// This defaults to [`Format::Text`].
+    pub fn format(self, format: Format) -> Diagnostics<'c> {
+        match format {
+            Format::Text => self.sink(TerminalSink),
+            Format::Json => self.sink(JsonSink::stderr()),
+        }
+    }
+
+    /// Sets the sink that emissions go to, replacing any others.
+    pub fn sink(mut self, sink: impl DiagnosticSink + 'static) -> Diagnostics<'c> {
+        self.sinks = vec![Box::new(sink)];
+        self
+    }
+
+    /// Adds another sink for emissions to go to, after the existing ones.
+    pub fn add_sink(mut self, sink: impl DiagnosticSink + 'static) -> Diagnostics<'c> {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Sets the level that emissions have to be at or above to be active.
+    /// This defaults to [`Level::Info`].
+    pub fn level(mut self, level: Level) -> Diagnostics<'c> {
+        self.active = level;
+        self
+    }
+
+    /// Sets the number of active errors after which nothing more is emitted;
+    /// see [`exceeded()`].  By default, there is no limit.
+    pub fn limit(mut self, limit: Option<usize>) -> Diagnostics<'c> {
+        self.limit = limit;
+        self
+    }
+
+    /// Sets the levels of the given diagnostics everywhere, in place of
+    /// their default levels.  These are given in order, so a later override
+    /// of a diagnostic wins over an earlier one; the overrides in the source
+    /// win over all of them.
+    pub fn overrides(
+        mut self,
+        overrides: impl IntoIterator<Item = (Name, Level)>,
+    ) -> Diagnostics<'c> {
+        for (name, level) in overrides {
+            self.overrides.insert(name, level);
+        }
+        self
+    }
+
+    /// Creates a new virtual source, and returns the proper reference to
+    /// that source.  This allows us to create information about the source
     /// while using a copyable id to refer to it later.
     pub fn push(
         &mut self,
         name: impl Into<Cow<'c, str>>,
         content: Option<impl Into<Cow<'c, str>>>,
     ) -> SourceId {
-        let id = self.next;
-        self.next += 1;
-        let id = SourceId(id);
-        self.sources.insert(
-            id,
-            Source {
-                id,
-                name: name.into(),
-                content: content.map(Into::into),
-            },
-        );
-        id
+        self.sources.push(name, content)
+    }
+
+    /// Reads the file at the given path in as a new source; see
+    /// [`SourceMap::load()`].
+    pub fn load(&mut self, path: impl AsRef<Path>) -> IoResult<SourceId> {
+        self.sources.load(path)
+    }
+
+    /// Remaps the lines of a source from the given line on, as a `#line`
+    /// directive does; see [`SourceMap::remap()`].
+    pub fn remap(&mut self, id: SourceId, line: usize, name: Option<String>, to: usize) {
+        self.sources.remap(id, line, name, to)
+    }
+
+    pub fn sources(&self) -> &SourceMap<'c> {
+        &self.sources
     }
 
     /// Emits the given emission if, and only if, the given check name
@@ -104,38 +183,150 @@ impl<'c> Diagnostics<'c> {
 
     /// Emits a diagnostic.  Even if the given diagostic name would not be
     /// emitted, it is still appended to the emissions list.  If it is emitted,
-    /// it is emitted with all of the information that can be gathered, and
-    /// emitted with [`Emission::emit()`] - with terminal support by default,
-    /// otherwise, just stderr.
+    /// it is given to each of the sinks - by default, written to stderr with
+    /// terminal support.
     pub fn emit(&mut self, name: Name, span: Span, message: impl Into<Cow<'static, str>>) {
-        let emission = Emission::new(name, self.overrides.lookup(name), span, message);
-        if self.active(name) {
-            let source = span.source().and_then(|s| self.sources.get(&s));
-            if let Some(mut term) = ::term::stderr() {
-                emission.emit(source, &mut *term).unwrap();
-            } else {
-                emission
-                    .emit(source, &mut self::output::NonTerminal::stderr())
-                    .unwrap();
+        self.report(Emission::new(name, span, message))
+    }
+
+    /// Emits an already-constructed emission, e.g. one with labels, notes,
+    /// or help attached, as if by [`emit()`].  The emission's level is set
+    /// from its name, taking any overrides into account.  Emissions that
+    /// are identical to an earlier one, or that come after the error limit
+    /// has been reached, are dropped.
+    pub fn report(&mut self, mut emission: Emission) {
+        let message = emission.message().to_string();
+        let key = (emission.name(), emission.span(), message);
+        if self.exceeded() || !self.seen.insert(key) {
+            return;
+        }
+
+        emission.set_level(self.overrides.lookup(emission.name()));
+        if self.active(emission.name()) {
+            match emission.level() {
+                Level::Warning => self.warnings += 1,
+                level if level >= Level::Error => self.errors += 1,
+                _ => {}
+            }
+            for sink in &mut self.sinks {
+                sink.emit(&emission, &self.sources);
             }
         }
 
         self.emissions.push(emission);
     }
 
+    /// Finishes handling any emissions that the sinks have held back.  This
+    /// should be called once compilation is done, or some emissions might
+    /// not be written.
+    pub fn flush(&mut self) {
+        for sink in &mut self.sinks {
+            sink.flush(&self.sources);
+        }
+    }
+
+    /// All of the emissions so far, whether or not they were active.
+    pub fn emissions(&self) -> &[Emission] {
+        &self.emissions
+    }
+
+    /// The number of active emissions that are errors (or worse), and the
+    /// number that are warnings.  The levels of the emissions are the ones
+    /// they were emitted at, with any overrides applied.
+    pub fn counts(&self) -> (usize, usize) {
+        (self.errors, self.warnings)
+    }
+
+    /// Whether or not the error limit has been reached; once it has,
+    /// compilation should stop.
+    pub fn exceeded(&self) -> bool {
+        self.limit.is_some_and(|limit| self.counts().0 >= limit)
+    }
+
+    /// A line summarizing the active emissions, e.g. `3 errors, 5 warnings
+    /// emitted`, if there were any errors or warnings.
+    pub fn summary(&self) -> Option<String> {
+        let count = |count, what| match count {
+            1 => format!("1 {}", what),
+            _ => format!("{} {}s", count, what),
+        };
+        match self.counts() {
+            (0, 0) => None,
+            (errors, 0) => Some(format!("{} emitted", count(errors, "error"))),
+            (0, warnings) => Some(format!("{} emitted", count(warnings, "warning"))),
+            (errors, warnings) => Some(format!(
+                "{}, {} emitted",
+                count(errors, "error"),
+                count(warnings, "warning")
+            )),
+        }
+    }
+
+    /// The sources with the machine-applicable suggestions of the active
+    /// emissions applied to them, as pairs of the path of the source and
+    /// the fixed content.  Sources without any such suggestions, or without
+    /// any content, aren't included, and neither are virtual sources, as
+    /// there's nowhere to write them back to.
+    pub fn fixed(&self) -> Vec<(PathBuf, String)> {
+        let mut suggestions = HashMap::<_, Vec<_>>::new();
+        let applicable = self
+            .emissions
+            .iter()
+            .filter(|emission| emission.level() >= self.active)
+            .flat_map(Emission::suggestions)
+            .filter(|s| s.applicability() == Applicability::MachineApplicable);
+        for suggestion in applicable {
+            if let Some(source) = suggestion.span().source() {
+                suggestions.entry(source).or_default().push(suggestion);
+            }
+        }
+
+        suggestions
+            .into_iter()
+            .flat_map(|(id, suggestions)| {
+                let source = self.sources.get(id)?;
+                let path = source.path()?.to_path_buf();
+                let content = self::fix::apply(source.content()?, suggestions);
+                Some((path, content))
+            })
+            .collect()
+    }
+
     pub fn active(&self, name: Name) -> bool {
         self.overrides.lookup(name) >= self.active
     }
+
+    /// Sets the levels of the given diagnostics within a scope in the
+    /// source, until [`pop_overrides()`] is called.  These replace the
+    /// overrides of any scope that was already pushed, rather than adding
+    /// to them, so each scope has to give all of the overrides that apply
+    /// to it.
+    pub fn push_overrides(&mut self, overrides: impl IntoIterator<Item = (Name, Level)>) {
+        self.overrides.push();
+        for (name, level) in overrides {
+            self.overrides.insert(name, level);
+        }
+    }
+
+    /// Removes the overrides of the scope that was last pushed with
+    /// [`push_overrides()`].
+    pub fn pop_overrides(&mut self) {
+        self.overrides.pop()
+    }
 }
 
 impl<'c> Default for Diagnostics<'c> {
     fn default() -> Diagnostics<'c> {
         Diagnostics {
-            sources: Default::default(),
-            next: 0,
+            sources: SourceMap::new(),
             active: Level::default(),
             overrides: Overrides::new(),
             emissions: vec![],
+            sinks: vec![Box::new(TerminalSink)],
+            seen: HashSet::new(),
+            errors: 0,
+            warnings: 0,
+            limit: None,
         }
     }
 }
@@ -144,8 +335,8 @@ impl<'c> Default for Diagnostics<'c> {
 pub struct DiagnosticSync<'c>(Arc<Mutex<Diagnostics<'c>>>);
 
 impl<'c> DiagnosticSync<'c> {
-    /// Creates a new source, and returns the proper reference to that
-    /// source.  This allows us to create information about the source
+    /// Creates a new virtual source, and returns the proper reference to
+    /// that source.  This allows us to create information about the source
     /// while using a copyable id to refer to it later.
     pub fn push(
         &self,
@@ -155,6 +346,23 @@ impl<'c> DiagnosticSync<'c> {
         self.0.lock().unwrap().push(name, content)
     }
 
+    /// See [`Diagnostics::load()`].
+    pub fn load(&self, path: impl AsRef<Path>) -> IoResult<SourceId> {
+        self.0.lock().unwrap().load(path)
+    }
+
+    /// See [`Diagnostics::remap()`].
+    pub fn remap(&self, id: SourceId, line: usize, name: Option<String>, to: usize) {
+        self.0.lock().unwrap().remap(id, line, name, to)
+    }
+
+    /// The content of the given source, if it has any.  Since the
+    /// diagnostics are shared, this is a copy of it.
+    pub fn content(&self, id: SourceId) -> Option<String> {
+        let diag = self.0.lock().unwrap();
+        diag.sources.get(id)?.content().map(String::from)
+    }
+
     /// Emits the given emission if, and only if, the given check name
     /// diagnostic is active.  More concisely, if check is active, then emit
     /// a diagnostic with name `name`, at location `span`, with message
@@ -171,16 +379,63 @@ impl<'c> DiagnosticSync<'c> {
 
     /// Emits a diagnostic.  Even if the given diagostic name would not be
     /// emitted, it is still appended to the emissions list.  If it is emitted,
-    /// it is emitted with all of the information that can be gathered, and
-    /// emitted with [`Emission::emit()`] - with terminal support by default,
-    /// otherwise, just stderr.
+    /// it is given to each of the sinks - by default, written to stderr with
+    /// terminal support.
     pub fn emit(&self, name: Name, span: Span, message: impl Into<Cow<'static, str>>) {
         self.0.lock().unwrap().emit(name, span, message)
     }
 
+    /// Emits an already-constructed emission; see [`Diagnostics::report()`].
+    pub fn report(&self, emission: Emission) {
+        self.0.lock().unwrap().report(emission)
+    }
+
+    /// Finishes handling any emissions that the sinks have held back; see
+    /// [`Diagnostics::flush()`].
+    pub fn flush(&self) {
+        self.0.lock().unwrap().flush()
+    }
+
+    /// All of the emissions so far.  Since the diagnostics are shared, this
+    /// is a copy of them.
+    pub fn emissions(&self) -> Vec<Emission> {
+        self.0.lock().unwrap().emissions().to_vec()
+    }
+
+    /// See [`Diagnostics::counts()`].
+    pub fn counts(&self) -> (usize, usize) {
+        self.0.lock().unwrap().counts()
+    }
+
+    /// See [`Diagnostics::exceeded()`].
+    pub fn exceeded(&self) -> bool {
+        self.0.lock().unwrap().exceeded()
+    }
+
+    /// See [`Diagnostics::summary()`].
+    pub fn summary(&self) -> Option<String> {
+        self.0.lock().unwrap().summary()
+    }
+
+    /// The sources with the machine-applicable suggestions applied to them;
+    /// see [`Diagnostics::fixed()`].
+    pub fn fixed(&self) -> Vec<(PathBuf, String)> {
+        self.0.lock().unwrap().fixed()
+    }
+
     pub fn active(&self, name: Name) -> bool {
         self.0.lock().unwrap().active(name)
     }
+
+    /// See [`Diagnostics::push_overrides()`].
+    pub fn push_overrides(&self, overrides: impl IntoIterator<Item = (Name, Level)>) {
+        self.0.lock().unwrap().push_overrides(overrides)
+    }
+
+    /// See [`Diagnostics::pop_overrides()`].
+    pub fn pop_overrides(&self) {
+        self.0.lock().unwrap().pop_overrides()
+    }
 }
 
 impl<'c> From<Diagnostics<'c>> for DiagnosticSync<'c> {
@@ -188,3 +443,32 @@ impl<'c> From<Diagnostics<'c>> for DiagnosticSync<'c> {
         DiagnosticSync(Arc::new(Mutex::new(diag)))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let memory = MemorySink::new();
+        let mut diag = Diagnostics::new()
+            .sink(memory.clone())
+            .overrides(vec![(Name::TestInfo, Level::Warning)])
+            .limit(Some(2));
+        assert_eq!(diag.summary(), None);
+
+        diag.emit(Name::TestError, Span::default(), "first");
+        diag.emit(Name::TestError, Span::default(), "first");
+        diag.emit(Name::TestInfo, Span::default(), "now a warning");
+        diag.emit(Name::TestDebug, Span::default(), "not active");
+        assert_eq!(memory.emissions().len(), 2);
+        assert_eq!(diag.summary().unwrap(), "1 error, 1 warning emitted");
+        assert!(!diag.exceeded());
+
+        diag.emit(Name::TestPanic, Span::default(), "second");
+        diag.emit(Name::TestWarning, Span::default(), "past the limit");
+        assert!(diag.exceeded());
+        assert_eq!(diag.counts(), (2, 1));
+        assert_eq!(diag.summary().unwrap(), "2 errors, 1 warning emitted");
+    }
+}
@@ -1,17 +1,24 @@
 use super::Level;
+use crate::error::Error;
 
 macro_rules! diag_variant {
     (pub enum $name:tt {
-        $($variant:tt = ($short:expr, $level:expr)),*
+        $($(#[doc = $doc:expr])* $variant:ident = ($short:expr, $level:expr, $code:expr)),*
     }) => {
         #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub enum $name {
             $(
+                $(#[doc = $doc])*
                 $variant,
             )*
         }
 
         impl $name {
+            /// Every diagnostic name, in the order they were declared.
+            pub fn all() -> &'static [$name] {
+                &[$($name::$variant),*]
+            }
+
             pub fn level(&self) -> $crate::diag::Level {
                 match self {
                     $($name::$variant => $level),*
@@ -23,6 +30,21 @@ macro_rules! diag_variant {
                     $($name::$variant => $short),*
                 }
             }
+
+            /// The stable code of the diagnostic, e.g. `S0010`.  Unlike the
+            /// variants, the codes never change once they're given out.
+            pub fn code(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $code),*
+                }
+            }
+
+            /// The documentation of the variant, as it was written.
+            fn docs(&self) -> &'static str {
+                match self {
+                    $($name::$variant => concat!($($doc, "\n"),*)),*
+                }
+            }
         }
 
         impl ::std::fmt::Display for $name {
@@ -37,33 +59,471 @@ macro_rules! diag_variant {
 
 diag_variant! {
     pub enum Name {
-        TestDebug = ("test-debug", Level::Debug),
-        TestInfo = ("test-info", Level::Info),
-        TestWarning = ("test-warning", Level::Warning),
-        TestError = ("test-error", Level::Error),
-        TestPanic = ("test-panic", Level::Panic),
+        /// A debug-level diagnostic, used by the compiler's own tests.
+        TestDebug = ("test-debug", Level::Debug, "S0001"),
+        /// An info-level diagnostic, used by the compiler's own tests.
+        TestInfo = ("test-info", Level::Info, "S0002"),
+        /// A warning-level diagnostic, used by the compiler's own tests.
+        TestWarning = ("test-warning", Level::Warning, "S0003"),
+        /// An error-level diagnostic, used by the compiler's own tests.
+        TestError = ("test-error", Level::Error, "S0004"),
+        /// A panic-level diagnostic, used by the compiler's own tests.
+        TestPanic = ("test-panic", Level::Panic, "S0005"),
 
-        Note = ("note", Level::Info),
+        /// General information about the compilation, that isn't a problem
+        /// with the source.  Nothing emits this yet.
+        Note = ("note", Level::Info, "S0006"),
 
-        UnexpectedToken = ("unexpected-token", Level::Panic),
+        /// The parser found a token that can't come where it is.  Parsing
+        /// stops at the first of these, so any problems after it aren't
+        /// reported until it's fixed.  If the token that was expected was a
+        /// `;`, the fix is to insert it, which `--fix` can do:
+        ///
+        /// ```slip
+        /// fn f(): i32 {
+        ///     let a = 1
+        ///     return a;
+        /// }
+        /// ```
+        UnexpectedToken = ("unexpected-token", Level::Panic, "S0007"),
 
-        LiteralError = ("literal-error", Level::Error),
-        UndefinedLocal = ("undefined-local", Level::Error),
+        /// A literal that can't be read as a value, e.g. an integer that is
+        /// too large for 64 bits:
+        ///
+        /// ```slip
+        /// const BIG: u64 = 99999999999999999999;
+        /// ```
+        LiteralError = ("literal-error", Level::Error, "S0008"),
+        /// A name was used as a local, but no local of that name is defined
+        /// at that point, either as a parameter or with `let`:
+        ///
+        /// ```slip
+        /// fn f(): i32 {
+        ///     return a;
+        /// }
+        /// ```
+        UndefinedLocal = ("undefined-local", Level::Error, "S0009"),
+        /// A local was defined with `let`, but never used afterwards.  It can
+        /// be removed, or its value used:
+        ///
+        /// ```slip
+        /// fn f(): i32 {
+        ///     let a = 1;
+        ///     return 2;
+        /// }
+        /// ```
+        UnusedLocal = ("unused-local", Level::Warning, "S0010"),
+        /// A local was defined with the same name as a local that's still in
+        /// scope, which can no longer be referenced afterwards:
+        ///
+        /// ```slip
+        /// fn f(a: i32): i32 {
+        ///     let a = 2;
+        ///     return a;
+        /// }
+        /// ```
+        ShadowedLocal = ("shadowed-local", Level::Warning, "S0011"),
 
-        AmbiguousType = ("ambiguous-type", Level::Error),
-        UnknownType = ("unknown-type", Level::Error),
-        TypeTrace = ("type-trace", Level::Never),
-        PossibleType = ("type-trace.possible", Level::Never),
-        AcceptedType = ("type-trace.accepted", Level::Never),
+        /// A type name refers to more than one type, e.g. through two
+        /// glob imports that each have a type of that name.  The type has to
+        /// be named with more of its path, or imported by name:
+        ///
+        /// ```slip
+        /// export module Geo {
+        ///     export struct Point { x: i32 }
+        /// }
+        ///
+        /// export module Shapes {
+        ///     export struct Point { y: i32 }
+        /// }
+        ///
+        /// use Geo::*;
+        /// use Shapes::*;
+        ///
+        /// fn f(p: Point) { }
+        /// ```
+        AmbiguousType = ("ambiguous-type", Level::Error, "S0012"),
+        /// A type name doesn't refer to any type in scope - neither one
+        /// defined in the source, one imported with `use`, nor one from a
+        /// dependency.  If there's a type with a similar name, it's suggested:
+        ///
+        /// ```slip
+        /// struct Point { x: i32, y: i32 }
+        ///
+        /// fn f(p: Piont) { }
+        /// ```
+        UnknownType = ("unknown-type", Level::Error, "S0013"),
+        /// Traces how type names are resolved.  This is off unless it's
+        /// asked for (e.g. with `-W type-trace`); when on, the errors for
+        /// types that can't be resolved point at every type that was tried.
+        /// This also turns on `type-trace.possible` and `type-trace.accepted`.
+        TypeTrace = ("type-trace", Level::Never, "S0014"),
+        /// With `type-trace`, marks each of the types that an ambiguous type
+        /// name could have referred to.
+        PossibleType = ("type-trace.possible", Level::Never, "S0015"),
+        /// With `type-trace`, marks the type that an ambiguous type name was
+        /// taken to refer to.
+        AcceptedType = ("type-trace.accepted", Level::Never, "S0016"),
+        /// A `use` names a module path that none of the sources, search
+        /// directories, or dependencies define:
+        ///
+        /// ```slip
+        /// use Does::Not::Exist;
+        /// ```
+        UnresolvedImport = ("unresolved-import", Level::Error, "S0017"),
+        /// The files of the program import each other in a cycle.  Each file
+        /// is still only loaded once, but the cycle usually means the modules
+        /// should be split up differently:
+        ///
+        /// ```slip
+        /// // A.slip
+        /// use B;
+        /// // B.slip
+        /// use A;
+        /// ```
+        ImportCycle = ("import-cycle", Level::Error, "S0018"),
+        /// Nothing was found through an import, so it can be removed:
+        ///
+        /// ```slip
+        /// use Geo::Point;
+        ///
+        /// fn f(): i32 {
+        ///     return 1;
+        /// }
+        /// ```
+        UnusedImport = ("unused-import", Level::Warning, "S0019"),
+        /// An import has the same name as a type defined in the module it's
+        /// in (or a module around it).  Types in scope are looked at before
+        /// imports, so the import is never used:
+        ///
+        /// ```slip
+        /// use Geo::Point;
+        ///
+        /// struct Point { x: i32, y: i32 }
+        /// ```
+        ShadowedImport = ("shadowed-import", Level::Warning, "S0020"),
+        /// The same path is imported under the same name more than once in a
+        /// module; all but the first can be removed:
+        ///
+        /// ```slip
+        /// use Geo::Point;
+        /// use Geo::{Point, Line};
+        /// ```
+        DuplicateImport = ("duplicate-import", Level::Warning, "S0021"),
 
-        TypeReference = ("type-reference", Level::Info),
-        NonConstExpr = ("non-const-expr", Level::Error),
+        /// Information about a reference to a type.  Nothing emits this yet.
+        TypeReference = ("type-reference", Level::Info, "S0022"),
+        /// Something is used in a constant (or an enum discriminant) that
+        /// can't be evaluated while compiling, e.g. a function call:
+        ///
+        /// ```slip
+        /// const A: i32 = compute();
+        /// ```
+        NonConstExpr = ("non-const-expr", Level::Error, "S0023"),
+        /// A constant couldn't be evaluated, e.g. because an operator can't
+        /// be applied to its operands, or its value doesn't fit in its type:
+        ///
+        /// ```slip
+        /// const A: u8 = 256;
+        /// ```
+        ConstEvaluation = ("const-evaluation", Level::Error, "S0024"),
+        /// A constant depends on its own value, either directly or through
+        /// other constants:
+        ///
+        /// ```slip
+        /// const A: i32 = B + 1;
+        /// const B: i32 = A + 1;
+        /// ```
+        ConstCycle = ("const-cycle", Level::Error, "S0025"),
 
-        Generics = ("generics", Level::Error),
-        TypeRedefinition = ("type-redefinition", Level::Error),
-        FuncRedefinition = ("func-redefinition", Level::Error)
+        /// A generic type was given the wrong number of generics, or a
+        /// generic of a function couldn't be inferred from the arguments of
+        /// a call to it.  Generics with defaults can be left off:
+        ///
+        /// ```slip
+        /// struct Pair<A, B = i32> { a: A, b: B }
+        ///
+        /// fn f(p: Pair<i32, i32, i32>) { }
+        /// fn g(p: Pair) { }
+        /// ```
+        Generics = ("generics", Level::Error, "S0026"),
+        /// A generic is bound by something that isn't a known capability.
+        /// The known capabilities are `Equal`, `Compare`, `Hash`, `Default`,
+        /// `Add`, `Subtract`, `Multiply`, `Divide`, `Remainder`, `Negate`,
+        /// and `Index`:
+        ///
+        /// ```slip
+        /// fn sort<T: Sortable>(a: T) { }
+        /// ```
+        UnknownCapability = ("unknown-capability", Level::Error, "S0027"),
+        /// A type was given for a generic, but doesn't have a capability that
+        /// the generic is bound by, since it doesn't define the function the
+        /// capability requires (e.g. `<=>` for `Compare`):
+        ///
+        /// ```slip
+        /// struct Point { x: i32, y: i32 }
+        /// struct Sorted<T: Compare> { value: T }
+        ///
+        /// fn f(s: Sorted<Point>) { }
+        /// ```
+        MissingCapability = ("missing-capability", Level::Error, "S0028"),
+        /// Two types (or constants) are defined at the same path.  Only the
+        /// first is used:
+        ///
+        /// ```slip
+        /// struct Point { x: i32 }
+        /// struct Point { y: i32 }
+        /// ```
+        TypeRedefinition = ("type-redefinition", Level::Error, "S0029"),
+        /// Two functions are defined at the same path.  Only the first is
+        /// used:
+        ///
+        /// ```slip
+        /// fn f(): i32 { return 1; }
+        /// fn f(): i32 { return 2; }
+        /// ```
+        FuncRedefinition = ("func-redefinition", Level::Error, "S0030"),
 
+        /// A function was called that isn't defined, either in the source or
+        /// by a dependency:
+        ///
+        /// ```slip
+        /// fn f(): i32 {
+        ///     return g();
+        /// }
+        /// ```
+        UnknownFunction = ("unknown-function", Level::Error, "S0031"),
+        /// A field was used that the struct doesn't have, either in an access
+        /// or when constructing it:
+        ///
+        /// ```slip
+        /// struct Point { x: i32, y: i32 }
+        ///
+        /// fn f(p: Point): i32 {
+        ///     return p.z;
+        /// }
+        /// ```
+        UnknownField = ("unknown-field", Level::Error, "S0032"),
+        /// A struct was constructed without giving every one of its fields:
+        ///
+        /// ```slip
+        /// struct Point { x: i32, y: i32 }
+        ///
+        /// fn f(): Point {
+        ///     return Point { x: 1 };
+        /// }
+        /// ```
+        MissingField = ("missing-field", Level::Error, "S0033"),
+        /// A struct was constructed with the same field given more than once:
+        ///
+        /// ```slip
+        /// struct Point { x: i32, y: i32 }
+        ///
+        /// fn f(): Point {
+        ///     return Point { x: 1, x: 2, y: 3 };
+        /// }
+        /// ```
+        DuplicateField = ("duplicate-field", Level::Error, "S0034"),
+        /// Something that isn't a struct was constructed as if it were one:
+        ///
+        /// ```slip
+        /// enum Shape { Empty }
+        ///
+        /// fn f(): Shape {
+        ///     return Shape { x: 1 };
+        /// }
+        /// ```
+        NotAStruct = ("not-a-struct", Level::Error, "S0035"),
+        /// A variant was used that the enum doesn't have:
+        ///
+        /// ```slip
+        /// enum Shape { Empty, Circle(i32) }
+        ///
+        /// fn f(): Shape {
+        ///     return Shape::Square;
+        /// }
+        /// ```
+        UnknownVariant = ("unknown-variant", Level::Error, "S0036"),
+        /// A variant was given a different number of values than it takes:
+        ///
+        /// ```slip
+        /// enum Shape { Empty, Circle(i32) }
+        ///
+        /// fn f(): Shape {
+        ///     return Shape::Circle(1, 2);
+        /// }
+        /// ```
+        VariantArity = ("variant-arity", Level::Error, "S0037"),
+        /// A value has a different type than the one that's expected where
+        /// it's used, e.g. as an enum discriminant or a constant's value:
+        ///
+        /// ```slip
+        /// const A: i32 = 1.5;
+        /// ```
+        MismatchedType = ("mismatched-type", Level::Error, "S0038"),
+        /// An argument to a function has a different type than the
+        /// parameter it's given for:
+        ///
+        /// ```slip
+        /// fn f(a: i32): i32 { return a; }
+        /// fn g(): i32 { return f(1.5); }
+        /// ```
+        MismatchedArgument = ("mismatched-argument", Level::Error, "S0039"),
+        /// A function returns a value of a different type than its return
+        /// type, returns a value when it has no return type, or returns no
+        /// value when it has one:
+        ///
+        /// ```slip
+        /// fn f(): i32 {
+        ///     return 1.5;
+        /// }
+        /// ```
+        MismatchedReturn = ("mismatched-return", Level::Error, "S0040"),
+        /// A `let` with a type was given a value of a different type:
+        ///
+        /// ```slip
+        /// fn f(): i32 {
+        ///     let a: i32 = 1.5;
+        ///     return a;
+        /// }
+        /// ```
+        MismatchedLet = ("mismatched-let", Level::Error, "S0041"),
+        /// The condition of an `if` or `while` isn't a `bool`:
+        ///
+        /// ```slip
+        /// fn f(a: i32) {
+        ///     if a { }
+        /// }
+        /// ```
+        MismatchedCondition = ("mismatched-condition", Level::Error, "S0042"),
+        /// A function was called with a different number of arguments than
+        /// it takes:
+        ///
+        /// ```slip
+        /// fn f(a: i32): i32 { return a; }
+        /// fn g(): i32 { return f(1, 2); }
+        /// ```
+        ArgumentCount = ("argument-count", Level::Error, "S0043"),
+        /// Something was referenced from outside of the module it's defined
+        /// in, but it (or a module around it) isn't marked `export`:
+        ///
+        /// ```slip
+        /// module Geo {
+        ///     struct Point { x: i32, y: i32 }
+        /// }
+        ///
+        /// fn f(p: Geo::Point) { }
+        /// ```
+        NotExported = ("not-exported", Level::Error, "S0044"),
+        /// An operator was used on a type that doesn't define it, or that
+        /// defines it with a different number of operands:
+        ///
+        /// ```slip
+        /// struct Point { x: i32, y: i32 }
+        ///
+        /// fn f(a: Point, b: Point): Point {
+        ///     return a + b;
+        /// }
+        /// ```
+        UnknownOperator = ("unknown-operator", Level::Error, "S0045"),
+        /// An operator could refer to more than one definition for the types
+        /// of its operands.  With `operator-trace`, each of them is marked.
+        AmbiguousOperator = ("ambiguous-operator", Level::Error, "S0046"),
+        /// With an ambiguous operator, marks each of the definitions that it
+        /// could have referred to.  This is off unless it's asked for.
+        PossibleOperator = ("operator-trace.possible", Level::Never, "S0047"),
+        /// With an ambiguous operator, marks the definition that it was taken
+        /// to refer to.  This is off unless it's asked for.
+        AcceptedOperator = ("operator-trace.accepted", Level::Never, "S0048"),
+        /// The source uses something that the compiler doesn't support yet,
+        /// e.g. an enum with both discriminants and payloads:
+        ///
+        /// ```slip
+        /// enum Shape { Empty = 1, Circle(i32) }
+        /// ```
+        Unsupported = ("unsupported", Level::Error, "S0049"),
 
+        /// An attribute on an item isn't one that the compiler knows.  The
+        /// known attributes are `allow`, `warn`, and `deny`:
+        ///
+        /// ```slip
+        /// #[ignore(unused-local)]
+        /// fn f() { }
+        /// ```
+        UnknownAttribute = ("unknown-attribute", Level::Warning, "S0050"),
+        /// A name given to `allow`, `warn`, or `deny` (whether as an
+        /// attribute, or on the command line) isn't the short name of any
+        /// diagnostic, or of a group of them:
+        ///
+        /// ```slip
+        /// #[allow(unused-locals)]
+        /// fn f() { }
+        /// ```
+        UnknownDiagnostic = ("unknown-diagnostic", Level::Warning, "S0051")
+    }
+}
+
+impl Name {
+    /// Finds the diagnostic with the given short name, if there is one.
+    pub fn from_short(short: &str) -> Option<Name> {
+        Name::all()
+            .iter()
+            .cloned()
+            .find(|name| name.short() == short)
+    }
+
+    /// Finds the diagnostic that the given code or short name refers to.
+    /// Codes are matched regardless of case.  If it doesn't refer to any,
+    /// the error lists every short name that does.
+    pub fn find(name: &str) -> Result<Name, Error> {
+        Name::all()
+            .iter()
+            .cloned()
+            .find(|known| known.code().eq_ignore_ascii_case(name) || known.short() == name)
+            .ok_or_else(|| Name::unknown(name))
+    }
+
+    /// The long-form explanation of the diagnostic, with an example of the
+    /// source that causes it (where there is one).  This is taken from the
+    /// documentation on the variant.
+    pub fn explanation(&self) -> String {
+        let lines = self.docs().lines();
+        let lines = lines.map(|line| line.strip_prefix(' ').unwrap_or(line));
+        lines.collect::<Vec<_>>().join("\n").trim().to_string()
+    }
+
+    /// Finds the diagnostics that the given short name refers to.  Short
+    /// names are grouped by dots, and a name refers to every diagnostic in
+    /// the group it names as well as itself; e.g., `type-trace` refers to
+    /// `type-trace`, `type-trace.possible`, and `type-trace.accepted`.
+    pub fn matching(short: &str) -> impl Iterator<Item = Name> + '_ {
+        Name::all().iter().cloned().filter(move |name| {
+            let name = name.short();
+            name == short || (name.starts_with(short) && name[short.len()..].starts_with('.'))
+        })
+    }
+
+    /// Finds the diagnostics that the given short name refers to, as with
+    /// [`matching()`]; if it doesn't refer to any, the error lists every
+    /// short name that does.
+    pub fn lookup(short: &str) -> Result<Vec<Name>, Error> {
+        let names = Name::matching(short).collect::<Vec<_>>();
+        if names.is_empty() {
+            Err(Name::unknown(short))
+        } else {
+            Ok(names)
+        }
+    }
+
+    fn unknown(name: &str) -> Error {
+        Error::UnknownDiagnosticError {
+            name: name.to_string(),
+            expected: Name::all()
+                .iter()
+                .map(Name::short)
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
     }
 }
 
@@ -74,3 +534,34 @@ diag_variant! {
 // Error,
 // Panic,
 // Off,
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_documented() {
+        let mut codes = HashSet::new();
+        let mut shorts = HashSet::new();
+        for name in Name::all() {
+            let code = name.code();
+            assert!(!name.explanation().is_empty(), "{} is undocumented", code);
+            assert!(
+                code.len() == 5 && code.starts_with('S') && code[1..].parse::<u16>().is_ok(),
+                "{:?} has the invalid code {}",
+                name,
+                code
+            );
+            assert!(codes.insert(code), "{} is reused", code);
+            assert!(shorts.insert(name.short()), "{} is reused", name);
+        }
+
+        assert_eq!(Name::find("S0010").unwrap(), Name::UnusedLocal);
+        assert_eq!(Name::find("s0010").unwrap(), Name::UnusedLocal);
+        assert_eq!(Name::find("unused-local").unwrap(), Name::UnusedLocal);
+        assert!(Name::find("S9999").is_err());
+        let explanation = Name::UnusedLocal.explanation();
+        assert!(explanation.contains("```slip\nfn f(): i32 {\n"));
+    }
+}
@@ -1,6 +1,29 @@
 use std::io::{Result as IoResult, Write};
 use std::ops::{Deref, DerefMut};
 use term::{color, Attr, Result, Terminal};
+
+/// How emissions are written out as they're emitted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Format {
+    /// Human-readable text, colored if stderr is a terminal.
+    #[default]
+    Text,
+    /// One JSON object per emission, per line; see the `json` module for
+    /// the layout.
+    Json,
+}
+
+impl Format {
+    /// Writes out an error that stops the compile, but was never an
+    /// emission, to stderr in this format.
+    pub fn fatal(self, message: &str) {
+        match self {
+            Format::Text => eprintln!("{}", message),
+            Format::Json => super::json::fatal(message, ::std::io::stderr()).unwrap(),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
 /// This allows us to wrap a non-terminal target with stubbed out terminal
 /// behavior, ignoring all terminal requests.  This is only really good if
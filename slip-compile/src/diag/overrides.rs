@@ -1,6 +1,13 @@
 use super::{Level, Name};
 use std::collections::HashMap;
 
+/// The levels that diagnostics have been set to, in place of their default
+/// levels.  The first frame holds the overrides that apply everywhere (e.g.
+/// from the command line); each frame pushed on top of it holds all of the
+/// overrides for a scope in the source.  Since the scopes aren't always
+/// processed in a nested manner - an item can be processed while another
+/// is, if it's needed by it - only the topmost frame is applied on top of
+/// the first.
 #[derive(Debug, Clone, Default)]
 pub(super) struct Overrides {
     overrides: Vec<HashMap<Name, Level>>,
@@ -17,24 +24,22 @@ impl Overrides {
 
     pub fn push(&mut self) {
         self.overrides.push(HashMap::new());
+        self.rebuild();
     }
 
     pub fn pop(&mut self) {
         if self.overrides.len() == 1 {
-            let empty = self.overrides[0].is_empty();
             self.overrides[0].clear();
-            if !empty {
-                self.rebuild();
-            }
-        } else if let Some(result) = self.overrides.pop() {
-            if !result.is_empty() {
-                self.rebuild();
-            }
+        } else {
+            self.overrides.pop();
         }
+        // Popping a frame, even an empty one, can uncover the one below it.
+        self.rebuild();
     }
 
     pub fn insert(&mut self, diag: Name, level: Level) {
         self.overrides.last_mut().unwrap().insert(diag, level);
+        self.rebuild();
     }
 
     pub fn lookup(&self, diag: Name) -> Level {
@@ -42,7 +47,9 @@ impl Overrides {
     }
 
     pub fn rebuild(&mut self) {
-        self.cache = self.overrides.iter().fold(HashMap::new(), |mut acc, val| {
+        let first = self.overrides.first().into_iter();
+        let last = self.overrides.last().filter(|_| self.overrides.len() > 1);
+        self.cache = first.chain(last).fold(HashMap::new(), |mut acc, val| {
             acc.extend(val.iter());
             acc
         })
@@ -0,0 +1,157 @@
+//! Sinks are where emissions go once they're emitted.  The diagnostics hold
+//! one or more of them, and hand each active emission to every one of them,
+//! in order.  The emissions are still kept in the diagnostics' own list
+//! regardless of the sinks.
+
+use super::emission::Emission;
+use super::output::NonTerminal;
+use super::SourceMap;
+use std::fmt::Debug;
+use std::io::{Stderr, Write};
+use std::sync::{Arc, Mutex};
+
+/// Somewhere for emissions to go.  Sinks are only given the emissions that
+/// are active; the sources are given so that the spans of the emissions can
+/// be looked up.
+pub trait DiagnosticSink: Debug + Send {
+    /// Handles a new emission.
+    fn emit(&mut self, emission: &Emission, sources: &SourceMap<'_>);
+
+    /// Finishes handling any emissions that have been held back, e.g. by
+    /// flushing the output.
+    fn flush(&mut self, sources: &SourceMap<'_>) {
+        let _ = sources;
+    }
+}
+
+/// Writes emissions to stderr as text, colored if stderr is a terminal.
+/// This is the default sink.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TerminalSink;
+
+impl DiagnosticSink for TerminalSink {
+    fn emit(&mut self, emission: &Emission, sources: &SourceMap<'_>) {
+        if let Some(mut term) = ::term::stderr() {
+            emission.emit(sources, &mut *term).unwrap();
+        } else {
+            emission.emit(sources, &mut NonTerminal::stderr()).unwrap();
+        }
+    }
+}
+
+/// Writes emissions as text, without any coloring, to the given output.
+#[derive(Debug)]
+pub struct PlainSink<W: Write>(NonTerminal<W>);
+
+impl<W: Write> PlainSink<W> {
+    pub fn new(out: W) -> PlainSink<W> {
+        PlainSink(NonTerminal::new(out))
+    }
+
+    pub fn into_inner(self) -> W {
+        self.0.into_inner()
+    }
+}
+
+impl PlainSink<Stderr> {
+    pub fn stderr() -> PlainSink<Stderr> {
+        PlainSink(NonTerminal::stderr())
+    }
+}
+
+impl<W: Write + Debug + Send> DiagnosticSink for PlainSink<W> {
+    fn emit(&mut self, emission: &Emission, sources: &SourceMap<'_>) {
+        emission.emit(sources, &mut self.0).unwrap();
+    }
+}
+
+/// Writes emissions as JSON Lines to the given output; see the `json`
+/// module for the layout.
+#[derive(Debug)]
+pub struct JsonSink<W: Write>(W);
+
+impl<W: Write> JsonSink<W> {
+    pub fn new(out: W) -> JsonSink<W> {
+        JsonSink(out)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl JsonSink<Stderr> {
+    pub fn stderr() -> JsonSink<Stderr> {
+        JsonSink::new(::std::io::stderr())
+    }
+}
+
+impl<W: Write + Debug + Send> DiagnosticSink for JsonSink<W> {
+    fn emit(&mut self, emission: &Emission, sources: &SourceMap<'_>) {
+        super::json::emit(emission, sources, &mut self.0).unwrap();
+    }
+
+    fn flush(&mut self, _: &SourceMap<'_>) {
+        self.0.flush().unwrap();
+    }
+}
+
+/// Keeps the emissions in memory.  This can be cloned before it's given to
+/// the diagnostics; all of the clones share the same emissions, so that
+/// they can be read afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct MemorySink(Arc<Mutex<Vec<Emission>>>);
+
+impl MemorySink {
+    pub fn new() -> MemorySink {
+        MemorySink::default()
+    }
+
+    /// The emissions that have been given to the sink so far.
+    pub fn emissions(&self) -> Vec<Emission> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl DiagnosticSink for MemorySink {
+    fn emit(&mut self, emission: &Emission, _: &SourceMap<'_>) {
+        self.0.lock().unwrap().push(emission.clone());
+    }
+}
+
+/// Ignores all emissions, so that they're only collected into the
+/// diagnostics' own list.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CollectSink;
+
+impl DiagnosticSink for CollectSink {
+    fn emit(&mut self, _: &Emission, _: &SourceMap<'_>) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::diag::{Diagnostics, Name, Span};
+
+    #[test]
+    fn test_memory_sink() {
+        let memory = MemorySink::new();
+        let mut diag = Diagnostics::new().sink(memory.clone());
+        diag.report(
+            Emission::new(
+                Name::TypeRedefinition,
+                Span::default(),
+                "item A already defined",
+            )
+            .with_label(Span::default(), "previous definition here"),
+        );
+        diag.emit(Name::TypeTrace, Span::default(), "not active");
+
+        let emissions = memory.emissions();
+        assert_eq!(emissions.len(), 1);
+        assert_eq!(emissions[0].message(), "item A already defined");
+        assert_eq!(emissions[0].labels().len(), 1);
+        assert_eq!(diag.emissions().len(), 2);
+        assert_eq!(diag.emissions()[1].name(), Name::TypeTrace);
+    }
+}
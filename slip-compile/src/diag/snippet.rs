@@ -0,0 +1,332 @@
+//! Renders the parts of the source that an emission points at.  The spans
+//! of an emission are grouped by the source they're in, and each group is
+//! written as one snippet, headed by the location of its first span:
+//!
+//! ```text
+//! error[S0029]: item A already defined
+//!  --> main.slip:3:1
+//!   |
+//! 1 | struct A { x: i32 }
+//!   | ------------------- previous definition here
+//! ...
+//! 3 | struct A { y: i32 }
+//!   | ^^^^^^^^^^^^^^^^^^^
+//! ```
+//!
+//! The primary span is marked with `^`, and the others with `-`.  A span
+//! over more than one line is marked on each of its lines, and if it's
+//! long, only the lines at the start and the end of it are shown.  Tabs are
+//! expanded, so that the marks line up with the line above them.
+
+use super::{Source, SourceId, SourceMap, Span};
+use std::collections::BTreeSet;
+use std::io::{Result as IoResult, Write};
+use term::{color, Terminal};
+
+/// The number of columns that a tab is expanded to.
+const TAB: usize = 4;
+
+/// The most lines of a span that are shown; past this, the lines in the
+/// middle of it are left out.
+const LINES: usize = 4;
+
+/// A span to mark within a snippet.
+#[derive(Debug, Copy, Clone)]
+pub(super) struct Mark<'a> {
+    pub span: Span,
+    pub label: Option<&'a str>,
+    pub primary: bool,
+}
+
+/// The number of columns needed for the line numbers of the given marks.
+pub(super) fn gutter(marks: &[Mark<'_>]) -> usize {
+    marks
+        .iter()
+        .map(|mark| mark.span.end().line().to_string().len())
+        .max()
+        .unwrap_or(1)
+}
+
+/// Writes a snippet for each source that the marks are in, in the order
+/// that the sources first appear in.  Marks that aren't in any source (or
+/// whose positions aren't valid) can't be shown, and are skipped.
+pub(super) fn render<T, TO>(
+    out: &mut T,
+    sources: &SourceMap<'_>,
+    marks: &[Mark<'_>],
+    gutter: usize,
+    color: color::Color,
+) -> IoResult<()>
+where
+    TO: Write,
+    T: Terminal<Output = TO> + Send + ?Sized,
+{
+    let mut groups: Vec<(SourceId, Vec<Mark<'_>>)> = vec![];
+    let shown = marks.iter().filter(|mark| mark.span.start().valid());
+    for mark in shown {
+        let id = match mark.span.source() {
+            Some(id) => id,
+            None => continue,
+        };
+        match groups.iter_mut().find(|(group, _)| *group == id) {
+            Some((_, group)) => group.push(*mark),
+            None => groups.push((id, vec![*mark])),
+        }
+    }
+
+    for (index, (id, group)) in groups.iter().enumerate() {
+        let arrow = if index == 0 { "-->" } else { ":::" };
+        let source = sources.get(*id);
+        out.fg(color::BRIGHT_BLUE).unwrap();
+        write!(out, "{:1$}{2} ", "", gutter, arrow)?;
+        out.reset().unwrap();
+        writeln!(out, "{}", group[0].span.annotate(sources))?;
+
+        if let Some(source) = source.filter(|source| source.content().is_some()) {
+            lines(out, source, group, gutter, color)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the lines that the marks are on, each followed by the marks on
+/// it.
+fn lines<T, TO>(
+    mut out: &mut T,
+    source: &Source<'_>,
+    marks: &[Mark<'_>],
+    gutter: usize,
+    color: color::Color,
+) -> IoResult<()>
+where
+    TO: Write,
+    T: Terminal<Output = TO> + Send + ?Sized,
+{
+    let numbers = marks
+        .iter()
+        .flat_map(|mark| shown(mark.span))
+        .filter(|number| *number <= source.line_count())
+        .collect::<BTreeSet<_>>();
+
+    out.fg(color::BRIGHT_BLUE).unwrap();
+    writeln!(&mut out, "{:1$} |", "", gutter)?;
+    out.reset().unwrap();
+
+    let mut previous = None;
+    for number in numbers {
+        let line = source.line(number).unwrap_or_default();
+        out.fg(color::BRIGHT_BLUE).unwrap();
+        if previous.is_some_and(|previous| number > previous + 1) {
+            writeln!(&mut out, "...")?;
+        }
+        write!(&mut out, "{:>1$} | ", number, gutter)?;
+        out.reset().unwrap();
+        writeln!(&mut out, "{}", expand(line).trim_end())?;
+
+        let blank = line.trim().is_empty();
+        let marked = marks.iter().filter(|mark| {
+            let span = mark.span;
+            let inner = span.start().line() < number && number < span.end().line();
+            covers(span, number) && !(blank && inner)
+        });
+        for mark in marked {
+            let (start, end) = columns(mark.span, number, line);
+            let (symbol, color) = if mark.primary {
+                ('^', color)
+            } else {
+                ('-', color::BRIGHT_BLUE)
+            };
+            out.fg(color::BRIGHT_BLUE).unwrap();
+            write!(&mut out, "{:1$} | ", "", gutter)?;
+            out.fg(color).unwrap();
+            let marker = symbol.to_string().repeat(end - start);
+            write!(&mut out, "{:1$}{2}", "", start, marker)?;
+            match mark.label {
+                Some(label) if number == mark.span.end().line() => {
+                    writeln!(&mut out, " {}", label)?
+                }
+                _ => writeln!(&mut out)?,
+            }
+            out.reset().unwrap();
+        }
+
+        previous = Some(number);
+    }
+
+    Ok(())
+}
+
+/// The line numbers of the span that are shown.  If the span is over more
+/// than [`LINES`] lines, only half of that are shown from each end of it.
+fn shown(span: Span) -> Vec<usize> {
+    let (start, end) = (span.start().line(), span.end().line());
+    if end - start < LINES {
+        (start..=end).collect()
+    } else {
+        let half = LINES / 2;
+        (start..start + half).chain(end + 1 - half..=end).collect()
+    }
+}
+
+fn covers(span: Span, line: usize) -> bool {
+    span.start().line() <= line && line <= span.end().line()
+}
+
+/// The display columns (from zero) that the span covers on the given line,
+/// as a half-open range.  The first line of the span is covered from where
+/// it starts, and the last line until where it ends; the lines in between
+/// are covered from their first non-blank character to their end.  At
+/// least one column is always covered, so that empty spans (e.g. where
+/// something should be inserted) can still be seen.
+fn columns(span: Span, number: usize, line: &str) -> (usize, usize) {
+    let indent = line.len() - line.trim_start().len();
+    let start = if number == span.start().line() {
+        span.start().column().saturating_sub(1)
+    } else {
+        indent
+    };
+    let end = if number == span.end().line() {
+        span.end().column().saturating_sub(1)
+    } else {
+        line.len()
+    };
+
+    let start = width(line, start);
+    let end = width(line, end);
+    (start, end.max(start + 1))
+}
+
+/// The display width of the first `bytes` bytes of the line, with tabs
+/// expanded.  Positions past the end of the line are one column each.
+fn width(line: &str, bytes: usize) -> usize {
+    let within = bytes.min(line.len());
+    let prefix = line.get(..within).unwrap_or(line);
+    let columns = prefix
+        .chars()
+        .map(|c| if c == '\t' { TAB } else { 1 })
+        .sum::<usize>();
+    columns + (bytes - within)
+}
+
+fn expand(line: &str) -> String {
+    line.replace('\t', &" ".repeat(TAB))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::diag::output::NonTerminal;
+    use crate::diag::{Emission, Name, Position};
+
+    const MAIN: &str = "struct A { x: i32 }\n\nstruct B {\n\ty: i32,\n\tz: Missing,\n\n\tw: i32,\n\tv: i32\n}\n\n\nfn f(): i32 {\n\treturn 1\n}\n";
+    const OTHER: &str = "use Geo::Point;\n";
+
+    fn sources() -> SourceMap<'static> {
+        let mut sources = SourceMap::new();
+        sources.push("main.slip", Some(MAIN));
+        sources.push("other.slip", Some(OTHER));
+        sources
+    }
+
+    /// The span from the given line and column to the other, in the given
+    /// source, with the offsets worked out from the content.
+    fn span(source: usize, (sl, sc): (usize, usize), (el, ec): (usize, usize)) -> Span {
+        let content = [MAIN, OTHER][source];
+        let at = |line, column| {
+            let before = content.split('\n').take(line - 1).map(|l| l.len() + 1);
+            Position::new(before.sum::<usize>() + column - 1, line, column)
+        };
+        Span::new(at(sl, sc), at(el, ec), Some(SourceId(source)))
+    }
+
+    fn render(emission: Emission) -> String {
+        let mut out = NonTerminal::new(vec![]);
+        emission.emit(&sources(), &mut out).unwrap();
+        String::from_utf8(out.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_single_line() {
+        let emission = Emission::new(
+            Name::TypeRedefinition,
+            span(0, (12, 1), (14, 2)),
+            "item f already defined",
+        )
+        .with_label(span(0, (1, 8), (1, 9)), "previous definition here")
+        .with_note("items are looked up by path");
+        let expected = "\
+error[S0029]: item f already defined
+  --> main.slip:12:1
+   |
+ 1 | struct A { x: i32 }
+   |        - previous definition here
+...
+12 | fn f(): i32 {
+   | ^^^^^^^^^^^^^
+13 |     return 1
+   |     ^^^^^^^^
+14 | }
+   | ^
+   = note: items are looked up by path
+
+";
+        assert_eq!(render(emission), expected);
+    }
+
+    #[test]
+    fn test_multi_line() {
+        // The span is long enough that its middle is left out, and the tabs
+        // are expanded both in the lines and under them.
+        let emission = Emission::new(
+            Name::UnknownType,
+            span(0, (4, 2), (9, 2)),
+            "unidentified type Missing",
+        )
+        .with_label(span(0, (5, 5), (5, 12)), "here");
+        let expected = "\
+error[S0013]: unidentified type Missing
+ --> main.slip:4:2
+  |
+4 |     y: i32,
+  |     ^^^^^^^
+5 |     z: Missing,
+  |     ^^^^^^^^^^^
+  |        ------- here
+...
+8 |     v: i32
+  |     ^^^^^^
+9 | }
+  | ^
+
+";
+        assert_eq!(render(emission), expected);
+    }
+
+    #[test]
+    fn test_multi_source() {
+        // An insertion after the end of a line is still marked.
+        let emission = Emission::new(
+            Name::UnexpectedToken,
+            span(0, (13, 10), (13, 10)),
+            "found token RightBrace, expected one of [Semicolon]",
+        )
+        .with_label(span(1, (1, 5), (1, 15)), "imported here");
+        let expected = "\
+panic[S0007]: found token RightBrace, expected one of [Semicolon]
+  --> main.slip:13:10
+   |
+13 |     return 1
+   |             ^
+  ::: other.slip:1:5
+   |
+ 1 | use Geo::Point;
+   |     ---------- imported here
+
+";
+        assert_eq!(render(emission), expected);
+        // Spans without a source only get the message.
+        let emission = Emission::new(Name::TestError, Span::default(), "no source");
+        assert_eq!(render(emission), "error[S0004]: no source\n\n");
+    }
+}
@@ -1,13 +1,125 @@
+//! The sources that a program is made up of.  Every source is owned by the
+//! [`SourceMap`], and referred to everywhere else by its [`SourceId`].  A
+//! source is either a file, loaded from disk, or virtual - given directly,
+//! e.g. generated code, or code given on the command line.  Only files can
+//! have fixes written back to them.
+//!
+//! When a source is added, the offset that each of its lines starts at is
+//! worked out once, so that an offset can be turned into a line and column
+//! (and back) with a binary search, rather than by scanning the content.
+//!
+//! Generated code can say where it was generated from with `#line`
+//! directives, as in C:
+//!
+//! ```text
+//! #line 40 "shapes.in"
+//! ```
+//!
+//! which makes the line after the directive be reported as line 40 of
+//! `shapes.in`, and so on from there.  The name can be left out to keep
+//! the name that was last given.  This only changes the locations that
+//! diagnostics are reported at; spans (and so the snippets) always refer
+//! to the source itself.
+
+use super::Position;
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Result as IoResult;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SourceId(pub(super) usize);
 
+/// Owns every source, and hands out the ids that refer to them.
+#[derive(Debug, Default)]
+pub struct SourceMap<'c> {
+    sources: HashMap<SourceId, Source<'c>>,
+    /// This contains the next source id.  Since we need to make sure that
+    /// this is unique across all sources, we'll just use a counter.
+    next: usize,
+}
+
+impl<'c> SourceMap<'c> {
+    pub fn new() -> SourceMap<'c> {
+        SourceMap::default()
+    }
+
+    /// Reads the file at the given path, and adds it as a source.  The
+    /// source is named by the path.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> IoResult<SourceId> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let name = path.display().to_string();
+        Ok(self.insert(name.into(), Some(content.into()), Some(path.to_path_buf())))
+    }
+
+    /// Adds a virtual source, with the given name and content (if any).
+    /// The name is only used to refer to the source in diagnostics.
+    pub fn push(
+        &mut self,
+        name: impl Into<Cow<'c, str>>,
+        content: Option<impl Into<Cow<'c, str>>>,
+    ) -> SourceId {
+        self.insert(name.into(), content.map(Into::into), None)
+    }
+
+    pub fn get(&self, id: SourceId) -> Option<&Source<'c>> {
+        self.sources.get(&id)
+    }
+
+    /// Reports the lines of the given source from `line` on as though they
+    /// were from line `to` on of the source named `name`; see the module
+    /// documentation.
+    pub fn remap(&mut self, id: SourceId, line: usize, name: Option<String>, to: usize) {
+        if let Some(source) = self.sources.get_mut(&id) {
+            source.remap(line, name, to);
+        }
+    }
+
+    fn insert(
+        &mut self,
+        name: Cow<'c, str>,
+        content: Option<Cow<'c, str>>,
+        path: Option<PathBuf>,
+    ) -> SourceId {
+        let id = SourceId(self.next);
+        self.next += 1;
+        let lines = content.as_deref().map(starts).unwrap_or_default();
+        let source = Source {
+            id,
+            name,
+            content,
+            path,
+            lines,
+            remaps: vec![],
+        };
+        self.sources.insert(id, source);
+        id
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Source<'c> {
-    pub(super) id: SourceId,
-    pub(super) name: Cow<'c, str>,
-    pub(super) content: Option<Cow<'c, str>>,
+    id: SourceId,
+    name: Cow<'c, str>,
+    content: Option<Cow<'c, str>>,
+    /// The path that the source was loaded from, if it's a file.
+    path: Option<PathBuf>,
+    /// The offset that each line starts at; the first line always starts
+    /// at zero.  This is empty if there's no content.
+    lines: Vec<usize>,
+    /// The `#line` directives in the source, in order of the lines they
+    /// apply from.
+    remaps: Vec<Remap>,
+}
+
+/// Where the lines of a source are reported from, starting at a line.
+#[derive(Debug, Clone)]
+struct Remap {
+    line: usize,
+    name: Option<String>,
+    to: usize,
 }
 
 impl<'c> Source<'c> {
@@ -20,6 +132,117 @@ impl<'c> Source<'c> {
     }
 
     pub fn content(&self) -> Option<&str> {
-        self.content.as_ref().map(AsRef::as_ref)
+        self.content.as_deref()
+    }
+
+    /// The path that the source was loaded from, or `None` if it's a
+    /// virtual source.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The number of lines in the source.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The text of the given line (from one), without its line ending.
+    pub fn line(&self, number: usize) -> Option<&str> {
+        let content = self.content()?;
+        let start = *self.lines.get(number.checked_sub(1)?)?;
+        let end = self.lines.get(number).copied().unwrap_or(content.len());
+        let line = &content[start..end];
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+
+    /// The position of the given offset, with its line and (byte) column
+    /// counted from one.  Offsets past the end of the content are on the
+    /// last line.
+    pub fn position(&self, offset: usize) -> Position {
+        let index = self
+            .lines
+            .partition_point(|start| *start <= offset)
+            .saturating_sub(1);
+        let start = self.lines.get(index).copied().unwrap_or(0);
+        Position::new(offset, index + 1, offset - start + 1)
+    }
+
+    /// The offset of the given line and column, if they're within the
+    /// content.  The column can be just past the end of the line, where
+    /// something would be inserted.
+    pub fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let text = self.line(line)?;
+        let column = column.checked_sub(1).filter(|c| *c <= text.len())?;
+        Some(self.lines[line - 1] + column)
+    }
+
+    /// The name and line that the given line is reported at, once the
+    /// `#line` directives before it are taken into account.
+    pub fn locate(&self, line: usize) -> (&str, usize) {
+        let index = self.remaps.partition_point(|remap| remap.line <= line);
+        match index.checked_sub(1).map(|index| &self.remaps[index]) {
+            Some(remap) => {
+                let name = remap.name.as_deref().unwrap_or(&self.name);
+                (name, remap.to + (line - remap.line))
+            }
+            None => (&self.name, line),
+        }
+    }
+
+    fn remap(&mut self, line: usize, name: Option<String>, to: usize) {
+        let index = self.remaps.partition_point(|remap| remap.line <= line);
+        // Without a name, the name that was last given is kept.
+        let name = name.or_else(|| {
+            let previous = index.checked_sub(1).map(|index| &self.remaps[index]);
+            previous.and_then(|remap| remap.name.clone())
+        });
+        self.remaps.insert(index, Remap { line, name, to });
+    }
+}
+
+/// The offsets that each line of the content starts at.
+fn starts(content: &str) -> Vec<usize> {
+    let ends = content.match_indices('\n').map(|(index, _)| index + 1);
+    let mut lines = std::iter::once(0).chain(ends).collect::<Vec<_>>();
+    // A line ending at the very end doesn't start another line.
+    if lines.len() > 1 && lines.last() == Some(&content.len()) {
+        lines.pop();
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_source_lines() {
+        let mut map = SourceMap::new();
+        let id = map.push("main.slip", Some("struct A {\r\n\tx: i32\n}\n"));
+        let source = map.get(id).unwrap();
+        assert_eq!(source.line_count(), 3);
+        assert_eq!(source.line(1), Some("struct A {"));
+        assert_eq!(source.line(2), Some("\tx: i32"));
+        assert_eq!(source.line(4), None);
+        assert_eq!(source.position(0), Position::new(0, 1, 1));
+        assert_eq!(source.position(13), Position::new(13, 2, 2));
+        assert_eq!(source.position(20), Position::new(20, 3, 1));
+        assert_eq!(source.offset(2, 2), Some(13));
+        assert_eq!(source.offset(2, 8), Some(19));
+        assert_eq!(source.offset(2, 9), None);
+        assert_eq!(source.path(), None);
+    }
+
+    #[test]
+    fn test_source_remap() {
+        let mut map = SourceMap::new();
+        let id = map.push("out.slip", Some(""));
+        map.remap(id, 3, Some("shapes.in".into()), 40);
+        map.remap(id, 10, None, 1);
+        let source = map.get(id).unwrap();
+        assert_eq!(source.locate(2), ("out.slip", 2));
+        assert_eq!(source.locate(5), ("shapes.in", 42));
+        assert_eq!(source.locate(12), ("shapes.in", 3));
     }
 }
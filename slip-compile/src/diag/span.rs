@@ -1,4 +1,4 @@
-use super::SourceId;
+use super::{SourceId, SourceMap};
 use std::fmt;
 use std::ops::{BitOr, BitOrAssign};
 
@@ -13,8 +13,11 @@ pub struct Span {
 }
 
 /// The location in the source file of a specific segment of text,
-/// annotated with the file name information.
-pub struct SourceSpan<'s>(Span, Option<&'s str>);
+/// annotated with the file name information.  This is displayed as the
+/// name of the source and the line and column that the span starts at,
+/// e.g. `main.slip:3:14`, after any `#line` directives in the source are
+/// taken into account.
+pub struct SourceSpan<'s>(Span, Option<(&'s str, usize)>);
 
 impl Span {
     pub fn new(start: Position, end: Position, source: Option<SourceId>) -> Span {
@@ -40,15 +43,15 @@ impl Span {
         self.source
     }
 
-    // pub fn annotate<'s>(&self, set: &'s super::DiagnosticSet) -> SourceSpan<'s> {
-    //     SourceSpan(
-    //         *self,
-    //         self.2
-    //             .as_ref()
-    //             .and_then(|s| set.sources.get(s))
-    //             .map(|s| &s.name[..]),
-    //     )
-    // }
+    /// Annotates the span with the name of the source it's in, if it's in
+    /// any of the given sources.
+    pub fn annotate<'s>(&self, sources: &'s SourceMap<'_>) -> SourceSpan<'s> {
+        let location = self
+            .source
+            .and_then(|source| sources.get(source))
+            .map(|source| source.locate(self.start.line()));
+        SourceSpan(*self, location)
+    }
 
     pub fn update(&mut self, other: &Self) {
         let start = self.start.lower_or(&other.start);
@@ -56,6 +59,7 @@ impl Span {
 
         self.start = start;
         self.end = end;
+        self.source = self.source.or(other.source);
     }
 }
 
@@ -97,15 +101,8 @@ impl fmt::Display for Span {
 
 impl<'s> fmt::Display for SourceSpan<'s> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}:{}..{}:{} in file {}",
-            self.0.start().line(),
-            self.0.start().column(),
-            self.0.end().line(),
-            self.0.end().column(),
-            self.1.unwrap_or("(missing)")
-        )
+        let (name, line) = self.1.unwrap_or(("(missing)", self.0.start().line()));
+        write!(f, "{}:{}:{}", name, line, self.0.start().column())
     }
 }
 
@@ -202,48 +199,25 @@ impl Position {
         !(self.line == 0 && self.column == 0)
     }
 
+    /// Whichever of the two positions comes first, ignoring either of them
+    /// if it isn't valid.  Positions are compared as a whole, by offset, so
+    /// that the line and column always stay together.
     fn lower_or(&self, other: &Self) -> Position {
-        if !self.valid() {
-            return *other;
-        }
-        let offset = if self.offset < other.offset {
-            self.offset
-        } else {
-            other.offset
-        };
-        let line = if self.line < other.line {
-            self.line
+        if !self.valid() || (other.valid() && other.offset < self.offset) {
+            *other
         } else {
-            other.line
-        };
-        let column = if self.column < other.column {
-            self.column
-        } else {
-            other.column
-        };
-        Position::new(offset, line, column)
+            *self
+        }
     }
 
+    /// Whichever of the two positions comes last, ignoring either of them
+    /// if it isn't valid; see [`lower_or()`].
     fn upper_or(&self, other: &Self) -> Position {
-        if !self.valid() {
-            return *other;
-        }
-        let offset = if self.offset > other.offset {
-            self.offset
+        if !self.valid() || (other.valid() && other.offset > self.offset) {
+            *other
         } else {
-            other.offset
-        };
-        let line = if self.line > other.line {
-            self.line
-        } else {
-            other.line
-        };
-        let column = if self.column > other.column {
-            self.column
-        } else {
-            other.column
-        };
-        Position::new(offset, line, column)
+            *self
+        }
     }
 }
 
@@ -25,6 +25,21 @@ pub enum Error {
 
     #[fail(display = "encountered an io exception")]
     IoError(::std::io::Error),
+
+    #[fail(display = "invalid configuration: {}", _0)]
+    ConfigError(::serde_json::Error),
+
+    #[fail(
+        display = "unknown diagnostic `{}`, expected one of: {}",
+        name, expected
+    )]
+    UnknownDiagnosticError { name: String, expected: String },
+
+    #[fail(display = "unknown level `{}`, expected one of: {}", level, expected)]
+    UnknownLevelError { level: String, expected: String },
+
+    #[fail(display = "stopping after {} errors", errors)]
+    ErrorLimitError { errors: usize },
 }
 
 impl From<::std::io::Error> for Error {
@@ -32,3 +47,9 @@ impl From<::std::io::Error> for Error {
         Error::IoError(er)
     }
 }
+
+impl From<::serde_json::Error> for Error {
+    fn from(er: ::serde_json::Error) -> Error {
+        Error::ConfigError(er)
+    }
+}
@@ -0,0 +1,288 @@
+//! Loads the sources that a program is made up of.  Starting from the files
+//! given to the compiler, every `use` is mapped onto a file on disk, which is
+//! then loaded in turn, and so on until every import has been found.
+//!
+//! An import is mapped onto a file by its module path, relative to each of
+//! the directories in the search path; e.g. `use Some::Thing;` is looked for
+//! at `Some/Thing.slip`, and then at `Some.slip`, so that a file can contain
+//! either a single type or a whole module.  Files are not wrapped in a module
+//! of their own; they declare the modules they define, like any other file.
+//!
+//! Files can import each other in a cycle.  This is reported, but each file
+//! is still only loaded once.  Once everything is loaded, any import that
+//! refers to something that none of the sources define - and that none of
+//! the prebuilt modules being compiled against export - is reported as
+//! unresolved.
+
+use crate::diag::{DiagnosticSync, Name, SourceId, Span};
+use crate::error::Error;
+use crate::reduce::{Annotation, Scope};
+use crate::stream::TokenStream;
+use crate::syn::{Item, Node, Root, Use};
+use slip_typal::module::Module;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The extension of source files.
+pub const EXTENSION: &str = "slip";
+
+pub struct Loader<'c> {
+    set: DiagnosticSync<'c>,
+    search: Vec<PathBuf>,
+    roots: Vec<Root>,
+    /// The files that have been loaded, or are being loaded.
+    loaded: HashSet<PathBuf>,
+    /// The files that are currently being loaded, in the order they were
+    /// imported in; this is used to detect import cycles.
+    stack: Vec<PathBuf>,
+}
+
+impl<'c> Loader<'c> {
+    pub fn new(set: DiagnosticSync<'c>, search: Vec<PathBuf>) -> Loader<'c> {
+        Loader {
+            set,
+            search,
+            roots: vec![],
+            loaded: HashSet::new(),
+            stack: vec![],
+        }
+    }
+
+    /// Loads the given file, along with every file that it imports.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = fs::canonicalize(path)?;
+        if self.loaded.insert(path.clone()) {
+            self.stack.push(path.clone());
+            let result = self.set.load(&path).map_err(Error::from);
+            let result = result.and_then(|id| self.parse(id));
+            self.stack.pop();
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the given virtual source, along with every file that it
+    /// imports.  The name is only used to refer to the source in
+    /// diagnostics.
+    pub fn source(&mut self, name: String, content: &str) -> Result<(), Error> {
+        let id = self.set.push(name, Some(content.to_string()));
+        self.parse(id)
+    }
+
+    fn parse(&mut self, id: SourceId) -> Result<(), Error> {
+        let content = self.set.content(id).unwrap_or_default();
+        let mut stream = TokenStream::new(&content, id, self.set.clone());
+        let root = Root::parse(&mut stream)?;
+
+        for (path, span) in imports(&root) {
+            self.import(&path, span)?;
+        }
+
+        self.roots.push(root);
+        Ok(())
+    }
+
+    /// Finishes loading, reporting any imports that none of the loaded
+    /// sources define, and none of the given dependencies export, and
+    /// returns the sources.
+    pub fn finish(self, dependencies: &[Module]) -> Vec<Root> {
+        let sources = self.roots.iter().flat_map(Scope::build).map(|item| {
+            let path = Annotation::from(item).to_path();
+            let mut parts = path.module().to_vec();
+            if !path.is_func() {
+                parts.extend(path.base.last().cloned());
+            }
+            parts.into_iter().map(String::from).collect::<Vec<_>>()
+        });
+        let exported = dependencies.iter().flat_map(|module| {
+            let types = module.exported_types().map(|(_, kind)| &kind.name);
+            let consts = module.exported_consts().map(|(_, const_)| &const_.name);
+            // Like those in the source, functions are imported through the
+            // module that they're in, rather than by their own name.
+            let funcs = module.exported_funcs().map(|(_, func)| {
+                let name = func.name.to_string();
+                let parts = name.split("::").map(String::from).collect::<Vec<_>>();
+                parts[..parts.len() - 1].to_vec()
+            });
+            types
+                .chain(consts)
+                .map(|name| name.to_string().split("::").map(String::from).collect())
+                .chain(funcs)
+                .collect::<Vec<Vec<_>>>()
+        });
+        // Every module along the way is defined, too.
+        let defined = sources
+            .chain(exported)
+            .flat_map(|parts| {
+                (1..=parts.len())
+                    .map(|len| parts[..len].join("::"))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<HashSet<_>>();
+
+        for (path, span) in self.roots.iter().flat_map(imports) {
+            let name = path.join("::");
+            if !defined.contains(&name) {
+                let message = format!("unresolved import {}", name);
+                self.set.emit(Name::UnresolvedImport, span, message);
+            }
+        }
+
+        self.roots
+    }
+
+    fn import(&mut self, path: &[String], span: Span) -> Result<(), Error> {
+        let file = match self.find(path) {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+
+        // A file may import the things that it defines itself.
+        if self.stack.last() == Some(&file) {
+            return Ok(());
+        }
+
+        if let Some(position) = self.stack.iter().position(|f| *f == file) {
+            let cycle = self.stack[position..]
+                .iter()
+                .chain(std::iter::once(&file))
+                .map(|f| f.display().to_string())
+                .collect::<Vec<_>>();
+            let message = format!("import cycle: {}", cycle.join(" -> "));
+            self.set.emit(Name::ImportCycle, span, message);
+            return Ok(());
+        }
+
+        self.load(file)
+    }
+
+    /// Finds the file that defines the given import, trying the longest
+    /// module path first in each directory of the search path.
+    fn find(&self, path: &[String]) -> Option<PathBuf> {
+        self.search.iter().find_map(|directory| {
+            (1..=path.len()).rev().find_map(|len| {
+                let mut file = directory.clone();
+                file.extend(&path[..len]);
+                file.set_extension(EXTENSION);
+                fs::canonicalize(file).ok().filter(|f| f.is_file())
+            })
+        })
+    }
+}
+
+/// Every import in the given source, as the full path of what is imported,
+/// along with the span to report it at.  Imports within modules are
+/// included.
+fn imports(root: &Root) -> Vec<(Vec<String>, Span)> {
+    let mut result = vec![];
+    collect(root.items(), &mut result);
+    result
+}
+
+fn collect(items: &[Item], result: &mut Vec<(Vec<String>, Span)>) {
+    for item in items {
+        match item {
+            Item::Use(use_) => imported(use_, result),
+            Item::Module(module) => collect(module.items(), result),
+            _ => {}
+        }
+    }
+}
+
+fn imported(use_: &Use, result: &mut Vec<(Vec<String>, Span)>) {
+    for import in use_.imports() {
+        let path = import.path().iter().map(|part| part.to_string()).collect();
+        result.push((path, import.span()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diag::{Diagnostics, MemorySink};
+
+    const FILES: &[(&str, &str)] = &[
+        (
+            "main.slip",
+            "use Shapes::Circle;\nuse A::Item;\nuse Nowhere::Thing;\n\nfn area(_: Circle) { }",
+        ),
+        (
+            "Shapes/Circle.slip",
+            "module Shapes {\n    struct Circle {\n        radius: i32\n    }\n}",
+        ),
+        (
+            "A.slip",
+            "use B::Item;\n\nmodule A {\n    struct Item {\n        x: i32\n    }\n}",
+        ),
+        (
+            "B.slip",
+            "use A::Item;\n\nmodule B {\n    struct Item {\n        x: i32\n    }\n}",
+        ),
+    ];
+
+    /// A loader with the given search path, that collects everything emitted
+    /// while loading.
+    fn loader(search: Vec<PathBuf>) -> (Loader<'static>, MemorySink) {
+        let memory = MemorySink::new();
+        let set = DiagnosticSync::from(Diagnostics::new().sink(memory.clone()));
+        (Loader::new(set, search), memory)
+    }
+
+    #[test]
+    fn test_load() {
+        let directory = std::env::temp_dir().join(format!("slip-load-{}", std::process::id()));
+        for (name, content) in FILES {
+            let path = directory.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let (mut loader, memory) = loader(vec![directory.clone()]);
+        loader.load(directory.join("main.slip")).unwrap();
+        // `A` and `B` import each other, but are still only loaded once.
+        let roots = loader.finish(&[]);
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(roots.len(), 4);
+
+        let unresolved = "unresolved import Nowhere::Thing";
+        let cycle = ["A.slip", "B.slip", "A.slip"]
+            .iter()
+            .map(|name| directory.join(name).display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        let emissions = memory
+            .emissions()
+            .iter()
+            .map(|e| (e.name(), e.message().to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            emissions,
+            [
+                (Name::ImportCycle, format!("import cycle: {}", cycle)),
+                (Name::UnresolvedImport, unresolved.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependency_imports() {
+        use slip_typal::spec::ModuleSpec;
+
+        let mut geo = ModuleSpec::new("geo", "1.2.0".parse().unwrap());
+        let point = geo.struct_type(vec!["Geo", "Point"], vec![], vec![]);
+        geo.update_type(point, |kind| kind.export = true);
+
+        let (mut loader, memory) = loader(vec![]);
+        let content = "use Geo::Point;\nuse Geo::Nothing;";
+        loader.source("main.slip".into(), content).unwrap();
+        loader.finish(&[geo.module().clone()]);
+
+        // What the dependency exports is resolved, and nothing else is.
+        let emissions = memory.emissions();
+        assert_eq!(emissions.len(), 1);
+        assert_eq!(emissions[0].name(), Name::UnresolvedImport);
+        assert_eq!(emissions[0].message(), "unresolved import Geo::Nothing");
+    }
+}
@@ -9,12 +9,229 @@ extern crate failure;
 extern crate serde;
 extern crate test;
 
+pub mod config;
 pub mod diag;
 pub mod error;
+pub mod load;
 pub mod reduce;
 pub mod stream;
 pub mod syn;
 
+use self::config::Config;
+use self::diag::{DiagnosticSync, Diagnostics, Format, Level, Name};
+use self::error::Error;
+use self::load::Loader;
+use self::reduce::Reduce;
+use slip_typal::module::Module;
+use slip_typal::mono::monomorphize;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+const USAGE: &str = "usage: slip [-I <directory>]... [-L <module>]... \
+                     [-A|-W|-D <diagnostic>]... [--level <level>] [--error-limit <count>] \
+                     [--config <file>] [--format <text|json>] [--fix] <file>...\n       \
+                     slip explain <code|diagnostic>";
+
 fn main() {
-    println!("Hello, world!");
+    let mut search = vec![];
+    let mut dependencies = vec![];
+    let mut files = vec![];
+    let mut format = Format::Text;
+    let mut fix = false;
+    let mut level = None;
+    let mut limit = None;
+    let mut config = None;
+    let mut overrides = vec![];
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("explain") {
+        match (args.nth(1), args.next()) {
+            (Some(name), None) => explain(&name),
+            _ => fail(USAGE),
+        }
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-I" => match args.next() {
+                Some(directory) => search.push(PathBuf::from(directory)),
+                None => fail(USAGE),
+            },
+            "-L" => match args.next() {
+                Some(module) => dependencies.push(PathBuf::from(module)),
+                None => fail(USAGE),
+            },
+            "--format" => match args.next().as_deref() {
+                Some("text") => format = Format::Text,
+                Some("json") => format = Format::Json,
+                _ => fail(USAGE),
+            },
+            "--fix" => fix = true,
+            "-A" | "-W" | "-D" => match args.next() {
+                Some(short) => {
+                    let action = match arg.as_str() {
+                        "-A" => Level::Never,
+                        "-W" => Level::Warning,
+                        _ => Level::Error,
+                    };
+                    let names = Name::lookup(&short).unwrap_or_else(|e| fail(&e.to_string()));
+                    overrides.extend(names.into_iter().map(|name| (name, action)));
+                }
+                None => fail(USAGE),
+            },
+            "--level" => match args.next().map(|value| value.parse::<Level>()) {
+                Some(Ok(value)) => level = Some(value),
+                Some(Err(error)) => fail(&error.to_string()),
+                None => fail(USAGE),
+            },
+            "--error-limit" => match args.next().map(|value| value.parse::<usize>()) {
+                Some(Ok(value)) => limit = Some(value),
+                _ => fail(USAGE),
+            },
+            "--config" => match args.next() {
+                Some(file) => config = Some(PathBuf::from(file)),
+                None => fail(USAGE),
+            },
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+
+    if files.is_empty() {
+        fail(USAGE);
+    }
+
+    // The directories that the given files are in are always searched, after
+    // the ones given explicitly.
+    for file in &files {
+        let parent = file.parent().map(PathBuf::from).unwrap_or_default();
+        if !search.contains(&parent) {
+            search.push(parent);
+        }
+    }
+
+    // The configuration file only has to exist if it was given explicitly.
+    let config = match config {
+        Some(path) => read_config(&path, format),
+        None if Path::new(config::FILE).is_file() => read_config(Path::new(config::FILE), format),
+        None => Config::default(),
+    };
+    let defaults = config
+        .overrides()
+        .unwrap_or_else(|e| abort(&e.to_string(), format));
+    let diagnostics = Diagnostics::new()
+        .format(format)
+        .level(level.or_else(|| config.level()).unwrap_or_default())
+        .limit(limit.or_else(|| config.error_limit()))
+        .overrides(defaults)
+        .overrides(overrides);
+
+    // The diagnostic sinks can buffer their output, so they're flushed
+    // before exiting; see `finish`.
+    let set = DiagnosticSync::from(diagnostics);
+    let mut loader = Loader::new(set.clone(), search);
+    for file in &files {
+        match loader.load(file) {
+            Ok(()) => {}
+            // The parse error has already been emitted as a diagnostic.
+            Err(Error::UnexpectedTokenError { .. }) => {
+                finish(&set, fix, format);
+                exit(1);
+            }
+            Err(error) => {
+                finish(&set, fix, format);
+                abort(&format!("{}: {}", file.display(), error), format);
+            }
+        }
+    }
+    if set.exceeded() {
+        finish(&set, fix, format);
+        let (errors, _) = set.counts();
+        abort(&Error::ErrorLimitError { errors }.to_string(), format);
+    }
+
+    // The dependencies are read before the sources are finished, so that
+    // imports of what they export aren't reported as unresolved.
+    let mut modules = vec![];
+    for path in &dependencies {
+        match File::open(path).map_err(Into::into).and_then(Module::read) {
+            Ok(module) => modules.push(module),
+            Err(error) => {
+                finish(&set, fix, format);
+                abort(&format!("{}: {}", path.display(), error), format)
+            }
+        }
+    }
+    let roots = loader.finish(&modules);
+    let mut reduce = Reduce::new(set.clone());
+    for module in modules {
+        reduce.depend(module);
+    }
+    for root in &roots {
+        reduce.push(root);
+    }
+
+    let result = reduce.reduce();
+    finish(&set, fix, format);
+    if let Err(error) = result {
+        abort(&error.to_string(), format);
+    }
+    // The errors have already been reported, and summarized; all that's
+    // left is to make sure that the compile is seen to have failed.
+    if set.counts().0 > 0 {
+        exit(1);
+    }
+
+    // Nothing is generated from the module yet, but it's monomorphized all
+    // the same, so that generics that can't be instantiated are reported.
+    if let Err(error) = monomorphize(reduce.module().module()) {
+        abort(&error.to_string(), format);
+    }
+}
+
+/// Flushes the diagnostics, and if we're fixing, applies the suggestions
+/// that are safe to apply to the source files.  When the diagnostics are
+/// written as text, this ends them with a summary of how many there were.
+fn finish(set: &DiagnosticSync<'_>, fix: bool, format: Format) {
+    set.flush();
+    if let (Format::Text, Some(summary)) = (format, set.summary()) {
+        eprintln!("{}", summary);
+    }
+    if fix {
+        for (path, content) in set.fixed() {
+            if let Err(error) = fs::write(&path, content) {
+                abort(&format!("{}: {}", path.display(), error), format);
+            }
+        }
+    }
+}
+
+/// Prints the explanation of the diagnostic with the given code or short
+/// name, and exits.
+fn explain(name: &str) -> ! {
+    let name = Name::find(name).unwrap_or_else(|error| fail(&error.to_string()));
+    let default = match name.level() {
+        Level::Never => "off".to_string(),
+        level => level.to_string(),
+    };
+    println!("{}: {} ({} by default)", name.code(), name, default);
+    println!();
+    println!("{}", name.explanation());
+    exit(0)
+}
+
+fn read_config(path: &Path, format: Format) -> Config {
+    Config::read(path)
+        .unwrap_or_else(|error| abort(&format!("{}: {}", path.display(), error), format))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(1)
+}
+
+/// Fails once the format is known, writing the message out in the same
+/// format as the diagnostics, so that JSON output stays all JSON.
+fn abort(message: &str, format: Format) -> ! {
+    format.fatal(message);
+    exit(1)
 }
@@ -1,7 +1,7 @@
 use super::Scope;
 use crate::diag::Span;
 use crate::reduce::Path;
-use crate::syn::{BasicNode, Item};
+use crate::syn::{Attribute, BasicNode, Item};
 
 /// An annotated item - an item, along with the path to the item (type path)
 /// and the type imports.  This is easier to keep track of than having these
@@ -20,6 +20,7 @@ impl<'s> Annotation<'s> {
             Item::Enum(enum_) => items.push(enum_.kind()),
             Item::Struct(struct_) => items.push(struct_.kind()),
             Item::Function(func_) => fname = Some(func_.name()),
+            Item::Const(const_) => items.push(const_.name()),
             _ => {}
         }
         Path::from_syn(items, fname)
@@ -39,6 +40,20 @@ impl<'s> Annotation<'s> {
         }
     }
 
+    pub fn is_const(&self) -> bool {
+        match self.item {
+            Item::Const(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether or not the item can be referenced from outside of the module
+    /// it is defined in.  This requires both the item and all of the modules
+    /// it is in to be exported.
+    pub fn is_exported(&self) -> bool {
+        self.item.export() && self.scope.exported()
+    }
+
     pub fn generic_list<'l>(&'l self) -> impl Iterator<Item = &'s crate::syn::Type> + 'l {
         // First, we'll collect all of the `Type`s from the scope, as they may have
         // generics we can use.
@@ -65,6 +80,13 @@ impl<'s> Annotation<'s> {
         scope_generics.chain(list_generics)
     }
 
+    /// The attributes that apply to the item - those of the modules it's in,
+    /// followed by its own.
+    pub fn attributes<'l>(&'l self) -> impl Iterator<Item = &'s Attribute> + 'l {
+        let scope = self.scope.attributes().iter().cloned();
+        scope.chain(self.item.attributes())
+    }
+
     pub fn item(&self) -> &'s Item {
         self.item
     }
@@ -0,0 +1,91 @@
+//! The attributes that change the levels of diagnostics within the items
+//! they're on.  `#[allow(...)]` silences the diagnostics it names,
+//! `#[warn(...)]` makes them warnings, and `#[deny(...)]` makes them errors.
+//! Diagnostics are named by their short names, and a dotted group name
+//! (e.g. `type-trace`) names every diagnostic in the group; see
+//! [`Name::matching()`].
+//!
+//! The attributes on a module apply to everything within it, and the
+//! attributes on an item come after (and so win over) those of the modules
+//! around it.  Reducing an item pushes its overrides onto the diagnostics
+//! for as long as it takes; see [`Reduce::within()`].
+
+use super::Reduce;
+use crate::diag::{closest, Applicability, Emission, Level, Name};
+use crate::syn::{Attribute, AttributeName, BasicNode, Item};
+
+/// The level that the given attribute sets the diagnostics it names to, if
+/// it's an attribute that does so.
+fn level(attribute: &Attribute) -> Option<Level> {
+    attribute.name().value().and_then(Level::from_action)
+}
+
+/// The overrides that the given attributes make, in order.  Unknown
+/// attributes and names are ignored here; they are warned about once, by
+/// [`check()`].
+pub(super) fn overrides<'a>(
+    attributes: impl IntoIterator<Item = &'a Attribute>,
+) -> Vec<(Name, Level)> {
+    attributes
+        .into_iter()
+        .filter_map(|attribute| level(attribute).map(|level| (attribute, level)))
+        .flat_map(|(attribute, level)| {
+            attribute.arguments().iter().flat_map(move |argument| {
+                let short = argument.to_string();
+                Name::matching(&short)
+                    .map(|name| (name, level))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect()
+}
+
+/// Warns about every attribute in the given items (and the modules within
+/// them) that isn't known, and every name given to a known attribute that
+/// doesn't name any diagnostic.
+pub(super) fn check(reduce: &Reduce<'_>, items: &[Item]) {
+    for item in items {
+        for attribute in item.attributes() {
+            if level(attribute).is_some() {
+                attribute
+                    .arguments()
+                    .iter()
+                    .for_each(|argument| check_name(reduce, argument));
+            } else {
+                let message = format!(
+                    "unknown attribute `{}`",
+                    attribute.name().value().unwrap_or_default()
+                );
+                reduce
+                    .set
+                    .emit(Name::UnknownAttribute, attribute.name().span(), message);
+            }
+        }
+
+        if let Item::Module(module) = item {
+            check(reduce, module.items());
+        }
+    }
+}
+
+fn check_name(reduce: &Reduce<'_>, argument: &AttributeName) {
+    let short = argument.to_string();
+    if Name::matching(&short).next().is_some() {
+        return;
+    }
+
+    let mut emission = Emission::new(
+        Name::UnknownDiagnostic,
+        argument.span(),
+        format!("unknown diagnostic `{}`", short),
+    );
+    if let Some(name) = closest(&short, Name::all(), |name| name.short()) {
+        emission = emission.with_suggestion(
+            argument.span(),
+            name.short(),
+            format!("did you mean `{}`?", name),
+            Applicability::MaybeIncorrect,
+        );
+    }
+    reduce.set.report(emission);
+}
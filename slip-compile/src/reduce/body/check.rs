@@ -0,0 +1,145 @@
+//! Checks the types of values in a function body.  The type of every local
+//! is known as soon as it is built - a `let` without a type takes on the type
+//! of its value - so checking is just a matter of comparing the type of a
+//! value against the type expected of it where it is used: arguments against
+//! parameters, return values against the return type, and conditions
+//! against `bool`.
+//!
+//! Integer and float literals are the exception.  They take on whichever
+//! integer (or float) type is expected of them, and are only `i32` (or `f64`)
+//! if nothing in particular is expected.
+
+use super::Body;
+use crate::diag::{Name, Span};
+use slip_typal::module::{FunctionId, Local, TypeDefinition, TypeReference};
+
+impl<'r, 's> Body<'r, 's> {
+    /// Checks that the given local is of the expected type, emitting the
+    /// given diagnostic if it isn't.  Locals of the error type have already
+    /// been reported, and generic types can't be checked until they are
+    /// known, so both of those are skipped; a void value is a mismatch like
+    /// any other.
+    pub(super) fn expect(
+        &mut self,
+        name: Name,
+        local: Local,
+        expected: &TypeReference,
+        span: Span,
+    ) -> bool {
+        let found = self.kind_of(local);
+        let error = self.reduce.module.is_error(&found) || self.reduce.module.is_error(expected);
+        if found == *expected || error || is_generic(expected) {
+            return true;
+        }
+
+        if self.literals.contains(&local) && self.is_literal_of(&found, expected) {
+            self.spec.retype(local, expected.clone());
+            return true;
+        }
+
+        let message = format!(
+            "mismatched types: expected {}, found {}",
+            self.describe_kind(expected),
+            self.describe_kind(&found)
+        );
+        self.reduce.set.emit(name, span, message);
+        false
+    }
+
+    /// Checks the arguments of a call against the parameters of the
+    /// function.  The spans are the spans of each of the arguments, and the
+    /// span of the call as a whole, respectively.
+    pub(super) fn check_arguments(
+        &mut self,
+        id: FunctionId,
+        arguments: &[Local],
+        spans: &[Span],
+        span: Span,
+    ) {
+        let func = match self.reduce.module.get_function(id) {
+            Some(func) => func,
+            None => return,
+        };
+        let name = func.name.to_string();
+        let parameters = func.parameters.clone();
+
+        if parameters.len() != arguments.len() {
+            let message = format!(
+                "function {} takes {} argument(s), but {} were given",
+                name,
+                parameters.len(),
+                arguments.len()
+            );
+            self.reduce.set.emit(Name::ArgumentCount, span, message);
+            return;
+        }
+
+        for ((argument, parameter), span) in arguments.iter().zip(&parameters).zip(spans) {
+            self.expect(Name::MismatchedArgument, *argument, parameter, *span);
+        }
+    }
+
+    /// Checks the value of a `return` against the return type of the
+    /// function.  A function without a return type can't return a value,
+    /// and a function with one has to.
+    pub(super) fn check_return(&mut self, value: Option<(Local, Span)>, span: Span) {
+        match (value, self.retval.clone()) {
+            (Some((local, span)), Some(retval)) => {
+                self.expect(Name::MismatchedReturn, local, &retval, span);
+            }
+            (None, None) => {}
+            (Some((local, _)), None) if self.is_error(local) => {}
+            (Some((local, span)), None) => {
+                let message = format!(
+                    "function has no return type, but returns a value of type {}",
+                    self.describe(local)
+                );
+                self.reduce.set.emit(Name::MismatchedReturn, span, message);
+            }
+            (None, Some(retval)) => {
+                let message = format!(
+                    "function returns {}, but no value is returned",
+                    self.describe_kind(&retval)
+                );
+                self.reduce.set.emit(Name::MismatchedReturn, span, message);
+            }
+        }
+    }
+
+    /// Checks that the condition of an `if`, `unless`, or `while` is a
+    /// `bool`.
+    pub(super) fn check_condition(&mut self, local: Local, span: Span) {
+        let bool_ = TypeReference::Absolute(self.reduce.module.primitive_type(1));
+        self.expect(Name::MismatchedCondition, local, &bool_, span);
+    }
+
+    /// Whether or not a literal of the found type can take on the expected
+    /// type instead.  Integer literals can be any integer type (but not
+    /// `bool`), and float literals can be any float type.
+    fn is_literal_of(&self, found: &TypeReference, expected: &TypeReference) -> bool {
+        let definition = |kind: &TypeReference| match kind {
+            TypeReference::Absolute(id) => self.reduce.module.get_type(*id).map(|t| &t.definition),
+            _ => None,
+        };
+
+        match (definition(found), definition(expected)) {
+            (Some(TypeDefinition::Primitive(_)), Some(TypeDefinition::Primitive(size))) => {
+                *size > 1
+            }
+            (Some(TypeDefinition::Primitive(_)), Some(TypeDefinition::PrimitiveSize)) => true,
+            (Some(TypeDefinition::PrimitiveFloat(_)), Some(TypeDefinition::PrimitiveFloat(_))) => {
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether or not the given type refers to a generic anywhere within it.
+fn is_generic(kind: &TypeReference) -> bool {
+    match kind {
+        TypeReference::Generic(_) => true,
+        TypeReference::Mix(_, generics) => generics.iter().any(is_generic),
+        TypeReference::Absolute(_) => false,
+    }
+}
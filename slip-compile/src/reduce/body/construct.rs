@@ -0,0 +1,86 @@
+//! Builds struct values.  Every field of the struct has to be given exactly
+//! once; anything else - a missing field, a field given twice, or a field
+//! that the struct doesn't have - is reported, and the construction is still
+//! built from the remaining fields so that building can continue.
+
+use super::{resolve, Body};
+use crate::diag::{Name, Span};
+use crate::syn::function::expression::Construct;
+use crate::syn::BasicNode;
+use slip_typal::module::{Local, Operation, TypeDefinition, TypeReference};
+use std::collections::HashMap;
+
+impl<'r, 's> Body<'r, 's> {
+    pub(super) fn construct(&mut self, construct: &'s Construct) -> Local {
+        let kind = resolve::kind(self.reduce, &self.annotation, construct.kind());
+        if self.reduce.module.is_error(&kind) {
+            return self.error();
+        }
+        let id = match &kind {
+            TypeReference::Absolute(id) | TypeReference::Mix(id, _) => Some(*id),
+            TypeReference::Generic(_) => None,
+        };
+        let definition = id
+            .and_then(|id| self.reduce.module.get_type(id))
+            .map(|t| t.definition.clone());
+        let result = self.spec.local(kind);
+
+        let fields = match definition {
+            Some(TypeDefinition::Struct(fields)) => fields,
+            _ => {
+                let message = format!("type {} is not a struct", self.describe(result));
+                self.reduce
+                    .set
+                    .emit(Name::NotAStruct, construct.kind().span(), message);
+                return self.error();
+            }
+        };
+
+        let mut values = vec![];
+        let mut given: HashMap<&'s str, Span> = HashMap::new();
+        for field in construct.fields() {
+            let name = field.name().value().unwrap_or("");
+            let value = match field.value() {
+                Some(expr) => self.expression(expr),
+                None => self.ident(field.name()),
+            };
+
+            if !fields.contains_key(name) {
+                let message = format!("no field {} on type {}", name, self.describe(result));
+                self.reduce
+                    .set
+                    .emit(Name::UnknownField, field.name().span(), message);
+            } else if given.insert(name, field.span()).is_some() {
+                let message = format!("field {} is given more than once", name);
+                self.reduce
+                    .set
+                    .emit(Name::DuplicateField, field.name().span(), message);
+            } else {
+                if let Some(id) = id {
+                    self.check_field(id, name, field.name().span());
+                }
+                values.push((name.to_string(), value));
+            }
+        }
+
+        let mut missing = fields
+            .keys()
+            .filter(|name| !given.contains_key(name.as_str()))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            missing.sort_unstable();
+            let message = format!(
+                "missing field(s) {} for type {}",
+                missing.join(", "),
+                self.describe(result)
+            );
+            self.reduce
+                .set
+                .emit(Name::MissingField, construct.span(), message);
+        }
+
+        self.push(Operation::Construct(result, values));
+        result
+    }
+}
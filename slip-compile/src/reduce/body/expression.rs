@@ -0,0 +1,454 @@
+use super::{bound, constant, resolve, Body};
+use crate::diag::{Name, Span};
+use crate::reduce::Path;
+use crate::stream::{Token, TokenKind};
+use crate::syn::function::expression::{
+    Access, Atom, Call, Expression, Index, InfixOperation, PrefixOperation, SuffixOperation,
+};
+use crate::syn::function::FunctionName;
+use crate::syn::{BasicNode, Roll};
+use slip_typal::module::{
+    Builtin, Constant, FunctionId, Local, Operation, TypeDefinition, TypeId, TypeReference,
+};
+
+impl<'r, 's> Body<'r, 's> {
+    pub(super) fn expression(&mut self, expr: &'s Expression) -> Local {
+        match expr {
+            Expression::Atom(atom) => self.atom(atom),
+            Expression::Infix(infix) => self.infix(infix),
+            Expression::Prefix(prefix) => self.prefix(prefix),
+            Expression::Suffix(suffix) => self.suffix(suffix),
+            Expression::Index(index) => self.index(index),
+            Expression::Call(call) => self.call(call),
+            Expression::Access(access) => self.access(access),
+        }
+    }
+
+    fn atom(&mut self, atom: &'s Atom) -> Local {
+        match atom {
+            Atom::Integer(tok) => self.integer(tok),
+            Atom::Float(tok) => self.float(tok),
+            Atom::Bool(tok) => {
+                let kind = self.reduce.module.primitive_type(1);
+                let value = Constant::Bool(tok.kind() == TokenKind::True);
+                self.constant(TypeReference::Absolute(kind), value)
+            }
+            Atom::SingleString(tok) | Atom::DoubleString(tok) => self.string(tok),
+            Atom::Ident(tok) | Atom::This(tok) => self.ident(tok),
+            Atom::Underscore(tok) => self.unsupported(tok.span(), "placeholders"),
+            Atom::Type(kind) => match constant::find(self.reduce, &self.annotation, kind, None) {
+                Some(path) => self.named_constant(&path, kind.span()),
+                None => self.unsupported(kind.span(), "type expressions"),
+            },
+            Atom::Construct(construct) => self.construct(construct),
+            Atom::Member(member) => self.member(member, None, member.span()),
+            Atom::Map(map) => self.unsupported(map.span(), "map literals"),
+            Atom::Array(array) => self.unsupported(array.span(), "array literals"),
+            Atom::Tuple(tuple) => self.unsupported(tuple.span(), "tuple literals"),
+        }
+    }
+
+    fn integer(&mut self, tok: &'s Token) -> Local {
+        let value = tok.value().unwrap_or("0");
+        let value = constant::integer(value).unwrap_or_else(|_| {
+            self.reduce.set.emit(
+                Name::LiteralError,
+                tok.span(),
+                format!("invalid integer literal {}", value),
+            );
+            0
+        });
+
+        let kind = self.reduce.module.primitive_type(32);
+        let local = self.constant(TypeReference::Absolute(kind), Constant::Integer(value));
+        self.literals.insert(local);
+        local
+    }
+
+    fn float(&mut self, tok: &'s Token) -> Local {
+        let value = tok.value().unwrap_or("0.0");
+        let value = value.parse::<f64>().unwrap_or_else(|_| {
+            self.reduce.set.emit(
+                Name::LiteralError,
+                tok.span(),
+                format!("invalid float literal {}", value),
+            );
+            0.0
+        });
+
+        let kind = self.reduce.module.float_type(64);
+        let local = self.constant(TypeReference::Absolute(kind), Constant::Float(value));
+        self.literals.insert(local);
+        local
+    }
+
+    fn string(&mut self, tok: &'s Token) -> Local {
+        let value = tok.value().unwrap_or("\"\"");
+        // Both kinds of strings are surrounded by a single quote character.
+        let value = value[1..(value.len() - 1)].to_string();
+        // Strings are pointers to bytes.
+        let ptr = self.reduce.module.pointer_type();
+        let byte = self.reduce.module.primitive_type(8);
+        let kind = TypeReference::Mix(ptr, vec![TypeReference::Absolute(byte)]);
+        self.constant(kind, Constant::String(value))
+    }
+
+    /// Replaces a reference to a constant with the value of the constant.
+    pub(super) fn named_constant(&mut self, path: &Path<'s>, span: Span) -> Local {
+        let const_ = constant::demand(self.reduce, path, span)
+            .and_then(|id| self.reduce.module.get_const(id))
+            .cloned();
+        match const_ {
+            Some(const_) => self.constant(const_.kind, const_.value),
+            None => self.error(),
+        }
+    }
+
+    fn constant(&mut self, kind: TypeReference, constant: Constant) -> Local {
+        let local = self.spec.local(kind);
+        self.push(Operation::Constant(local, constant));
+        local
+    }
+
+    pub(super) fn ident(&mut self, tok: &'s Token) -> Local {
+        let name = tok.value().unwrap_or("");
+        match self.scopes.lookup(name) {
+            Some(local) => local,
+            None => {
+                self.reduce.set.emit(
+                    Name::UndefinedLocal,
+                    tok.span(),
+                    format!("undefined local {}", name),
+                );
+                self.error()
+            }
+        }
+    }
+
+    fn infix(&mut self, infix: &'s InfixOperation) -> Local {
+        match infix.operator().kind() {
+            TokenKind::Equals => return self.assign(infix.left(), infix.right(), infix.span()),
+            TokenKind::LogicalAnd | TokenKind::LogicalOr => return self.logical(infix),
+            _ => {}
+        }
+
+        let left = self.expression(infix.left());
+        let right = self.expression(infix.right());
+        let spans = [infix.left().span(), infix.right().span()];
+        self.binary(infix.operator(), left, right, spans)
+    }
+
+    fn assign(&mut self, left: &'s Expression, right: &'s Expression, span: Span) -> Local {
+        let value = self.expression(right);
+        match left {
+            Expression::Atom(Atom::Ident(tok)) => {
+                let local = self.ident(tok);
+                self.push(Operation::Copy(local, value));
+            }
+            Expression::Access(access) => match access.name() {
+                FunctionName::Identifier(tok) => {
+                    let base = self.expression(access.base());
+                    // Make sure the field exists; we don't need the type.
+                    self.field(base, tok);
+                    let name = tok.value().unwrap_or("").to_string();
+                    self.push(Operation::SetField(base, name, value));
+                }
+                _ => {
+                    self.unsupported(span, "assignments to non-field accesses");
+                }
+            },
+            _ => {
+                self.unsupported(span, "assignments to this expression");
+            }
+        }
+
+        value
+    }
+
+    fn prefix(&mut self, prefix: &'s PrefixOperation) -> Local {
+        let operand = self.expression(prefix.operand());
+        let span = prefix.operand().span();
+        let value = self.unary(prefix.operator(), operand, false, span);
+        match prefix.operator().kind() {
+            // `++a` updates `a`, and yields the updated value.
+            TokenKind::DoublePlus | TokenKind::DoubleMinus => {
+                self.update(prefix.operand(), value);
+            }
+            _ => {}
+        }
+
+        value
+    }
+
+    fn suffix(&mut self, suffix: &'s SuffixOperation) -> Local {
+        let operand = self.expression(suffix.operand());
+        // `a++` updates `a`, but yields the original value; so we need to
+        // keep a copy of it around.
+        let original = self.spec.local(self.kind_of(operand));
+        self.push(Operation::Copy(original, operand));
+        let value = self.unary(suffix.operator(), operand, true, suffix.operand().span());
+        self.update(suffix.operand(), value);
+        original
+    }
+
+    /// Stores the value back into the place the expression came from, if
+    /// the expression is a place (i.e. a local).  This is used for the
+    /// increment and decrement operators.
+    fn update(&mut self, expr: &'s Expression, value: Local) {
+        if let Expression::Atom(Atom::Ident(tok)) = expr {
+            if let Some(local) = tok.value().and_then(|n| self.scopes.lookup(n)) {
+                self.push(Operation::Copy(local, value));
+            }
+        }
+    }
+
+    fn index(&mut self, index: &'s Index) -> Local {
+        let base = self.expression(index.base());
+        let arguments = self.arguments(Some(base), index.arguments());
+        let spans = spans(Some(index.base().span()), index.arguments());
+        self.subscript(arguments, &spans, index.span())
+    }
+
+    pub(super) fn arguments(
+        &mut self,
+        base: Option<Local>,
+        params: &'s Roll<Expression>,
+    ) -> Vec<Local> {
+        let mut arguments = base.into_iter().collect::<Vec<_>>();
+        for param in params {
+            arguments.push(self.expression(param));
+        }
+        arguments
+    }
+
+    fn call(&mut self, call: &'s Call) -> Local {
+        match call {
+            Call::Standard(standard) => {
+                let name = standard.token.value().unwrap_or("");
+                let arguments = self.arguments(None, &standard.params);
+                match resolve::func(self.reduce, self.annotation.scope(), name) {
+                    Some((path, id)) => {
+                        self.check_visible(&path, standard.token.span());
+                        let spans = spans(None, &standard.params);
+                        self.invoke(id, arguments, &spans, standard.span)
+                    }
+                    None => {
+                        self.reduce.set.emit(
+                            Name::UnknownFunction,
+                            standard.token.span(),
+                            format!("unknown function {}", name),
+                        );
+                        self.error()
+                    }
+                }
+            }
+            Call::Static(static_) => {
+                self.member(&static_.base, Some(&static_.params), static_.span)
+            }
+            Call::Unified(unified) => {
+                let base = self.expression(&unified.base);
+                let arguments = self.arguments(Some(base), &unified.params);
+                match self.method(base, unified.func.value()) {
+                    Some((path, id)) => {
+                        self.check_visible(&path, unified.func.span());
+                        let spans = spans(Some(unified.base.span()), &unified.params);
+                        self.invoke(id, arguments, &spans, unified.span)
+                    }
+                    // Nothing can be called on a value that failed to build.
+                    None if self.is_error(base) => self.error(),
+                    None => {
+                        let message = format!(
+                            "unknown function {} for type {}",
+                            unified.func.value(),
+                            self.describe(base)
+                        );
+                        self.reduce
+                            .set
+                            .emit(Name::UnknownFunction, unified.func.span(), message);
+                        self.error()
+                    }
+                }
+            }
+            Call::Expression(expr) => self.unsupported(expr.span, "calls to expressions"),
+        }
+    }
+
+    /// Finds the function with the given name on the type of the given
+    /// local.
+    pub(super) fn method(&mut self, base: Local, name: &str) -> Option<(Path<'s>, FunctionId)> {
+        let path = self.path_of(base)?;
+        let path = path.with_fname(Some(name.to_string()));
+        self.reduce.func(&path).map(|id| (path, id))
+    }
+
+    /// Calls the given function with the given arguments, returning the
+    /// local that holds the return value.  The arguments are checked against
+    /// the parameters of the function; see [`Body::check_arguments`].
+    pub(super) fn invoke(
+        &mut self,
+        id: FunctionId,
+        arguments: Vec<Local>,
+        spans: &[Span],
+        span: Span,
+    ) -> Local {
+        self.check_arguments(id, &arguments, spans, span);
+        // The generics of the function are inferred from the arguments, so
+        // that e.g. a function returning `T` returns the type of the argument
+        // given for `T`.
+        let (inferred, retval) = match self.reduce.module.get_function(id) {
+            Some(f) => {
+                let mut inferred = vec![None; f.generics.len()];
+                for (parameter, argument) in f.parameters.iter().zip(&arguments) {
+                    parameter.infer(self.spec.type_of(*argument), &mut inferred);
+                }
+                (inferred, f.retval.clone())
+            }
+            None => (vec![], None),
+        };
+        let generics = bound::func_generics(self.reduce, id, inferred, span);
+        let retval = retval.map(|kind| kind.substitute(&generics));
+        let result = match retval {
+            Some(kind) => self.spec.local(kind),
+            None => self.void(),
+        };
+        self.push(Operation::Call(result, id, arguments));
+        result
+    }
+
+    fn access(&mut self, access: &'s Access) -> Local {
+        let base = self.expression(access.base());
+        match access.name() {
+            FunctionName::Identifier(tok) => {
+                let kind = self.field(base, tok);
+                let result = self.spec.local(kind);
+                let name = tok.value().unwrap_or("").to_string();
+                self.push(Operation::Field(result, base, name));
+                result
+            }
+            name => self.unsupported(name.span(), "non-field accesses"),
+        }
+    }
+
+    /// Retrieves the type of the named field on the type of the given local.
+    /// If the type does not have that field, this emits a diagnostic, and
+    /// returns the error type.
+    fn field(&mut self, base: Local, tok: &'s Token) -> TypeReference {
+        let name = tok.value().unwrap_or("");
+        let found = match self.kind_of(base) {
+            TypeReference::Absolute(id) => self.field_of(id, name, &[]).map(|k| (id, k)),
+            TypeReference::Mix(id, generics) => self.field_of(id, name, &generics).map(|k| (id, k)),
+            TypeReference::Generic(_) => None,
+        };
+
+        match found {
+            Some((id, kind)) => {
+                self.check_field(id, name, tok.span());
+                kind
+            }
+            None if self.is_error(base) => TypeReference::Absolute(self.reduce.module.error_type()),
+            None => {
+                let message = format!("no field {} on type {}", name, self.describe(base));
+                self.reduce
+                    .set
+                    .emit(Name::UnknownField, tok.span(), message);
+                TypeReference::Absolute(self.reduce.module.error_type())
+            }
+        }
+    }
+
+    fn field_of(
+        &self,
+        id: TypeId,
+        name: &str,
+        generics: &[TypeReference],
+    ) -> Option<TypeReference> {
+        match &self.reduce.module.get_type(id)?.definition {
+            TypeDefinition::Struct(fields) => fields.get(name).map(|f| f.kind.substitute(generics)),
+            _ => None,
+        }
+    }
+
+    /// Whether or not the given local is of a primitive type.
+    pub(super) fn is_primitive(&self, local: Local) -> bool {
+        let id = match self.spec.type_of(local) {
+            TypeReference::Absolute(id) | TypeReference::Mix(id, _) => *id,
+            TypeReference::Generic(_) => return false,
+        };
+
+        self.reduce
+            .module
+            .get_type(id)
+            .map(|t| t.definition.is_primitive())
+            .unwrap_or(false)
+    }
+
+    /// The path the type of the given local was defined at.  Primitive and
+    /// generic types have no path.
+    pub(super) fn path_of(&self, local: Local) -> Option<Path<'s>> {
+        match self.spec.type_of(local) {
+            TypeReference::Absolute(id) | TypeReference::Mix(id, _) => {
+                self.reduce.path_of(*id).cloned()
+            }
+            TypeReference::Generic(_) => None,
+        }
+    }
+
+    /// A human-readable name for the type of the given local, for use in
+    /// diagnostics.
+    pub(super) fn describe(&self, local: Local) -> String {
+        self.describe_kind(self.spec.type_of(local))
+    }
+
+    /// A human-readable name for the given type, for use in diagnostics.
+    pub(super) fn describe_kind(&self, kind: &TypeReference) -> String {
+        let definition = match kind {
+            TypeReference::Absolute(id) => self.reduce.module.get_type(*id).map(|t| &t.definition),
+            _ => None,
+        };
+        match kind {
+            // A `bool` is a one-bit integer underneath, and the void type a
+            // zero-bit one, but they're only ever written as `bool` and
+            // `void`.
+            _ if definition == Some(&TypeDefinition::Primitive(1)) => "bool".to_string(),
+            _ if definition == Some(&TypeDefinition::Primitive(0)) => "void".to_string(),
+            TypeReference::Absolute(id) | TypeReference::Mix(id, _) => self
+                .reduce
+                .path_of(*id)
+                .map(ToString::to_string)
+                .or_else(|| self.reduce.module.get_type(*id).map(|t| t.name.to_string()))
+                .unwrap_or_else(|| "(unknown)".to_string()),
+            TypeReference::Generic(i) => format!("(generic {})", i),
+        }
+    }
+
+    /// Performs the given builtin operation, returning the local holding the
+    /// result.
+    pub(super) fn builtin(&mut self, builtin: Builtin, arguments: Vec<Local>) -> Local {
+        let kind = if builtin.is_predicate() {
+            TypeReference::Absolute(self.reduce.module.primitive_type(1))
+        } else if builtin == Builtin::Compare {
+            TypeReference::Absolute(self.reduce.module.primitive_type(8))
+        } else if builtin == Builtin::Index {
+            // Only pointers can be indexed (see `subscript`), and indexing
+            // them gives the type they point to.
+            match self.kind_of(arguments[0]) {
+                TypeReference::Mix(_, mut generics) if generics.len() == 1 => generics.remove(0),
+                _ => TypeReference::Absolute(self.reduce.module.error_type()),
+            }
+        } else {
+            self.kind_of(arguments[0])
+        };
+
+        let result = self.spec.local(kind);
+        self.push(Operation::Builtin(result, builtin, arguments));
+        result
+    }
+}
+
+/// The spans of the arguments to a call, for use in diagnostics.  The base
+/// is the receiver of the call, if it has one.
+pub(super) fn spans(base: Option<Span>, params: &Roll<Expression>) -> Vec<Span> {
+    base.into_iter()
+        .chain(params.iter().map(BasicNode::span))
+        .collect()
+}
@@ -0,0 +1,134 @@
+//! Resolves members of types, e.g. `Color::Red` or `Int::default()`.  The
+//! syntax for the two is the same, so which one it is depends on the type:
+//! if the type is an enum with a variant of that name, it is a variant;
+//! otherwise, it is a static function on that type.  A constant defined
+//! within a module, e.g. `Limits::MAX`, uses the same syntax as well, and
+//! takes precedence over both.
+
+use super::expression::spans;
+use super::{constant, resolve, Body};
+use crate::diag::{Name, Span};
+use crate::syn::function::expression::{Expression, Member};
+use crate::syn::{BasicNode, Roll};
+use slip_typal::module::{Enum, Local, Operation, TypeDefinition, TypeReference};
+
+impl<'r, 's> Body<'r, 's> {
+    /// Builds the given member, with the given parameters if it was called.
+    pub(super) fn member(
+        &mut self,
+        member: &'s Member,
+        params: Option<&'s Roll<Expression>>,
+        span: Span,
+    ) -> Local {
+        let name = member.name().value();
+        if params.is_none() {
+            if let Some(path) = constant::find(self.reduce, &self.annotation, member.kind(), name) {
+                return self.named_constant(&path, span);
+            }
+        }
+
+        let kind = resolve::kind(self.reduce, &self.annotation, member.kind());
+        if self.reduce.module.is_error(&kind) {
+            return self.error();
+        }
+        let name = name.unwrap_or("");
+        let (id, generics) = match &kind {
+            TypeReference::Absolute(id) => (*id, vec![]),
+            TypeReference::Mix(id, generics) => (*id, generics.clone()),
+            TypeReference::Generic(_) => {
+                return self.unsupported(member.kind().span(), "members of generic types")
+            }
+        };
+
+        let enum_ = match self.reduce.module.get_type(id).map(|t| &t.definition) {
+            Some(TypeDefinition::Enum(enum_)) => Some(enum_.clone()),
+            _ => None,
+        };
+        if let Some(payload) = enum_.as_ref().and_then(|e| payload_of(e, name)) {
+            let payload = payload
+                .iter()
+                .map(|k| k.substitute(&generics))
+                .collect::<Vec<_>>();
+            return self.variant(kind, member, payload, params, span);
+        }
+
+        let func = self
+            .reduce
+            .path_of(id)
+            .map(|path| path.clone().with_fname(Some(name.to_string())))
+            .and_then(|path| self.reduce.func(&path).map(|id| (path, id)));
+        match (func, params) {
+            (Some((path, func)), Some(params)) => {
+                self.check_visible(&path, member.name().span());
+                let arguments = self.arguments(None, params);
+                self.invoke(func, arguments, &spans(None, params), span)
+            }
+            (Some(_), None) => self.unsupported(span, "function references"),
+            (None, _) => {
+                let kind = self.describe_kind(&kind);
+                let (diagnostic, message) = if enum_.is_some() {
+                    let message = format!("no variant {} on enum {}", name, kind);
+                    (Name::UnknownVariant, message)
+                } else {
+                    let message = format!("unknown function {} for type {}", name, kind);
+                    (Name::UnknownFunction, message)
+                };
+                self.reduce
+                    .set
+                    .emit(diagnostic, member.name().span(), message);
+                self.error()
+            }
+        }
+    }
+
+    /// Builds a variant of an enum, checking the given parameters against
+    /// its payload.  A variant without parameters (e.g. `Color::Red`) is the
+    /// same as one with an empty list of parameters (e.g. `Color::Red()`).
+    fn variant(
+        &mut self,
+        kind: TypeReference,
+        member: &'s Member,
+        payload: Vec<TypeReference>,
+        params: Option<&'s Roll<Expression>>,
+        span: Span,
+    ) -> Local {
+        let mut arguments = vec![];
+        for param in params.into_iter().flatten() {
+            let value = self.expression(param);
+            arguments.push((value, param.span()));
+        }
+
+        if arguments.len() != payload.len() {
+            let message = format!(
+                "variant {} takes {} value(s), but {} were given",
+                member.name().value().unwrap_or(""),
+                payload.len(),
+                arguments.len()
+            );
+            self.reduce.set.emit(Name::VariantArity, span, message);
+        } else {
+            for ((value, span), expected) in arguments.iter().zip(payload.iter()) {
+                self.expect(Name::MismatchedType, *value, expected, *span);
+            }
+        }
+
+        let name = member.name().value().unwrap_or("").to_string();
+        let values = arguments.into_iter().map(|(value, _)| value).collect();
+        let result = self.spec.local(kind);
+        self.push(Operation::Variant(result, name, values));
+        result
+    }
+}
+
+/// The payload of the named variant of the given enum, if the enum has that
+/// variant.  Variants without a payload have an empty one.
+fn payload_of(enum_: &Enum, name: &str) -> Option<Vec<TypeReference>> {
+    match enum_ {
+        Enum::Simple(variants) => variants.iter().find(|v| *v == name).map(|_| vec![]),
+        Enum::Value(variants) => variants.iter().find(|(v, _)| v == name).map(|_| vec![]),
+        Enum::Unit(variants) => variants
+            .iter()
+            .find(|(v, _)| v == name)
+            .map(|(_, payload)| payload.clone()),
+    }
+}
@@ -0,0 +1,332 @@
+//! Builds the bodies of functions.  Every statement and expression in the
+//! body is lowered into operations on locals, spread across blocks; control
+//! flow statements (`if`, `unless`, `while`) become branches and jumps
+//! between those blocks.
+//!
+//! Operators are not special here.  An operator applied to a primitive type
+//! is lowered into a builtin operation; an operator applied to any other type
+//! is desugared into a call to the function named after the operator, defined
+//! on the type of the (left-hand) operand.  See [`operator`] for more
+//! information on that.
+//!
+//! The types of values are checked as the body is built; see [`check`].
+
+use super::{bound, constant, resolve, Annotation, Path, Reduce};
+use crate::diag::{Name, Span};
+use crate::error::Error;
+use crate::syn::function::statement::{For, If, Let, Statement, Try, Unless, While};
+use crate::syn::function::FunctionParameter;
+use crate::syn::{BasicNode, Item};
+use slip_typal::module::{
+    BlockId, FunctionId, Local, Operation, TypeDefinition, TypeId, TypeReference,
+};
+use slip_typal::spec::FunctionSpec;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+mod check;
+mod construct;
+mod expression;
+mod member;
+mod operator;
+mod scope;
+
+use self::scope::Scopes;
+
+pub(super) fn build<'s>(
+    reduce: &mut Reduce<'s>,
+    id: FunctionId,
+    annotation: Arc<Annotation<'s>>,
+) -> Result<(), Error> {
+    let func = match annotation.item() {
+        Item::Function(func) => func,
+        _ => unreachable!(),
+    };
+    let (parameters, retval) = reduce
+        .module
+        .get_function(id)
+        .map(|f| (f.parameters.clone(), f.retval.clone()))
+        .unwrap_or_default();
+    let spec = FunctionSpec::new(&parameters);
+    let mut body = Body {
+        reduce,
+        annotation: annotation.clone(),
+        retval,
+        block: spec.entry(),
+        spec,
+        scopes: Scopes::default(),
+        literals: HashSet::new(),
+    };
+
+    body.open_scope();
+    for (position, param) in func.parameters().iter().enumerate() {
+        let local = body.spec.parameter(position);
+        match param {
+            FunctionParameter::Static(tok, _) => body.bind(tok, local),
+            FunctionParameter::This(tok) => body.bind_self(tok.span(), local),
+            FunctionParameter::Ignore(_, _) => {}
+        }
+    }
+
+    if let Some(group) = func.body() {
+        // The value of the body, if it has one, is returned from the
+        // function.  A function without a return type can still end with an
+        // expression that has no value, e.g. a call to another such function.
+        let value = body.group(group);
+        let void = TypeReference::Absolute(body.reduce.module.void_type());
+        let value = value.filter(|value| {
+            body.retval.is_some() || (body.kind_of(*value) != void && !body.is_error(*value))
+        });
+        if let Some(value) = value {
+            if !body.spec.is_terminated(body.block) {
+                let span = group.tail().map_or_else(|| group.span(), BasicNode::span);
+                body.check_return(Some((value, span)), span);
+                body.push(Operation::Return(Some(value)));
+            }
+        }
+        body.close_scope();
+    }
+
+    if !body.spec.is_terminated(body.block) {
+        // Falling off of the end of the function is the same as a `return`
+        // without a value; this is only an error if the end is reachable.
+        if body.reachable() {
+            body.check_return(None, func.span());
+        }
+        body.push(Operation::Return(None));
+    }
+
+    let Body { reduce, spec, .. } = body;
+    reduce.module.update_function(id, |f| spec.apply(f));
+    Ok(())
+}
+
+/// The state of a function body while it is being built.
+struct Body<'r, 's> {
+    reduce: &'r mut Reduce<'s>,
+    annotation: Arc<Annotation<'s>>,
+    /// The return type of the function.
+    retval: Option<TypeReference>,
+    spec: FunctionSpec,
+    /// The block that operations are currently being pushed onto.
+    block: BlockId,
+    /// The named locals that are currently visible; see [`scope`].
+    scopes: Scopes<'s>,
+    /// The locals that hold integer or float literals; see [`check`].
+    literals: HashSet<Local>,
+}
+
+impl<'r, 's> Body<'r, 's> {
+    fn push(&mut self, operation: Operation) {
+        self.spec.push(self.block, operation);
+    }
+
+    /// Jumps to the given block, unless the current block has already been
+    /// terminated (e.g. by a `return`).
+    fn jump(&mut self, target: BlockId) {
+        if !self.spec.is_terminated(self.block) {
+            self.push(Operation::Jump(target));
+        }
+    }
+
+    fn reachable(&self) -> bool {
+        self.spec.is_reachable(self.block)
+    }
+
+    fn kind_of(&self, local: Local) -> TypeReference {
+        self.spec.type_of(local).clone()
+    }
+
+    /// Creates a local of the void type, for expressions that produce no
+    /// value.
+    fn void(&mut self) -> Local {
+        let void = self.reduce.module.void_type();
+        self.spec.local(TypeReference::Absolute(void))
+    }
+
+    /// Creates a local of the error type, for expressions that failed to
+    /// build, so that building can continue.  The failure has to have been
+    /// reported already; nothing is reported about the local afterwards.
+    fn error(&mut self) -> Local {
+        let error = self.reduce.module.error_type();
+        self.spec.local(TypeReference::Absolute(error))
+    }
+
+    /// Whether or not the given local failed to build; see [`error()`].
+    fn is_error(&self, local: Local) -> bool {
+        self.reduce.module.is_error(self.spec.type_of(local))
+    }
+
+    /// Checks that the function at the given path can be called from this
+    /// function, emitting a diagnostic if it can't.  See
+    /// [`resolve::visible`].
+    fn check_visible(&self, path: &Path<'s>, span: Span) {
+        if !resolve::visible(self.reduce, self.annotation.scope(), path) {
+            resolve::not_exported(&self.reduce.set, span, format!("function {}", path));
+        }
+    }
+
+    /// Checks that the named field of the given type can be accessed from
+    /// this function.  Fields that aren't exported can only be accessed from
+    /// within the module the type is defined in.
+    fn check_field(&self, id: TypeId, name: &str, span: Span) {
+        let exported = match self.reduce.module.get_type(id).map(|t| &t.definition) {
+            Some(TypeDefinition::Struct(fields)) => fields.get(name).is_none_or(|f| f.export),
+            _ => true,
+        };
+        let path = match self.reduce.path_of(id) {
+            Some(path) => path,
+            None => return,
+        };
+
+        if !exported && !resolve::within(self.annotation.scope(), path) {
+            let what = format!("field {} of type {}", name, path);
+            resolve::not_exported(&self.reduce.set, span, what);
+        }
+    }
+
+    fn unsupported(&mut self, span: Span, what: &str) -> Local {
+        self.reduce.set.emit(
+            Name::Unsupported,
+            span,
+            format!("{} are not currently supported", what),
+        );
+        self.error()
+    }
+
+    fn statement(&mut self, statement: &'s Statement) {
+        match statement {
+            Statement::Expression(expr) => {
+                self.expression(expr);
+            }
+            Statement::Let(let_) => self.let_(let_),
+            Statement::Return(return_) => {
+                let value = return_
+                    .value()
+                    .as_ref()
+                    .map(|v| (self.expression(v), v.span()));
+                self.check_return(value, return_.span());
+                self.push(Operation::Return(value.map(|(v, _)| v)));
+                // Anything after the return is unreachable, but it still
+                // needs somewhere to go.
+                self.block = self.spec.block();
+            }
+            Statement::If(if_) => self.if_(if_),
+            Statement::Unless(unless) => self.unless(unless),
+            Statement::While(while_) => self.while_(while_),
+            Statement::For(for_) => self.for_(for_),
+            Statement::Try(try_) => self.try_(try_),
+        }
+    }
+
+    fn let_(&mut self, let_: &'s Let) {
+        let value = let_.value().as_ref().map(|v| self.expression(v));
+        let kind = match (let_.kind(), value) {
+            (Some(kind), _) => resolve::kind(self.reduce, &self.annotation, kind),
+            (None, Some(value)) => self.kind_of(value),
+            (None, None) => TypeReference::Absolute(self.reduce.module.void_type()),
+        };
+
+        if let (Some(_), Some(value), Some(expr)) = (let_.kind(), value, let_.value()) {
+            self.expect(Name::MismatchedLet, value, &kind, expr.span());
+        }
+
+        let local = self.spec.local(kind);
+        if let Some(value) = value {
+            self.push(Operation::Copy(local, value));
+        }
+        self.bind(let_.token(), local);
+    }
+
+    fn if_(&mut self, if_: &'s If) {
+        let end = self.spec.block();
+        for condition in if_.conditions() {
+            match condition.condition() {
+                Some(expr) => {
+                    let value = self.expression(expr);
+                    self.check_condition(value, expr.span());
+                    let then = self.spec.block();
+                    let next = self.spec.block();
+                    self.push(Operation::Branch(value, then, next));
+                    self.block = then;
+                    self.group(condition.body());
+                    self.jump(end);
+                    self.block = next;
+                }
+                None => {
+                    self.group(condition.body());
+                }
+            }
+        }
+
+        self.jump(end);
+        self.block = end;
+    }
+
+    fn unless(&mut self, unless: &'s Unless) {
+        let value = self.expression(unless.condition());
+        self.check_condition(value, unless.condition().span());
+        let then = self.spec.block();
+        let end = self.spec.block();
+        self.push(Operation::Branch(value, end, then));
+        self.block = then;
+        self.group(unless.body());
+        self.jump(end);
+        self.block = end;
+    }
+
+    fn while_(&mut self, while_: &'s While) {
+        let head = self.spec.block();
+        let then = self.spec.block();
+        let end = self.spec.block();
+        self.jump(head);
+        self.block = head;
+        let value = self.expression(while_.condition());
+        self.check_condition(value, while_.condition().span());
+        self.push(Operation::Branch(value, then, end));
+        self.block = then;
+        self.group(while_.body());
+        self.jump(head);
+        self.block = end;
+    }
+
+    /// Builds the given closure in a block that can't be reached.  This is
+    /// used for statements that can't be built yet, so that the names in
+    /// them are still resolved and their types still checked.
+    fn detached(&mut self, func: impl FnOnce(&mut Self)) {
+        let resume = self.block;
+        self.block = self.spec.block();
+        func(self);
+        self.block = resume;
+    }
+
+    fn for_(&mut self, for_: &'s For) {
+        self.unsupported(for_.span(), "for loops");
+        self.expression(for_.iterator());
+        self.detached(|body| {
+            body.open_scope();
+            let local = body.error();
+            body.bind(for_.local(), local);
+            body.group(for_.body());
+            body.close_scope();
+        });
+    }
+
+    fn try_(&mut self, try_: &'s Try) {
+        self.unsupported(try_.span(), "try statements");
+        self.detached(|body| {
+            body.group(try_.base());
+            for catch in try_.catch() {
+                body.open_scope();
+                let kind = resolve::kind(body.reduce, &body.annotation, catch.kind());
+                let local = body.spec.local(kind);
+                body.bind(catch.local(), local);
+                body.group(catch.body());
+                body.close_scope();
+            }
+            if let Some(last) = try_.last() {
+                body.group(last);
+            }
+        });
+    }
+}
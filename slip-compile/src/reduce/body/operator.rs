@@ -0,0 +1,268 @@
+//! Resolves operators.  Operators on primitive types map directly onto
+//! builtin operations.  Operators on any other type are desugared into a
+//! call to the function named after the operator, defined on the type of the
+//! left-hand operand - so `a + b` becomes `A.+(a, b)`, `a[i]` becomes
+//! `A.[](a, i)`, and `a <=> b` becomes `A.<=>(a, b)`.
+//!
+//! Unary operators use the Ruby convention of marking the position of the
+//! operand with an `@`; so `-a` becomes `A.-@(a)`, `++a` becomes `A.++@(a)`,
+//! and `a++` becomes `A.@++(a)`.  The exceptions are `!` and `~`, which have
+//! no binary form, and so keep their name.  Finally, `a != b` has no function
+//! of its own - it is always `!(a == b)`.
+//!
+//! `&&` and `||` on primitive types short-circuit: the right operand is only
+//! evaluated if the left one doesn't already decide the result.  On any other
+//! type, they're calls like any other operator, and so evaluate both.
+
+use super::Body;
+use crate::diag::{Emission, Name, Span};
+use crate::stream::{Token, TokenKind};
+use crate::syn::function::expression::InfixOperation;
+use crate::syn::{BasicNode, Item};
+use slip_typal::module::{Builtin, Local, Operation, TypeReference};
+
+impl<'r, 's> Body<'r, 's> {
+    /// Applies the given binary operator to the operands.  The spans are
+    /// those of the left and right operands, respectively.
+    pub(super) fn binary(
+        &mut self,
+        op: &'s Token,
+        left: Local,
+        right: Local,
+        spans: [Span; 2],
+    ) -> Local {
+        // The operator is looked up on the left operand, so there's nothing
+        // to look it up on if that failed to build.
+        if self.is_error(left) {
+            return self.error();
+        } else if self.is_primitive(left) {
+            return match binary_builtin(op.kind()) {
+                Some(builtin) => {
+                    // Both operands have to be of the same type; a literal
+                    // takes on the type of the other operand.
+                    if self.literals.contains(&left) && !self.literals.contains(&right) {
+                        let kind = self.kind_of(right);
+                        self.expect(Name::MismatchedType, left, &kind, spans[0]);
+                    } else {
+                        let kind = self.kind_of(left);
+                        self.expect(Name::MismatchedType, right, &kind, spans[1]);
+                    }
+                    self.builtin(builtin, vec![left, right])
+                }
+                None => self.missing_operator(op.value().unwrap_or(""), op.span(), left),
+            };
+        }
+
+        if op.kind() == TokenKind::NotEqual {
+            let equal = self.overload("==", op.span(), vec![left, right], &spans);
+            return self.builtin(Builtin::LogicalNot, vec![equal]);
+        }
+
+        let name = op.value().unwrap_or("");
+        self.overload(name, op.span(), vec![left, right], &spans)
+    }
+
+    /// Applies `&&` or `||` to the operands, evaluating the right operand
+    /// only if it's needed; see the module documentation.
+    pub(super) fn logical(&mut self, infix: &'s InfixOperation) -> Local {
+        let left = self.expression(infix.left());
+        if self.is_error(left) || !self.is_primitive(left) {
+            let right = self.expression(infix.right());
+            let spans = [infix.left().span(), infix.right().span()];
+            return self.binary(infix.operator(), left, right, spans);
+        }
+
+        // The result is the left operand, unless it doesn't decide the
+        // result, in which case it's the right operand.
+        let kind = self.kind_of(left);
+        let result = self.spec.local(kind.clone());
+        self.push(Operation::Copy(result, left));
+        let rest = self.spec.block();
+        let end = self.spec.block();
+        if infix.operator().kind() == TokenKind::LogicalAnd {
+            self.push(Operation::Branch(left, rest, end));
+        } else {
+            self.push(Operation::Branch(left, end, rest));
+        }
+
+        self.block = rest;
+        let right = self.expression(infix.right());
+        self.expect(Name::MismatchedType, right, &kind, infix.right().span());
+        self.push(Operation::Copy(result, right));
+        self.jump(end);
+        self.block = end;
+        result
+    }
+
+    /// Applies the given unary operator to the operand.  Since `++` and `--`
+    /// can be applied either before or after the operand, `suffix` denotes
+    /// which one it is.  The span is that of the operand.
+    pub(super) fn unary(
+        &mut self,
+        op: &'s Token,
+        operand: Local,
+        suffix: bool,
+        span: Span,
+    ) -> Local {
+        if self.is_error(operand) {
+            return self.error();
+        } else if self.is_primitive(operand) {
+            return match op.kind() {
+                TokenKind::Plus => operand,
+                TokenKind::Minus => self.builtin(Builtin::Negate, vec![operand]),
+                TokenKind::LogicalNot => self.builtin(Builtin::LogicalNot, vec![operand]),
+                TokenKind::BitwiseNot => self.builtin(Builtin::BitwiseNot, vec![operand]),
+                TokenKind::DoublePlus => self.builtin(Builtin::Increment, vec![operand]),
+                TokenKind::DoubleMinus => self.builtin(Builtin::Decrement, vec![operand]),
+                _ => self.missing_operator(op.value().unwrap_or(""), op.span(), operand),
+            };
+        }
+
+        let name = match (op.kind(), suffix) {
+            (TokenKind::Plus, _) => "+@",
+            (TokenKind::Minus, _) => "-@",
+            (TokenKind::DoublePlus, false) => "++@",
+            (TokenKind::DoubleMinus, false) => "--@",
+            (TokenKind::DoublePlus, true) => "@++",
+            (TokenKind::DoubleMinus, true) => "@--",
+            _ => op.value().unwrap_or(""),
+        };
+
+        self.overload(name, op.span(), vec![operand], &[span])
+    }
+
+    /// Subscripts the first argument with the rest, i.e. `a[b, c]`.  Of the
+    /// primitive types, only pointers can be indexed.  The spans are those
+    /// of each of the arguments, and of the subscript as a whole.
+    pub(super) fn subscript(&mut self, arguments: Vec<Local>, spans: &[Span], span: Span) -> Local {
+        if self.is_error(arguments[0]) {
+            return self.error();
+        } else if self.is_primitive(arguments[0]) {
+            return match self.kind_of(arguments[0]) {
+                TypeReference::Mix(_, generics) if generics.len() == 1 => {
+                    self.builtin(Builtin::Index, arguments)
+                }
+                _ => {
+                    let message = format!("type {} cannot be indexed", self.describe(arguments[0]));
+                    self.reduce.set.emit(Name::UnknownOperator, span, message);
+                    self.error()
+                }
+            };
+        }
+
+        self.overload("[]", span, arguments, spans)
+    }
+
+    /// Calls the operator function with the given name on the type of the
+    /// first argument.  The operator function has to take exactly as many
+    /// parameters as there are arguments, including `self`.  The spans are
+    /// those of each of the arguments, so that a mismatched argument is
+    /// reported at the operand, rather than the operator.
+    fn overload(&mut self, name: &str, span: Span, arguments: Vec<Local>, spans: &[Span]) -> Local {
+        let base = arguments[0];
+        let path = match self.path_of(base) {
+            Some(path) => path.with_fname(Some(name.to_string())),
+            None => return self.missing_operator(name, span, base),
+        };
+
+        let definitions = self
+            .reduce
+            .annotated
+            .get(&path)
+            .cloned()
+            .unwrap_or_default();
+        let candidates = definitions
+            .iter()
+            .filter(|annotation| match annotation.item() {
+                Item::Function(func) => func.parameters().len() == arguments.len(),
+                _ => false,
+            })
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            if definitions.is_empty() {
+                // The operator may be defined by a dependency instead.
+                return match self.reduce.func(&path) {
+                    Some(id) => self.invoke(id, arguments, spans, span),
+                    None => self.missing_operator(name, span, base),
+                };
+            }
+
+            let message = format!(
+                "operator {} for type {} does not take {} operand(s)",
+                name,
+                self.describe(base),
+                arguments.len()
+            );
+            self.reduce.set.emit(Name::UnknownOperator, span, message);
+            return self.error();
+        } else if candidates.len() > 1 {
+            let spans = candidates.iter().map(|c| c.span()).collect::<Vec<_>>();
+            self.ambiguous_operator(name, span, base, &spans);
+        }
+
+        // The first of the candidates is the one that's accepted.
+        match self.reduce.overload(candidates[0]) {
+            Some(id) => {
+                self.check_visible(&path, span);
+                self.invoke(id, arguments, spans, span)
+            }
+            None => self.missing_operator(name, span, base),
+        }
+    }
+
+    fn missing_operator(&mut self, name: &str, span: Span, base: Local) -> Local {
+        let message = format!(
+            "no operator {} defined for type {}",
+            name,
+            self.describe(base)
+        );
+        self.reduce.set.emit(Name::UnknownOperator, span, message);
+        self.error()
+    }
+
+    fn ambiguous_operator(&mut self, name: &str, span: Span, base: Local, candidates: &[Span]) {
+        let message = format!(
+            "ambiguous operator {} for type {}",
+            name,
+            self.describe(base)
+        );
+        let mut emission = Emission::new(Name::AmbiguousOperator, span, message);
+        let mut candidates = candidates.iter();
+        if let Some(first) = candidates.next() {
+            if self.reduce.set.active(Name::AcceptedOperator) {
+                emission = emission.with_label(*first, "accepted operator");
+            }
+        }
+        if self.reduce.set.active(Name::PossibleOperator) {
+            for given in candidates {
+                emission = emission.with_label(*given, "possible operator");
+            }
+        }
+        self.reduce.set.report(emission);
+    }
+}
+
+/// The builtin operation for the given binary operator on primitive types.
+fn binary_builtin(kind: TokenKind) -> Option<Builtin> {
+    match kind {
+        TokenKind::Plus => Some(Builtin::Add),
+        TokenKind::Minus => Some(Builtin::Subtract),
+        TokenKind::Star => Some(Builtin::Multiply),
+        TokenKind::Divide => Some(Builtin::Divide),
+        TokenKind::Modulo => Some(Builtin::Remainder),
+        TokenKind::LeftShift => Some(Builtin::ShiftLeft),
+        TokenKind::RightShift => Some(Builtin::ShiftRight),
+        TokenKind::BitwiseAnd => Some(Builtin::BitwiseAnd),
+        TokenKind::BitwiseOr => Some(Builtin::BitwiseOr),
+        TokenKind::BitwiseXor => Some(Builtin::BitwiseXor),
+        TokenKind::Compare => Some(Builtin::Equal),
+        TokenKind::NotEqual => Some(Builtin::NotEqual),
+        TokenKind::LessThan => Some(Builtin::LessThan),
+        TokenKind::LessThanEqual => Some(Builtin::LessThanEqual),
+        TokenKind::GreaterThan => Some(Builtin::GreaterThan),
+        TokenKind::GreaterThanEqual => Some(Builtin::GreaterThanEqual),
+        TokenKind::Spaceship => Some(Builtin::Compare),
+        _ => None,
+    }
+}
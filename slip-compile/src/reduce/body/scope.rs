@@ -0,0 +1,132 @@
+//! Resolves the names of locals in a function body.  Every statement group
+//! introduces a new scope; a local is visible from the point it is bound
+//! until the end of the scope it was bound in.  Parameters are bound in the
+//! outermost scope, so they are visible throughout the whole body.
+//!
+//! Binding a name that is already visible shadows the earlier local, and
+//! a local that is never used by the end of its scope is reported as
+//! unused.  Locals whose names start with an underscore are exempt from
+//! both of those.
+
+use super::Body;
+use crate::diag::{Name, Span};
+use crate::stream::Token;
+use crate::syn::function::statement::StatementGroup;
+use slip_typal::module::Local;
+
+/// The scopes that are currently open, innermost last.
+#[derive(Debug, Default)]
+pub(super) struct Scopes<'s>(Vec<Vec<Binding<'s>>>);
+
+#[derive(Debug)]
+struct Binding<'s> {
+    name: &'s str,
+    local: Local,
+    span: Span,
+    used: bool,
+}
+
+impl<'s> Scopes<'s> {
+    fn push(&mut self) {
+        self.0.push(vec![]);
+    }
+
+    /// Closes the innermost scope, returning the bindings in it that were
+    /// never used.
+    fn pop(&mut self) -> Vec<(&'s str, Span)> {
+        self.0
+            .pop()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|binding| !binding.used && !binding.name.starts_with('_'))
+            .map(|binding| (binding.name, binding.span))
+            .collect()
+    }
+
+    /// Binds the name in the innermost scope, returning the span of the
+    /// binding that it shadows, if any.
+    fn bind(&mut self, name: &'s str, local: Local, span: Span) -> Option<Span> {
+        let previous = self.find(name).map(|binding| binding.span);
+        let used = name == "self";
+        if self.0.is_empty() {
+            self.push();
+        }
+        self.0.last_mut().unwrap().push(Binding {
+            name,
+            local,
+            span,
+            used,
+        });
+        previous
+    }
+
+    fn find(&mut self, name: &str) -> Option<&mut Binding<'s>> {
+        self.0
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|binding| binding.name == name)
+    }
+
+    /// Looks up the local bound to the given name, marking it as used.
+    pub(super) fn lookup(&mut self, name: &str) -> Option<Local> {
+        self.find(name).map(|binding| {
+            binding.used = true;
+            binding.local
+        })
+    }
+}
+
+impl<'r, 's> Body<'r, 's> {
+    /// Binds the given token to the local in the current scope.  Tokens
+    /// without a name (e.g. `_`) aren't bound at all.
+    pub(super) fn bind(&mut self, token: &'s Token, local: Local) {
+        let name = match token.value() {
+            Some(name) => name,
+            None => return,
+        };
+
+        let previous = self.scopes.bind(name, local, token.span());
+        if let Some(previous) = previous.filter(|_| !name.starts_with('_')) {
+            let message = format!(
+                "local {} shadows an earlier local of the same name, defined on line {}",
+                name,
+                previous.start().line()
+            );
+            self.reduce
+                .set
+                .emit(Name::ShadowedLocal, token.span(), message);
+        }
+    }
+
+    /// Binds `self` to the given local in the current scope.  `self` can't
+    /// be shadowed, and doesn't have to be used.
+    pub(super) fn bind_self(&mut self, span: Span, local: Local) {
+        self.scopes.bind("self", local, span);
+    }
+
+    pub(super) fn open_scope(&mut self) {
+        self.scopes.push();
+    }
+
+    /// Closes the innermost scope, reporting any locals in it that were
+    /// never used.
+    pub(super) fn close_scope(&mut self) {
+        for (name, span) in self.scopes.pop() {
+            let message = format!("local {} is never used", name);
+            self.reduce.set.emit(Name::UnusedLocal, span, message);
+        }
+    }
+
+    /// Builds the statement group in its own scope, returning the value of
+    /// its tail expression, if it has one.
+    pub(super) fn group(&mut self, group: &'s StatementGroup) -> Option<Local> {
+        self.open_scope();
+        for statement in group {
+            self.statement(statement);
+        }
+        let value = group.tail().map(|tail| self.expression(tail));
+        self.close_scope();
+        value
+    }
+}
@@ -0,0 +1,220 @@
+//! Bounds and defaults on generics.  A generic can be declared with bounds,
+//! e.g. `fn sort<T: Compare + Equal>(...)`, and with a default, e.g.
+//! `struct Map<K, V = i32>`.
+//!
+//! Each bound names a capability; a type has the capability if it defines
+//! all of the functions that the capability requires, e.g. a type is
+//! `Compare` if it defines the `<=>` operator.  Primitive types have every
+//! capability, as their operators are built in.  The capabilities are fixed;
+//! see [`CAPABILITIES`].
+//!
+//! Bounds are checked when the generics are instantiated - when a generic
+//! type is referenced with concrete generics, or when a generic function is
+//! called.  Generics that are themselves generic (e.g. `Box<T>` within
+//! another generic item) are checked when that item is instantiated instead.
+
+use super::{resolve, Annotation, Reduce};
+use crate::diag::{Name, Span};
+use crate::syn::{BasicNode, Type};
+use slip_typal::module::{FunctionId, TypeId, TypeReference};
+use std::sync::Arc;
+
+/// The capabilities that a generic can be bound by, along with the
+/// functions that a type needs to define in order to have the capability.
+static CAPABILITIES: &[(&str, &[&str])] = &[
+    ("Equal", &["=="]),
+    ("Compare", &["<=>"]),
+    ("Hash", &["hash"]),
+    ("Default", &["default"]),
+    ("Add", &["+"]),
+    ("Subtract", &["-"]),
+    ("Multiply", &["*"]),
+    ("Divide", &["/"]),
+    ("Remainder", &["%"]),
+    ("Negate", &["-@"]),
+    ("Index", &["[]"]),
+];
+
+/// Checks that every bound on the generics declared by the item is a known
+/// capability.  This is done once, where the item is declared, so that the
+/// instantiations of the item can skip over unknown bounds.
+pub(super) fn declare(reduce: &Reduce<'_>, annotation: &Annotation<'_>) {
+    let generics = annotation.item().generics().into_iter().flatten();
+    for bound in generics.flat_map(Type::bounds) {
+        let name = bound.to_name().to_string();
+        if capability(&name).is_none() {
+            let known = CAPABILITIES
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>();
+            let message = format!(
+                "unknown capability {}; the known capabilities are {}",
+                name,
+                known.join(", ")
+            );
+            reduce
+                .set
+                .emit(Name::UnknownCapability, bound.span(), message);
+        }
+    }
+}
+
+/// Instantiates the generics of the given type.  The type may be given
+/// fewer generics than it declares if the rest have defaults.  If it's given
+/// too many or too few, this emits a diagnostic, and returns `None`.
+pub(super) fn type_generics<'s>(
+    reduce: &mut Reduce<'s>,
+    id: TypeId,
+    given: Vec<TypeReference>,
+    span: Span,
+) -> Option<Vec<TypeReference>> {
+    let annotation = match reduce.path_of(id).and_then(|p| reduce.annotated.get(p)) {
+        Some(annotations) => annotations[0].clone(),
+        None => return Some(given),
+    };
+    let declared = annotation.generic_list().collect::<Vec<_>>();
+    let required = declared.iter().filter(|g| g.default().is_none()).count();
+
+    if given.len() > declared.len() || given.len() < required {
+        let takes = if required == declared.len() {
+            declared.len().to_string()
+        } else if given.len() < required {
+            format!("at least {}", required)
+        } else {
+            format!("at most {}", declared.len())
+        };
+        let message = format!(
+            "type {} takes {} generic(s), but {} were given",
+            annotation.to_path().to_name(),
+            takes,
+            given.len()
+        );
+        reduce.set.emit(Name::Generics, span, message);
+        return None;
+    }
+
+    let given = given.into_iter().map(Some).collect();
+    Some(instantiate(reduce, annotation, given, span))
+}
+
+/// Instantiates the generics of the given function, as inferred from the
+/// arguments of a call to it.  Generics that couldn't be inferred take on
+/// their default, if they have one.
+pub(super) fn func_generics<'s>(
+    reduce: &mut Reduce<'s>,
+    id: FunctionId,
+    inferred: Vec<Option<TypeReference>>,
+    span: Span,
+) -> Vec<TypeReference> {
+    let annotation = reduce
+        .funcs
+        .iter()
+        .find(|(_, fid)| **fid == id)
+        .and_then(|(path, _)| reduce.annotated.get(path))
+        .map(|annotations| annotations[0].clone());
+    match annotation {
+        Some(annotation) => instantiate(reduce, annotation, inferred, span),
+        None => uninferred(inferred),
+    }
+}
+
+fn instantiate<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: Arc<Annotation<'s>>,
+    mut given: Vec<Option<TypeReference>>,
+    span: Span,
+) -> Vec<TypeReference> {
+    let declared = annotation.generic_list().collect::<Vec<_>>();
+    given.resize(declared.len(), None);
+
+    let mut generics = vec![];
+    for (generic, kind) in declared.iter().zip(given) {
+        let kind = match (kind, generic.default()) {
+            (Some(kind), _) => kind,
+            // A default may refer to the generics before it.
+            (None, Some(default)) => {
+                resolve::kind(reduce, &annotation, default).substitute(&generics)
+            }
+            // Only the generics of a function can be left out, when they
+            // can't be inferred from the arguments.
+            (None, None) => {
+                let message = format!(
+                    "the generic {} of {} could not be inferred",
+                    generic,
+                    annotation.to_path().to_name()
+                );
+                reduce.set.emit(Name::Generics, span, message);
+                TypeReference::Absolute(reduce.module.error_type())
+            }
+        };
+
+        for bound in generic.bounds() {
+            check(reduce, &annotation, generic, bound, &kind, span);
+        }
+        generics.push(kind);
+    }
+
+    generics
+}
+
+/// Checks that the type given for the generic has the capability named by
+/// the bound, emitting a diagnostic naming the capability if it doesn't.
+fn check(
+    reduce: &mut Reduce<'_>,
+    annotation: &Annotation<'_>,
+    generic: &Type,
+    bound: &Type,
+    kind: &TypeReference,
+    span: Span,
+) {
+    let id = match kind {
+        TypeReference::Absolute(id) | TypeReference::Mix(id, _) => *id,
+        TypeReference::Generic(_) => return,
+    };
+    let name = bound.to_name().to_string();
+    let required = match capability(&name) {
+        Some(required) => required,
+        None => return,
+    };
+    let primitive = reduce
+        .module
+        .get_type(id)
+        .is_none_or(|t| t.definition.is_primitive());
+    let path = match reduce.path_of(id) {
+        Some(path) if !primitive => path.clone(),
+        _ => return,
+    };
+
+    let missing = required.iter().find(|func| {
+        reduce
+            .func(&path.clone().with_fname(Some(**func)))
+            .is_none()
+    });
+    if let Some(func) = missing {
+        let message = format!(
+            "type {} does not have the capability {}, required by the generic {} of {}; it has no function {}",
+            path,
+            name,
+            generic,
+            annotation.to_path().to_name(),
+            func
+        );
+        reduce.set.emit(Name::MissingCapability, span, message);
+    }
+}
+
+fn capability(name: &str) -> Option<&'static [&'static str]> {
+    CAPABILITIES
+        .iter()
+        .find(|(capability, _)| *capability == name)
+        .map(|(_, required)| *required)
+}
+
+/// Leaves the generics that weren't inferred as they are.
+fn uninferred(generics: Vec<Option<TypeReference>>) -> Vec<TypeReference> {
+    generics
+        .into_iter()
+        .enumerate()
+        .map(|(i, kind)| kind.unwrap_or(TypeReference::Generic(i as u64)))
+        .collect()
+}
@@ -0,0 +1,548 @@
+//! Evaluates constant expressions.  These are the values of `const` items,
+//! e.g. `const MAX: i32 = 1 << 8;`, and the discriminants of enums, e.g.
+//! `enum Color { Red = 1, Green }`.  They are evaluated while the module is
+//! reduced, and the results are stored in the module; a reference to a
+//! constant in a function body is replaced with its value.
+//!
+//! Only a subset of expressions are constant: literals, the arithmetic,
+//! bitwise, logical, and comparison operators, references to other
+//! constants, and enum discriminants.  Anything else - a local, a call, a
+//! struct - is reported as a non-const expression.  Integers are evaluated
+//! as 64-bit signed integers, and then checked against the type of the
+//! constant once the whole expression is evaluated.
+//!
+//! Constants are looked up like functions: from the innermost module the
+//! reference is in, outwards, and then through the imports in scope.
+//! Constants can reference each other regardless of the order they were
+//! defined in, as they're evaluated on demand; a constant that ends up
+//! referencing itself is reported as a cycle.
+
+use super::{resolve, Annotation, Path, Reduce};
+use crate::diag::{Name, Span};
+use crate::error::Error;
+use crate::stream::{Token, TokenKind};
+use crate::syn::function::expression::{Atom, Expression, InfixOperation, PrefixOperation};
+use crate::syn::{BasicNode, Const as SynConst, Type};
+use slip_typal::module::{Const, ConstId, Constant, Enum, TypeDefinition, TypeReference};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::num::ParseIntError;
+use std::sync::Arc;
+
+/// A value computed from a constant expression.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Value {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Bool(bool),
+}
+
+impl Value {
+    pub(super) fn describe(&self) -> &'static str {
+        match self {
+            Value::Integer(_) => "an integer",
+            Value::Float(_) => "a float",
+            Value::String(_) => "a string",
+            Value::Bool(_) => "a bool",
+        }
+    }
+}
+
+impl From<Value> for Constant {
+    fn from(value: Value) -> Constant {
+        match value {
+            Value::Integer(value) => Constant::Integer(value as u64),
+            Value::Float(value) => Constant::Float(value),
+            Value::String(value) => Constant::String(value),
+            Value::Bool(value) => Constant::Bool(value),
+        }
+    }
+}
+
+impl From<Constant> for Value {
+    fn from(constant: Constant) -> Value {
+        match constant {
+            Constant::Integer(value) => Value::Integer(value as i64),
+            Constant::Float(value) => Value::Float(value),
+            Constant::String(value) => Value::String(value),
+            Constant::Bool(value) => Value::Bool(value),
+        }
+    }
+}
+
+/// Parses an integer literal; these can be given in hexadecimal (`0x1f`),
+/// binary (`0b101`), or octal (`017`), as well as in decimal.
+pub(super) fn integer(value: &str) -> Result<u64, ParseIntError> {
+    if let Some(hex) = value.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16)
+    } else if let Some(binary) = value.strip_prefix("0b") {
+        u64::from_str_radix(binary, 2)
+    } else if value.len() > 1 && value.starts_with('0') {
+        u64::from_str_radix(&value[1..], 8)
+    } else {
+        value.parse::<u64>()
+    }
+}
+
+/// Evaluates the given `const` item, and adds it to the module.  A constant
+/// whose value couldn't be evaluated is still added, with a placeholder
+/// value, so that references to it don't report any more errors.
+pub(super) fn build<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: Arc<Annotation<'s>>,
+    const_: &'s SynConst,
+) -> Result<(), Error> {
+    let path = annotation.to_path();
+    reduce.evaluating.insert(path.clone());
+    let kind = resolve::kind(reduce, &annotation, const_.kind());
+    let value = evaluate(reduce, &annotation, const_.value());
+    reduce.evaluating.remove(&path);
+
+    let value = check(reduce, const_, &kind, value);
+    let id = reduce.module.const_push(Const {
+        name: path.to_name(),
+        kind,
+        value,
+        export: annotation.is_exported(),
+    });
+    reduce.consts.insert(path, id);
+    Ok(())
+}
+
+/// Evaluates the given expression, reporting any part of it that isn't
+/// constant.  If the expression couldn't be evaluated, the errors have
+/// already been reported.
+pub(super) fn evaluate<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: &Annotation<'s>,
+    expr: &'s Expression,
+) -> Option<Value> {
+    match expr {
+        Expression::Atom(atom) => self::atom(reduce, annotation, atom),
+        Expression::Prefix(prefix) => self::prefix(reduce, annotation, prefix),
+        Expression::Infix(infix) => self::infix(reduce, annotation, infix),
+        Expression::Suffix(_) => non_const(reduce, expr.span(), "suffix operators"),
+        Expression::Index(_) => non_const(reduce, expr.span(), "indexing"),
+        Expression::Call(_) => non_const(reduce, expr.span(), "function calls"),
+        Expression::Access(_) => non_const(reduce, expr.span(), "field accesses"),
+    }
+}
+
+/// Finds the constant that the given name refers to, from within the scope
+/// of the annotation; for a member, e.g. `Limits::MAX`, the name is split
+/// into the type and the member name.  This returns `None` if the name
+/// doesn't refer to any constant, without reporting anything, as the name
+/// could still refer to something else.
+pub(super) fn find<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: &Annotation<'s>,
+    kind: &'s Type,
+    name: Option<&'s str>,
+) -> Option<Path<'s>> {
+    if kind.generics().is_some() {
+        return None;
+    }
+
+    let with_name = |(span, path): (Span, Path<'s>)| {
+        let mut parts = path.base.into_owned();
+        parts.extend(name.map(Cow::Borrowed));
+        (span, Path::new(parts, None))
+    };
+    let scope = annotation.scope();
+    let mut in_scope = resolve::in_scope_references(scope, kind)
+        .map(with_name)
+        .collect::<Vec<_>>();
+    // The innermost definition takes precedence over the outer ones.
+    in_scope.reverse();
+    let found = match in_scope.into_iter().find(|(_, path)| reduce.is_const(path)) {
+        Some(found) => Some(found),
+        None => {
+            let imported = resolve::imported_references(scope, kind)
+                .map(with_name)
+                .collect::<Vec<_>>();
+            let found = imported.into_iter().find(|(_, path)| reduce.is_const(path));
+            if let Some((span, _)) = found.as_ref() {
+                reduce.used_imports.insert(*span);
+            }
+            found
+        }
+    };
+
+    let (_, path) = found?;
+    if !resolve::visible(reduce, scope, &path) {
+        resolve::not_exported(&reduce.set, kind.span(), format!("constant {}", path));
+    }
+    Some(path)
+}
+
+/// Retrieves the constant at the given path, which was found with
+/// [`find`], evaluating it first if it hasn't been evaluated yet.
+pub(super) fn demand<'s>(reduce: &mut Reduce<'s>, path: &Path<'s>, span: Span) -> Option<ConstId> {
+    if reduce.evaluating.contains(path) {
+        reduce.set.emit(
+            Name::ConstCycle,
+            span,
+            format!("constant {} depends on its own value", path),
+        );
+        return None;
+    }
+
+    reduce.constant(path)
+}
+
+fn atom<'s>(reduce: &mut Reduce<'s>, annotation: &Annotation<'s>, atom: &'s Atom) -> Option<Value> {
+    match atom {
+        Atom::Integer(tok) => {
+            let value = tok.value().unwrap_or("0");
+            match integer(value) {
+                Ok(value) => Some(Value::Integer(value as i64)),
+                Err(_) => {
+                    let message = format!("invalid integer literal {}", value);
+                    reduce.set.emit(Name::LiteralError, tok.span(), message);
+                    None
+                }
+            }
+        }
+        Atom::Float(tok) => {
+            let value = tok.value().unwrap_or("0.0");
+            match value.parse::<f64>() {
+                Ok(value) => Some(Value::Float(value)),
+                Err(_) => {
+                    let message = format!("invalid float literal {}", value);
+                    reduce.set.emit(Name::LiteralError, tok.span(), message);
+                    None
+                }
+            }
+        }
+        Atom::Bool(tok) => Some(Value::Bool(tok.kind() == TokenKind::True)),
+        Atom::SingleString(tok) | Atom::DoubleString(tok) => {
+            let value = tok.value().unwrap_or("\"\"");
+            Some(Value::String(value[1..(value.len() - 1)].to_string()))
+        }
+        Atom::Type(kind) => reference(reduce, annotation, kind, None, kind.span()),
+        Atom::Member(member) => {
+            let name = member.name().value();
+            match find(reduce, annotation, member.kind(), name) {
+                Some(path) => value_of(reduce, &path, member.span()),
+                None => discriminant(reduce, annotation, member.kind(), member.name()),
+            }
+        }
+        Atom::Ident(tok) => non_const(reduce, tok.span(), "locals"),
+        Atom::This(tok) => non_const(reduce, tok.span(), "self"),
+        Atom::Underscore(tok) => non_const(reduce, tok.span(), "placeholders"),
+        Atom::Construct(construct) => non_const(reduce, construct.span(), "structs"),
+        Atom::Map(map) => non_const(reduce, map.span(), "map literals"),
+        Atom::Array(array) => non_const(reduce, array.span(), "array literals"),
+        Atom::Tuple(tuple) => match tuple.elements() {
+            [inner] => evaluate(reduce, annotation, inner),
+            _ => non_const(reduce, tuple.span(), "tuple literals"),
+        },
+    }
+}
+
+fn reference<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: &Annotation<'s>,
+    kind: &'s Type,
+    name: Option<&'s str>,
+    span: Span,
+) -> Option<Value> {
+    match find(reduce, annotation, kind, name) {
+        Some(path) => value_of(reduce, &path, span),
+        None => {
+            let message = format!("{} is not a constant", kind);
+            reduce.set.emit(Name::NonConstExpr, span, message);
+            None
+        }
+    }
+}
+
+fn value_of<'s>(reduce: &mut Reduce<'s>, path: &Path<'s>, span: Span) -> Option<Value> {
+    let id = demand(reduce, path, span)?;
+    let constant = reduce.module.get_const(id)?.value.clone();
+    Some(constant.into())
+}
+
+/// The discriminant of the given variant of an enum, e.g. `Color::Red`.
+fn discriminant<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: &Annotation<'s>,
+    kind: &'s Type,
+    name: &'s Token,
+) -> Option<Value> {
+    let variant = name.value().unwrap_or("");
+    let id = match resolve::kind(reduce, annotation, kind) {
+        TypeReference::Absolute(id) => id,
+        _ => return non_const(reduce, kind.span(), "generic types"),
+    };
+    let value = match reduce.module.get_type(id).map(|t| &t.definition) {
+        Some(TypeDefinition::Enum(Enum::Simple(variants))) => variants
+            .iter()
+            .position(|v| v == variant)
+            .map(|index| index as i64),
+        Some(TypeDefinition::Enum(Enum::Value(variants))) => variants
+            .iter()
+            .find(|(v, _)| v == variant)
+            .map(|(_, value)| *value as i64),
+        _ => None,
+    };
+
+    if value.is_none() {
+        let message = format!("{}::{} is not a constant", kind, variant);
+        reduce.set.emit(Name::NonConstExpr, name.span(), message);
+    }
+    value.map(Value::Integer)
+}
+
+fn prefix<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: &Annotation<'s>,
+    prefix: &'s PrefixOperation,
+) -> Option<Value> {
+    let op = prefix.operator();
+    let operand = evaluate(reduce, annotation, prefix.operand())?;
+    let result = match (op.kind(), &operand) {
+        (TokenKind::Plus, Value::Integer(_)) | (TokenKind::Plus, Value::Float(_)) => {
+            Ok(operand.clone())
+        }
+        (TokenKind::Minus, Value::Integer(value)) => value
+            .checked_neg()
+            .map(Value::Integer)
+            .ok_or(Failure::Overflow),
+        (TokenKind::Minus, Value::Float(value)) => Ok(Value::Float(-value)),
+        (TokenKind::LogicalNot, Value::Bool(value)) => Ok(Value::Bool(!value)),
+        (TokenKind::BitwiseNot, Value::Integer(value)) => Ok(Value::Integer(!value)),
+        (TokenKind::DoublePlus, _) | (TokenKind::DoubleMinus, _) => {
+            return non_const(reduce, op.span(), "increments and decrements")
+        }
+        _ => Err(Failure::Operands),
+    };
+
+    result.map(Some).unwrap_or_else(|failure| {
+        let operands = operand.describe().to_string();
+        fail(reduce, failure, op, operands, prefix.span())
+    })
+}
+
+fn infix<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: &Annotation<'s>,
+    infix: &'s InfixOperation,
+) -> Option<Value> {
+    let op = infix.operator();
+    if op.kind() == TokenKind::Equals {
+        return non_const(reduce, infix.span(), "assignments");
+    }
+
+    // Both sides are evaluated even if one of them fails, so that any error
+    // in the other is still reported.
+    let left = evaluate(reduce, annotation, infix.left());
+    let right = evaluate(reduce, annotation, infix.right());
+    let (left, right) = (left?, right?);
+    let result = match (&left, &right) {
+        (Value::Integer(a), Value::Integer(b)) => integers(op.kind(), *a, *b),
+        (Value::Float(a), Value::Float(b)) => floats(op.kind(), *a, *b),
+        (Value::Bool(a), Value::Bool(b)) => bools(op.kind(), *a, *b),
+        (Value::String(a), Value::String(b)) => match op.kind() {
+            TokenKind::Compare => Ok(Value::Bool(a == b)),
+            TokenKind::NotEqual => Ok(Value::Bool(a != b)),
+            _ => Err(Failure::Operands),
+        },
+        _ => Err(Failure::Operands),
+    };
+
+    result.map(Some).unwrap_or_else(|failure| {
+        let operands = format!("{} and {}", left.describe(), right.describe());
+        fail(reduce, failure, op, operands, infix.span())
+    })
+}
+
+/// Why an operator couldn't be applied to its operands.
+enum Failure {
+    Overflow,
+    DivisionByZero,
+    /// The operator doesn't apply to operands of those kinds.
+    Operands,
+}
+
+fn fail(
+    reduce: &Reduce<'_>,
+    failure: Failure,
+    op: &Token,
+    operands: String,
+    span: Span,
+) -> Option<Value> {
+    let op = op.value().unwrap_or("");
+    let message = match failure {
+        Failure::Overflow => format!("{} overflows in a constant", op),
+        Failure::DivisionByZero => "division by zero in a constant".to_string(),
+        Failure::Operands => format!("can't apply {} to {} in a constant", op, operands),
+    };
+    reduce.set.emit(Name::ConstEvaluation, span, message);
+    None
+}
+
+fn integers(op: TokenKind, a: i64, b: i64) -> Result<Value, Failure> {
+    let shift = || u32::try_from(b).map_err(|_| Failure::Overflow);
+    let value = match op {
+        TokenKind::Plus => a.checked_add(b),
+        TokenKind::Minus => a.checked_sub(b),
+        TokenKind::Star => a.checked_mul(b),
+        TokenKind::Divide | TokenKind::Modulo if b == 0 => return Err(Failure::DivisionByZero),
+        TokenKind::Divide => a.checked_div(b),
+        TokenKind::Modulo => a.checked_rem(b),
+        TokenKind::LeftShift => a.checked_shl(shift()?),
+        TokenKind::RightShift => a.checked_shr(shift()?),
+        TokenKind::BitwiseAnd => Some(a & b),
+        TokenKind::BitwiseOr => Some(a | b),
+        TokenKind::BitwiseXor => Some(a ^ b),
+        _ => return compare(op, a.partial_cmp(&b)),
+    };
+
+    value.map(Value::Integer).ok_or(Failure::Overflow)
+}
+
+fn floats(op: TokenKind, a: f64, b: f64) -> Result<Value, Failure> {
+    match op {
+        TokenKind::Plus => Ok(Value::Float(a + b)),
+        TokenKind::Minus => Ok(Value::Float(a - b)),
+        TokenKind::Star => Ok(Value::Float(a * b)),
+        TokenKind::Divide => Ok(Value::Float(a / b)),
+        TokenKind::Modulo => Ok(Value::Float(a % b)),
+        _ => compare(op, a.partial_cmp(&b)),
+    }
+}
+
+fn bools(op: TokenKind, a: bool, b: bool) -> Result<Value, Failure> {
+    match op {
+        TokenKind::LogicalAnd | TokenKind::BitwiseAnd => Ok(Value::Bool(a && b)),
+        TokenKind::LogicalOr | TokenKind::BitwiseOr => Ok(Value::Bool(a || b)),
+        TokenKind::BitwiseXor => Ok(Value::Bool(a ^ b)),
+        TokenKind::Compare => Ok(Value::Bool(a == b)),
+        TokenKind::NotEqual => Ok(Value::Bool(a != b)),
+        _ => Err(Failure::Operands),
+    }
+}
+
+/// Applies a comparison operator, given the ordering of the operands.  Any
+/// comparison with an unordered operand (i.e. NaN) is false, except for
+/// `!=`.
+fn compare(op: TokenKind, ordering: Option<std::cmp::Ordering>) -> Result<Value, Failure> {
+    use std::cmp::Ordering::*;
+    let result = match (op, ordering) {
+        (TokenKind::Compare, ordering) => ordering == Some(Equal),
+        (TokenKind::NotEqual, ordering) => ordering != Some(Equal),
+        (TokenKind::LessThan, ordering) => ordering == Some(Less),
+        (TokenKind::LessThanEqual, ordering) => matches!(ordering, Some(Less) | Some(Equal)),
+        (TokenKind::GreaterThan, ordering) => ordering == Some(Greater),
+        (TokenKind::GreaterThanEqual, ordering) => {
+            matches!(ordering, Some(Greater) | Some(Equal))
+        }
+        _ => return Err(Failure::Operands),
+    };
+
+    Ok(Value::Bool(result))
+}
+
+/// Checks the evaluated value against the type of the constant, converting
+/// it into the constant stored in the module.  Integers have to fit in the
+/// type; since signedness isn't part of the type, they can be anywhere from
+/// the smallest signed value to the largest unsigned value.
+fn check(
+    reduce: &mut Reduce<'_>,
+    const_: &SynConst,
+    kind: &TypeReference,
+    value: Option<Value>,
+) -> Constant {
+    let span = const_.value().span();
+    // Unknown types have already been reported; there's nothing to check
+    // the value against.
+    if reduce.module.is_error(kind) {
+        return Constant::Integer(0);
+    }
+    let (definition, placeholder) = match expected(reduce, kind) {
+        Some(expected) => expected,
+        None => {
+            let message = format!("constants of type {} are not supported", const_.kind());
+            reduce
+                .set
+                .emit(Name::Unsupported, const_.kind().span(), message);
+            return Constant::Integer(0);
+        }
+    };
+    let value = match value {
+        Some(value) => value,
+        None => return placeholder,
+    };
+
+    let fits = match (&definition, &value) {
+        (TypeDefinition::Primitive(1), Value::Bool(_)) => true,
+        (TypeDefinition::Primitive(1), _) => false,
+        (TypeDefinition::Primitive(size), Value::Integer(value)) if *size < 64 => {
+            let min = -(1i128 << (size - 1));
+            let max = (1i128 << size) - 1;
+            if !(min..=max).contains(&i128::from(*value)) {
+                let message = format!("{} does not fit in the type {}", value, const_.kind());
+                reduce.set.emit(Name::ConstEvaluation, span, message);
+                return placeholder;
+            }
+            true
+        }
+        (TypeDefinition::Primitive(_), Value::Integer(_))
+        | (TypeDefinition::PrimitiveSize, Value::Integer(_))
+        | (TypeDefinition::PrimitiveFloat(_), Value::Float(_))
+        | (TypeDefinition::PrimitivePtr, Value::String(_)) => true,
+        _ => false,
+    };
+
+    if fits {
+        value.into()
+    } else {
+        let message = format!(
+            "expected a constant of type {}, found {}",
+            const_.kind(),
+            value.describe()
+        );
+        reduce.set.emit(Name::MismatchedType, span, message);
+        placeholder
+    }
+}
+
+/// The definition of the type that a constant can have, along with a
+/// placeholder value for it.  Only primitive types, and strings, can be
+/// constants.
+fn expected(reduce: &mut Reduce<'_>, kind: &TypeReference) -> Option<(TypeDefinition, Constant)> {
+    let (id, generics) = match kind {
+        TypeReference::Absolute(id) => (*id, &[][..]),
+        TypeReference::Mix(id, generics) => (*id, &generics[..]),
+        TypeReference::Generic(_) => return None,
+    };
+    let definition = reduce.module.get_type(id)?.definition.clone();
+    let placeholder = match &definition {
+        TypeDefinition::Primitive(1) => Constant::Bool(false),
+        TypeDefinition::Primitive(_) | TypeDefinition::PrimitiveSize => Constant::Integer(0),
+        TypeDefinition::PrimitiveFloat(_) => Constant::Float(0.0),
+        // Strings are pointers to bytes.
+        TypeDefinition::PrimitivePtr if is_byte(reduce, generics) => {
+            Constant::String(String::new())
+        }
+        _ => return None,
+    };
+
+    Some((definition, placeholder))
+}
+
+fn is_byte(reduce: &mut Reduce<'_>, generics: &[TypeReference]) -> bool {
+    let byte = reduce.module.primitive_type(8);
+    generics == [TypeReference::Absolute(byte)]
+}
+
+fn non_const(reduce: &Reduce<'_>, span: Span, what: &str) -> Option<Value> {
+    reduce.set.emit(
+        Name::NonConstExpr,
+        span,
+        format!("{} can't be used in a constant", what),
+    );
+    None
+}
@@ -0,0 +1,208 @@
+//! Dependencies on prebuilt modules.  A program can be compiled against
+//! modules that have already been compiled, instead of against their source;
+//! the types, functions, and constants that such a module exports can then be
+//! referenced by their full name, as if they were defined in the source.
+//!
+//! Ids are local to the module they're defined in, so an item is copied into
+//! the module being built the first time that it's referenced, along with
+//! every type that it refers to.  Functions are copied without their bodies,
+//! as they're already compiled into the dependency.  Primitive types are
+//! shared, so that e.g. an `i32` from a dependency is the same as our own.
+
+use super::{Path, Reduce};
+use slip_typal::module::{
+    Const, ConstId, Enum, Field, FunctionId, Module, Origin, TypeDefinition, TypeId, TypeReference,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+pub(super) struct Dependencies {
+    modules: Vec<Module>,
+    /// The types that have been copied out of the dependencies, keyed by the
+    /// dependency they were copied from and their id within it.
+    types: HashMap<(usize, TypeId), TypeId>,
+    /// The functions that have been copied out of the dependencies; see
+    /// [`Dependencies::types`].
+    funcs: HashMap<(usize, FunctionId), FunctionId>,
+    /// The constants that have been copied out of the dependencies; see
+    /// [`Dependencies::types`].
+    consts: HashMap<(usize, ConstId), ConstId>,
+}
+
+impl Dependencies {
+    pub(super) fn push(&mut self, module: Module) {
+        self.modules.push(module);
+    }
+
+    /// Finds an item in the first dependency that it can be found in,
+    /// along with the index of that dependency.
+    fn find<T>(&self, mut func: impl FnMut(&Module) -> Option<T>) -> Option<(usize, T)> {
+        self.modules
+            .iter()
+            .enumerate()
+            .find_map(|(index, module)| func(module).map(|item| (index, item)))
+    }
+}
+
+/// Finds the type exported at the given path by any of the dependencies,
+/// copying it into the module being built.
+pub(super) fn kind<'s>(reduce: &mut Reduce<'s>, path: &Path<'s>) -> Option<TypeId> {
+    let name = path.to_name();
+    let (index, id) = reduce.dependencies.find(|module| {
+        module
+            .exported_types()
+            .find(|(_, kind)| kind.name == name)
+            .map(|(id, _)| *id)
+    })?;
+
+    let id = import_type(reduce, index, id);
+    reduce.types.insert(path.clone(), id);
+    Some(id)
+}
+
+/// Finds the function exported at the given path by any of the
+/// dependencies, copying its signature into the module being built.  The
+/// copy records the dependency and id that it was copied from, as its body
+/// is only in the dependency.
+pub(super) fn func<'s>(reduce: &mut Reduce<'s>, path: &Path<'s>) -> Option<FunctionId> {
+    let name = path.to_name();
+    let (index, id) = reduce.dependencies.find(|module| {
+        module
+            .exported_funcs()
+            .find(|(_, func)| func.name == name)
+            .map(|(id, _)| *id)
+    })?;
+
+    if let Some(id) = reduce.dependencies.funcs.get(&(index, id)) {
+        return Some(*id);
+    }
+
+    let func = reduce.dependencies.modules[index].funcs[&id].clone();
+    let parameters = func
+        .parameters
+        .iter()
+        .map(|kind| reference(reduce, index, kind))
+        .collect::<Vec<_>>();
+    let retval = func
+        .retval
+        .as_ref()
+        .map(|kind| reference(reduce, index, kind));
+    let imported = reduce
+        .module
+        .stub_function(func.name, func.generics, parameters, retval);
+    let origin = Origin {
+        requirement: reduce.dependencies.modules[index].name.clone(),
+        id,
+    };
+    reduce
+        .module
+        .update_function(imported, |func| func.origin = Some(origin));
+    reduce.dependencies.funcs.insert((index, id), imported);
+    reduce.funcs.insert(path.clone(), imported);
+    Some(imported)
+}
+
+/// Finds the constant exported at the given path by any of the
+/// dependencies, copying it into the module being built.
+pub(super) fn constant<'s>(reduce: &mut Reduce<'s>, path: &Path<'s>) -> Option<ConstId> {
+    let name = path.to_name();
+    let (index, id) = reduce.dependencies.find(|module| {
+        module
+            .exported_consts()
+            .find(|(_, const_)| const_.name == name)
+            .map(|(id, _)| *id)
+    })?;
+
+    if let Some(id) = reduce.dependencies.consts.get(&(index, id)) {
+        return Some(*id);
+    }
+
+    let const_ = reduce.dependencies.modules[index].consts[&id].clone();
+    let kind = reference(reduce, index, &const_.kind);
+    let imported = reduce.module.const_push(Const {
+        kind,
+        export: false,
+        ..const_
+    });
+    reduce.dependencies.consts.insert((index, id), imported);
+    reduce.consts.insert(path.clone(), imported);
+    Some(imported)
+}
+
+fn import_type(reduce: &mut Reduce<'_>, index: usize, id: TypeId) -> TypeId {
+    if let Some(id) = reduce.dependencies.types.get(&(index, id)) {
+        return *id;
+    }
+
+    let kind = match reduce.dependencies.modules[index].types.get(&id) {
+        Some(kind) => kind.clone(),
+        None => return reduce.module.void_type(),
+    };
+    let imported = match kind.definition {
+        TypeDefinition::Primitive(size) => reduce.module.primitive_type(size),
+        TypeDefinition::PrimitiveFloat(size) => reduce.module.float_type(size),
+        TypeDefinition::PrimitiveSize => reduce.module.size_type(),
+        TypeDefinition::PrimitivePtr => reduce.module.pointer_type(),
+        definition => {
+            // The type is stubbed out before its definition is copied, so
+            // that types that refer to themselves can be copied.
+            let imported = reduce.module.stub_type(kind.name, kind.generics);
+            reduce.dependencies.types.insert((index, id), imported);
+            let definition = copy_definition(reduce, index, definition);
+            reduce
+                .module
+                .update_type(imported, |kind| kind.definition = definition);
+            imported
+        }
+    };
+
+    reduce.dependencies.types.insert((index, id), imported);
+    imported
+}
+
+fn copy_definition(
+    reduce: &mut Reduce<'_>,
+    index: usize,
+    definition: TypeDefinition,
+) -> TypeDefinition {
+    match definition {
+        TypeDefinition::Struct(fields) => TypeDefinition::Struct(
+            fields
+                .into_iter()
+                .map(|(name, field)| {
+                    let kind = reference(reduce, index, &field.kind);
+                    (name, Field { kind, ..field })
+                })
+                .collect(),
+        ),
+        TypeDefinition::Enum(Enum::Unit(variants)) => TypeDefinition::Enum(Enum::Unit(
+            variants
+                .into_iter()
+                .map(|(name, kinds)| {
+                    let kinds = kinds
+                        .iter()
+                        .map(|kind| reference(reduce, index, kind))
+                        .collect();
+                    (name, kinds)
+                })
+                .collect(),
+        )),
+        TypeDefinition::Alias(kind) => TypeDefinition::Alias(reference(reduce, index, &kind)),
+        definition => definition,
+    }
+}
+
+fn reference(reduce: &mut Reduce<'_>, index: usize, kind: &TypeReference) -> TypeReference {
+    match kind {
+        TypeReference::Absolute(id) => TypeReference::Absolute(import_type(reduce, index, *id)),
+        TypeReference::Mix(id, generics) => {
+            let id = import_type(reduce, index, *id);
+            let generics = generics
+                .iter()
+                .map(|kind| reference(reduce, index, kind))
+                .collect();
+            TypeReference::Mix(id, generics)
+        }
+        TypeReference::Generic(_) => kind.clone(),
+    }
+}
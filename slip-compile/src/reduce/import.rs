@@ -0,0 +1,136 @@
+//! Lints on the `use`s in the source.  While types are resolved, every
+//! import that a type was found through is marked as used; see
+//! [`super::resolve`].  Once everything has been reduced, each import is
+//! checked, in the order it was written in:
+//!
+//! 1. An import of the same path, under the same name, as an earlier import
+//!    in the same module is a duplicate.
+//! 2. An import whose name is also the name of a type defined in the module
+//!    it is in (or any module around it) is shadowed by that type, as types
+//!    in scope are always looked at before imports.
+//! 3. Any other import that no type was found through is unused.
+//!
+//! Each of these is only a warning, and has its own diagnostic, so that each
+//! can be silenced on its own.
+
+use super::{attribute, Path, Reduce};
+use crate::diag::{Name, Span};
+use crate::syn::{Attribute, Import, Item, Type, Use};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// The `use`s written directly within a module, along with the path to the
+/// module and the attributes of it and the modules around it.  The root of
+/// a source is a module with an empty path.
+pub(super) type Uses<'s> = (Vec<&'s Type>, Vec<&'s Attribute>, Vec<&'s Use>);
+
+/// Collects the `use`s in the given items, grouped by the module that they
+/// are written directly within.
+pub(super) fn collect<'s>(
+    base: Vec<&'s Type>,
+    attributes: Vec<&'s Attribute>,
+    items: &'s [Item],
+    result: &mut Vec<Uses<'s>>,
+) {
+    let uses = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Use(use_) => Some(&**use_),
+            _ => None,
+        })
+        .collect();
+    result.push((base.clone(), attributes.clone(), uses));
+
+    for item in items {
+        if let Item::Module(module) = item {
+            let mut base = base.clone();
+            base.push(module.kind());
+            let mut attributes = attributes.clone();
+            attributes.extend(module.attributes());
+            collect(base, attributes, module.items(), result);
+        }
+    }
+}
+
+pub(super) fn lint(reduce: &Reduce<'_>) {
+    for (base, attributes, uses) in &reduce.uses {
+        let mut seen = HashMap::new();
+        for use_ in uses {
+            let attributes = attributes.iter().cloned().chain(use_.attributes());
+            reduce.set.push_overrides(attribute::overrides(attributes));
+            for import in use_.imports() {
+                let key = (import.path().to_vec(), import.name());
+                if let Some(previous) = seen.get(&key) {
+                    duplicate(reduce, &import, *previous);
+                } else if let Some(path) = shadowed_by(reduce, base, &import) {
+                    let message = format!(
+                        "import {} is shadowed by the type {}, defined in scope",
+                        describe(&import),
+                        path
+                    );
+                    reduce
+                        .set
+                        .emit(Name::ShadowedImport, import.span(), message);
+                } else if !reduce.used_imports.contains(&import.span()) {
+                    let message = format!("unused import {}", describe(&import));
+                    reduce.set.emit(Name::UnusedImport, import.span(), message);
+                }
+
+                seen.entry(key).or_insert_with(|| import.span());
+            }
+            reduce.set.pop_overrides();
+        }
+    }
+}
+
+fn duplicate(reduce: &Reduce<'_>, import: &Import<'_>, previous: Span) {
+    let message = format!(
+        "{} is already imported on line {}",
+        describe(import),
+        previous.start().line()
+    );
+    reduce
+        .set
+        .emit(Name::DuplicateImport, import.span(), message);
+}
+
+/// Finds the type in scope that has the same name as the import, if there
+/// is one; see [`super::resolve`] for the order that types are looked for
+/// in.  Importing a type into the module that defines it doesn't shadow it.
+fn shadowed_by<'s>(
+    reduce: &Reduce<'s>,
+    base: &[&'s Type],
+    import: &Import<'s>,
+) -> Option<Path<'s>> {
+    let name = import.name()?;
+    (0..=base.len()).find_map(|len| {
+        let mut parts = Path::from_syn(base[..len].to_vec(), None).base.into_owned();
+        parts.push(Cow::Borrowed(name));
+        let path = Path::new(parts, None);
+        let is_type = reduce
+            .annotated
+            .get(&path)
+            .and_then(|annotations| annotations.first())
+            .map(|annotation| annotation.is_type())
+            .unwrap_or(false);
+        let same = path
+            .base
+            .iter()
+            .map(Cow::as_ref)
+            .eq(import.path().iter().cloned());
+        if is_type && !same {
+            Some(path)
+        } else {
+            None
+        }
+    })
+}
+
+fn describe(import: &Import<'_>) -> String {
+    let path = import.path().join("::");
+    match import.name() {
+        None => format!("{}::*", path),
+        Some(name) if import.path().last() != Some(&name) => format!("{} as {}", path, name),
+        Some(_) => path,
+    }
+}
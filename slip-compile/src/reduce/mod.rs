@@ -15,14 +15,26 @@
 //!    trace its components down until all items defined have been converted
 //!    into a type definition, marking up the missing or unknown types (and
 //!    replacing them with a void type or unsized type).
-//! 3. Build up function definitions.  These will be exported to define the
+//! 3. Evaluate the constants, along with the discriminants of enums.  These
+//!    are evaluated on demand, so that they can reference each other (and
+//!    enums) regardless of definition order; see [`constant`].
+//! 4. Build up function definitions.  These will be exported to define the
 //!    behavior of the overall module.  Ideally such a file should contain a
 //!    "main" function, which allows it to interoperate with the system.
+//! 5. Build up the function bodies.  This happens only once every function
+//!    signature is known, so that calls - including the calls that operators
+//!    desugar into - can be resolved regardless of definition order.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 mod annotation;
+mod attribute;
+mod body;
+mod bound;
+mod constant;
+mod dependency;
+mod import;
 #[macro_use]
 mod path;
 mod process;
@@ -30,21 +42,42 @@ mod resolve;
 mod type_;
 
 pub use self::annotation::Annotation;
+use self::dependency::Dependencies;
 pub use self::path::Path;
 pub use self::type_::Scope;
-use crate::diag::DiagnosticSync;
+use crate::diag::{DiagnosticSync, Span};
 
 use crate::error::Error;
-use crate::syn::Root;
-use slip_typal::module::{FunctionId, TypeId};
+use crate::syn::{BasicNode, Root};
+use slip_typal::module::{ConstId, FunctionId, Module, Requirement, TypeId};
 use slip_typal::spec::ModuleSpec;
 
 pub struct Reduce<'s> {
     set: DiagnosticSync<'s>,
     module: ModuleSpec,
     annotated: HashMap<Path<'s>, Vec<Arc<Annotation<'s>>>>,
+    /// The paths of the annotated items, in the order they were first
+    /// defined in.  Items are processed in this order, so that the ids they
+    /// get - and the order of the diagnostics - are the same every time.
+    order: Vec<Path<'s>>,
     types: HashMap<Path<'s>, TypeId>,
     funcs: HashMap<Path<'s>, FunctionId>,
+    consts: HashMap<Path<'s>, ConstId>,
+    /// The constants that are currently being evaluated.  A constant that is
+    /// referenced while it is being evaluated depends on its own value.
+    evaluating: HashSet<Path<'s>>,
+    /// The functions whose bodies still need to be built.  These are only
+    /// built after every item has been processed.
+    bodies: Vec<(FunctionId, Arc<Annotation<'s>>)>,
+    /// The functions of the definitions of operators other than the first,
+    /// by the span of the definition; see [`Reduce::overload()`].
+    overloads: HashMap<Span, FunctionId>,
+    dependencies: Dependencies,
+    /// Every `use` in the source, grouped by the module it is in; see
+    /// [`import`].
+    uses: Vec<import::Uses<'s>>,
+    /// The spans of the imports that a type was found through.
+    used_imports: HashSet<Span>,
 }
 
 impl<'s> Reduce<'s> {
@@ -54,28 +87,165 @@ impl<'s> Reduce<'s> {
             set,
             module,
             annotated: HashMap::new(),
+            order: vec![],
             types: HashMap::new(),
             funcs: HashMap::new(),
+            consts: HashMap::new(),
+            evaluating: HashSet::new(),
+            bodies: vec![],
+            overloads: HashMap::new(),
+            dependencies: Dependencies::default(),
+            uses: vec![],
+            used_imports: HashSet::new(),
         }
     }
 
+    pub fn module(&self) -> &ModuleSpec {
+        &self.module
+    }
+
     pub fn push(&mut self, root: &'s Root) {
         for item in Scope::build(root) {
             let annotation = Annotation::from(item);
             let name = annotation.to_path();
+            if !self.annotated.contains_key(&name) {
+                self.order.push(name.clone());
+            }
             self.annotated
                 .entry(name)
                 .or_insert_with(|| vec![])
                 .push(Arc::new(annotation));
         }
+        import::collect(vec![], vec![], root.items(), &mut self.uses);
+        attribute::check(self, root.items());
+    }
+
+    /// Adds a prebuilt module as a dependency.  The types and functions that
+    /// it exports can then be referenced from the source, and it is recorded
+    /// as a requirement of the module being built; see [`dependency`].
+    pub fn depend(&mut self, module: Module) {
+        self.module.require(Requirement {
+            name: module.name.clone(),
+            version: module.version.clone(),
+        });
+        self.dependencies.push(module);
     }
 
     pub fn reduce(&mut self) -> Result<(), Error> {
         process::verify_singluar_items(self);
         while let Some(name) = self.pluck() {
             self.process(name)?;
+            self.check_limit()?;
+        }
+        // Building a body can build more functions - the overloads of an
+        // operator that it uses - whose bodies are then built in turn.
+        while !self.bodies.is_empty() {
+            for (id, annotation) in std::mem::take(&mut self.bodies) {
+                self.within(&annotation.clone(), |reduce| {
+                    body::build(reduce, id, annotation)
+                })?;
+                self.check_limit()?;
+            }
         }
-        unimplemented!()
+        import::lint(self);
+        Ok(())
+    }
+
+    /// Stops the reduction once the error limit of the diagnostics has been
+    /// reached, since nothing more would be emitted.
+    fn check_limit(&self) -> Result<(), Error> {
+        if self.set.exceeded() {
+            let (errors, _) = self.set.counts();
+            Err(Error::ErrorLimitError { errors })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Retrieves the type defined at the given path, processing it first if
+    /// it has not been processed yet.  This allows types to reference each
+    /// other regardless of the order they were defined in.
+    fn demand(&mut self, name: &Path<'s>) -> Option<TypeId> {
+        if !self.types.contains_key(name) {
+            let is_type = self
+                .annotated
+                .get(name)
+                .and_then(|t| t.first())
+                .map(|a| a.is_type())
+                .unwrap_or(false);
+            if is_type {
+                self.process(name.clone()).ok()?;
+            }
+        }
+
+        self.types.get(name).cloned()
+    }
+
+    /// Retrieves the function defined at the given path, either in the
+    /// source or by a dependency.
+    fn func(&mut self, name: &Path<'s>) -> Option<FunctionId> {
+        match self.funcs.get(name) {
+            Some(id) => Some(*id),
+            None => dependency::func(self, name),
+        }
+    }
+
+    /// Retrieves the function of the given definition of an operator.  Only
+    /// the first definition at a path is processed with the other items;
+    /// the others are built the first time that they're called.
+    fn overload(&mut self, annotation: &Arc<Annotation<'s>>) -> Option<FunctionId> {
+        let first = self
+            .annotated
+            .get(&annotation.to_path())
+            .and_then(|items| items.first());
+        if first.is_some_and(|first| Arc::ptr_eq(first, annotation)) {
+            return self.func(&annotation.to_path());
+        }
+
+        let span = annotation.span();
+        if let Some(id) = self.overloads.get(&span) {
+            return Some(*id);
+        }
+        let id = self.within(&annotation.clone(), |reduce| {
+            process::overload(reduce, annotation.clone())
+        });
+        let id = id.ok()?;
+        self.overloads.insert(span, id);
+        Some(id)
+    }
+
+    /// Retrieves the constant defined at the given path, either in the
+    /// source or by a dependency, evaluating it first if it has not been
+    /// evaluated yet.
+    fn constant(&mut self, name: &Path<'s>) -> Option<ConstId> {
+        if !self.consts.contains_key(name) && self.is_const(name) {
+            self.process(name.clone()).ok()?;
+        }
+
+        match self.consts.get(name) {
+            Some(id) => Some(*id),
+            None => dependency::constant(self, name),
+        }
+    }
+
+    /// Whether or not there is a constant defined at the given path.
+    fn is_const(&mut self, name: &Path<'s>) -> bool {
+        self.consts.contains_key(name)
+            || self
+                .annotated
+                .get(name)
+                .and_then(|t| t.first())
+                .map(|a| a.is_const())
+                .unwrap_or(false)
+            || dependency::constant(self, name).is_some()
+    }
+
+    /// Retrieves the path that the given type was defined at.
+    fn path_of(&self, id: TypeId) -> Option<&Path<'s>> {
+        self.types
+            .iter()
+            .find(|(_, tid)| **tid == id)
+            .map(|(path, _)| path)
     }
 
     fn process(&mut self, name: Path<'s>) -> Result<(), Error> {
@@ -85,21 +255,42 @@ impl<'s> Reduce<'s> {
             .and_then(|t| t.first())
             .unwrap()
             .clone();
-        if annotation.is_type() {
-            process::kind(self, annotation)?;
-        } else if annotation.is_func() {
-            process::func(self, annotation)?;
-        }
+        self.within(&annotation.clone(), |reduce| {
+            if annotation.is_type() {
+                process::kind(reduce, annotation)
+            } else if annotation.is_func() {
+                process::func(reduce, annotation)
+            } else if annotation.is_const() {
+                process::constant(reduce, annotation)
+            } else {
+                Ok(())
+            }
+        })
+    }
 
-        Ok(())
+    /// Runs the given function with the diagnostic overrides from the
+    /// attributes of the given item in place; see [`attribute`].
+    fn within<T>(&mut self, annotation: &Annotation<'s>, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.set
+            .push_overrides(attribute::overrides(annotation.attributes()));
+        let result = f(self);
+        self.set.pop_overrides();
+        result
     }
 
-    fn pluck(&self) -> Option<Path<'s>> {
-        self.annotated
+    /// The annotated items, in the order they were first defined in.
+    fn annotated(&self) -> impl Iterator<Item = (&Path<'s>, &Vec<Arc<Annotation<'s>>>)> {
+        self.order
             .iter()
+            .map(move |path| (path, &self.annotated[path]))
+    }
+
+    fn pluck(&self) -> Option<Path<'s>> {
+        self.annotated()
             .find(|(key, value)| {
                 let func_defined = self.funcs.contains_key(key);
                 let type_defined = self.types.contains_key(key);
+                let const_defined = self.consts.contains_key(key);
                 let is_func = value
                     .first()
                     .map(AsRef::as_ref)
@@ -110,9 +301,1110 @@ impl<'s> Reduce<'s> {
                     .map(AsRef::as_ref)
                     .map(Annotation::is_type)
                     .unwrap_or(false);
+                let is_const = value
+                    .first()
+                    .map(AsRef::as_ref)
+                    .map(Annotation::is_const)
+                    .unwrap_or(false);
 
-                !func_defined && !type_defined && (is_func || is_type)
+                let defined = func_defined || type_defined || const_defined;
+                !defined && (is_func || is_type || is_const)
             })
             .map(|(n, _)| n.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diag::{Diagnostics, MemorySink, Name as DiagnosticName};
+    use crate::syn::of;
+    use slip_typal::module::{
+        Builtin, Constant, Enum, Field, Name, Operation, Origin, TypeDefinition, TypeReference,
+    };
+
+    const OPERATOR_SOURCE: &str = r#"
+struct Point {
+    x: i32,
+    y: i32
+}
+
+export module Point {
+    export fn +(self, other: Point): Point {
+        return other;
+    }
+
+    export fn -@(self): Point {
+        return self;
+    }
+}
+
+fn sum(a: i32, b: i32): i32 {
+    return a + b;
+}
+
+fn add(a: Point, b: Point): Point {
+    return -a + b;
+}
+"#;
+
+    fn calls(reduce: &Reduce<'_>, name: &[&str]) -> Vec<Operation> {
+        let name = Name::from(name.to_vec());
+        let func = reduce
+            .module()
+            .module()
+            .funcs
+            .values()
+            .find(|f| f.name == name)
+            .unwrap();
+        func.blocks.values().flatten().cloned().collect()
+    }
+
+    /// Reduces the given root, collecting everything emitted while doing so.
+    fn reduced<'s>(root: &'s Root) -> (Reduce<'s>, MemorySink) {
+        let memory = MemorySink::new();
+        let set = DiagnosticSync::from(Diagnostics::new().sink(memory.clone()));
+        let mut reduce = Reduce::new(set);
+        reduce.push(root);
+        reduce.reduce().unwrap();
+        (reduce, memory)
+    }
+
+    /// The names of everything emitted, in order.
+    fn names(memory: &MemorySink) -> Vec<DiagnosticName> {
+        memory.emissions().iter().map(|e| e.name()).collect()
+    }
+
+    /// Reduces the source, returning the name, message, and starting line
+    /// and column of everything emitted.
+    fn emitted(source: &str) -> Vec<(DiagnosticName, String, (usize, usize))> {
+        let root = of(source).unwrap();
+        let (_, memory) = reduced(&root);
+        memory
+            .emissions()
+            .iter()
+            .map(|e| {
+                let start = (e.span().start().line(), e.span().start().column());
+                (e.name(), e.message().to_string(), start)
+            })
+            .collect()
+    }
+
+    fn id_of(reduce: &Reduce<'_>, name: &[&str]) -> FunctionId {
+        let name = Name::from(name.to_vec());
+        *reduce
+            .module()
+            .module()
+            .funcs
+            .iter()
+            .find(|(_, f)| f.name == name)
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_operator_resolution() {
+        let root = of(OPERATOR_SOURCE).unwrap();
+        let mut reduce = Reduce::new(DiagnosticSync::default());
+        reduce.push(&root);
+        reduce.reduce().unwrap();
+
+        let sum = calls(&reduce, &["sum"]);
+        assert!(sum
+            .iter()
+            .any(|op| matches!(op, Operation::Builtin(_, Builtin::Add, _))));
+
+        let plus = id_of(&reduce, &["Point", "+"]);
+        let negate = id_of(&reduce, &["Point", "-@"]);
+        let add = calls(&reduce, &["add"]);
+        assert!(add
+            .iter()
+            .any(|op| matches!(op, Operation::Call(_, id, _) if *id == plus)));
+        assert!(add
+            .iter()
+            .any(|op| matches!(op, Operation::Call(_, id, _) if *id == negate)));
+    }
+
+    const CONSTRUCT_SOURCE: &str = r#"
+struct Point {
+    x: i32,
+    y: i32
+}
+
+fn make(y: i32): Point {
+    return Point { x: 1, y };
+}"#;
+
+    const OVERLOAD_SOURCE: &str = r#"
+struct Grid {
+    width: i32
+}
+
+export module Grid {
+    export fn [](self, _: i32, j: i32): i32 {
+        return j;
+    }
+
+    export fn [](self, i: i32): i32 {
+        return i;
+    }
+}
+
+fn cell(grid: Grid): i32 {
+    return grid[1, 2];
+}
+
+fn row(grid: Grid): i32 {
+    return grid[1];
+}"#;
+
+    #[test]
+    fn test_operator_overloads() {
+        let root = of(OVERLOAD_SOURCE).unwrap();
+        let (reduce, memory) = reduced(&root);
+        // Overloading on the number of operands isn't a redefinition.
+        assert_eq!(names(&memory), []);
+
+        // Each index calls the definition that takes as many operands.
+        let name = Name::from(vec!["Grid", "[]"]);
+        let funcs = &reduce.module().module().funcs;
+        let index = |arity| {
+            let overloads = funcs.iter().filter(|(_, f)| f.name == name);
+            let mut matching = overloads.filter(|(_, f)| f.parameters.len() == arity);
+            *matching.next().unwrap().0
+        };
+        let called = |caller| {
+            let calls = calls(&reduce, &[caller]);
+            calls.iter().find_map(|op| match op {
+                Operation::Call(_, id, _) => Some(*id),
+                _ => None,
+            })
+        };
+        assert_eq!(called("cell"), Some(index(3)));
+        assert_eq!(called("row"), Some(index(2)));
+
+        // A mismatched operand is reported at the operand itself.
+        let source = format!(
+            "{}\n\nfn wrong(grid: Grid): i32 {{\n    return grid[1, true];\n}}",
+            OVERLOAD_SOURCE
+        );
+        let found = emitted(&source)
+            .into_iter()
+            .map(|(name, _, start)| (name, start))
+            .collect::<Vec<_>>();
+        assert_eq!(found, [(DiagnosticName::MismatchedArgument, (25, 20))]);
+    }
+
+    const LOGICAL_SOURCE: &str = r#"
+fn check(): bool {
+    return true;
+}
+
+fn both(a: bool): bool {
+    return a && check();
+}
+
+fn either(a: bool): bool {
+    return a || check();
+}"#;
+
+    #[test]
+    fn test_short_circuit() {
+        let root = of(LOGICAL_SOURCE).unwrap();
+        let (reduce, memory) = reduced(&root);
+        assert_eq!(names(&memory), []);
+
+        // `check()` is only called in the block that's branched to when `a`
+        // doesn't decide the result.
+        let check = id_of(&reduce, &["check"]);
+        let taken = |caller: &str| {
+            let id = id_of(&reduce, &[caller]);
+            let func = reduce.module().get_function(id).unwrap();
+            let (then, otherwise) = func
+                .blocks
+                .values()
+                .flatten()
+                .find_map(|op| match op {
+                    Operation::Branch(_, then, otherwise) => Some((*then, *otherwise)),
+                    _ => None,
+                })
+                .unwrap();
+            let calls = |block| {
+                func.blocks[&block]
+                    .iter()
+                    .any(|op| matches!(op, Operation::Call(_, id, _) if *id == check))
+            };
+            (calls(then), calls(otherwise))
+        };
+        assert_eq!(taken("both"), (true, false));
+        assert_eq!(taken("either"), (false, true));
+    }
+
+    #[test]
+    fn test_primitive_index() {
+        // Only pointers can be indexed; the failure is only reported once,
+        // even though the result is used.
+        let source = "fn index(a: i32): i32 {\n    let x = a[0];\n    x\n}";
+        let message = "type i32 cannot be indexed".to_string();
+        assert_eq!(
+            emitted(source),
+            [(DiagnosticName::UnknownOperator, message, (2, 13))]
+        );
+    }
+
+    #[test]
+    fn test_reproducible() {
+        // Every reduction of the same source gives the same module, down to
+        // the ids of its items.
+        let root = of(OPERATOR_SOURCE).unwrap();
+        let written = (0..4)
+            .map(|_| {
+                let mut reduce = Reduce::new(DiagnosticSync::default());
+                reduce.push(&root);
+                reduce.reduce().unwrap();
+                let mut bytes = vec![];
+                reduce.module().module().write(&mut bytes).unwrap();
+                bytes
+            })
+            .collect::<Vec<_>>();
+        assert!(written.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
+    #[test]
+    fn test_struct_construction() {
+        let root = of(CONSTRUCT_SOURCE).unwrap();
+        let (reduce, memory) = reduced(&root);
+        assert_eq!(names(&memory), []);
+
+        let make = calls(&reduce, &["make"]);
+        let fields = make
+            .iter()
+            .find_map(|op| match op {
+                Operation::Construct(_, fields) => Some(fields.clone()),
+                _ => None,
+            })
+            .unwrap();
+        let mut names = fields
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, ["x", "y"]);
+        // The shorthand takes the value of the parameter directly.
+        assert!(fields
+            .iter()
+            .any(|(name, local)| name == "y" && local.index() == 0));
+    }
+
+    const FIELD_SOURCE: &str = r#"
+struct Point {
+    x: i32,
+    y: i32
+}
+
+enum Shape {
+    Empty
+}
+
+fn missing(): Point {
+    return Point { x: 1 };
+}
+
+fn duplicate(): Point {
+    return Point { x: 1, x: 2, y: 3 };
+}
+
+fn unknown(): Point {
+    return Point { x: 1, y: 2, z: 3 };
+}
+
+fn shape(): Shape {
+    return Shape { x: 1 };
+}"#;
+
+    #[test]
+    fn test_construction_errors() {
+        let names = emitted(FIELD_SOURCE)
+            .into_iter()
+            .map(|(name, _, (line, _))| (name, line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                (DiagnosticName::MissingField, 12),
+                (DiagnosticName::DuplicateField, 16),
+                (DiagnosticName::UnknownField, 20),
+                (DiagnosticName::NotAStruct, 24),
+            ]
+        );
+    }
+
+    const MEMBER_SOURCE: &str = r#"
+enum Shape {
+    Empty,
+    Circle(i32)
+}
+
+struct Int {
+    value: i32
+}
+
+export module Int {
+    export fn default(): Int {
+        return Int { value: 0 };
+    }
+}
+
+fn empty(): Shape {
+    return Shape::Empty;
+}
+
+fn circle(r: i32): Shape {
+    return Shape::Circle(r);
+}
+
+fn zero(): Int {
+    return Int::default();
+}"#;
+
+    #[test]
+    fn test_member_resolution() {
+        let root = of(MEMBER_SOURCE).unwrap();
+        let (reduce, memory) = reduced(&root);
+        assert_eq!(names(&memory), []);
+
+        let empty = calls(&reduce, &["empty"]);
+        assert!(empty.iter().any(|op| match op {
+            Operation::Variant(_, name, values) => name == "Empty" && values.is_empty(),
+            _ => false,
+        }));
+
+        let circle = calls(&reduce, &["circle"]);
+        assert!(circle.iter().any(|op| match op {
+            Operation::Variant(_, name, values) => name == "Circle" && values.len() == 1,
+            _ => false,
+        }));
+
+        let default = id_of(&reduce, &["Int", "default"]);
+        let zero = calls(&reduce, &["zero"]);
+        assert!(zero
+            .iter()
+            .any(|op| matches!(op, Operation::Call(_, id, _) if *id == default)));
+    }
+
+    const VARIANT_SOURCE: &str = r#"
+enum Shape {
+    Empty,
+    Circle(i32)
+}
+
+struct Int {
+    value: i32
+}
+
+fn unknown(): Shape {
+    return Shape::Square;
+}
+
+fn arity(): Shape {
+    return Shape::Circle(1, 2);
+}
+
+fn payload(flag: bool): Shape {
+    return Shape::Circle(flag);
+}
+
+fn function(): Int {
+    return Int::make();
+}"#;
+
+    #[test]
+    fn test_variant_errors() {
+        let names = emitted(VARIANT_SOURCE)
+            .into_iter()
+            .map(|(name, _, (line, _))| (name, line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                (DiagnosticName::UnknownVariant, 12),
+                (DiagnosticName::VariantArity, 16),
+                (DiagnosticName::MismatchedType, 20),
+                (DiagnosticName::UnknownFunction, 24),
+            ]
+        );
+    }
+
+    const EXPORT_SOURCE: &str = r#"
+export module Shapes {
+    export struct Point {
+        export x: i32,
+        y: i32
+    }
+
+    struct Hidden {
+        value: i32
+    }
+
+    export fn origin(): Point {
+        return Point { x: 0, y: 0 };
+    }
+}
+
+module Internal {
+    export fn helper() {
+    }
+}"#;
+
+    #[test]
+    fn test_export() {
+        let root = of(EXPORT_SOURCE).unwrap();
+        let (reduce, memory) = reduced(&root);
+        assert_eq!(names(&memory), []);
+        let module = reduce.module().module();
+
+        let types = module
+            .exported_types()
+            .map(|(_, t)| t.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(types, ["Shapes::Point"]);
+        let funcs = module
+            .exported_funcs()
+            .map(|(_, f)| f.name.to_string())
+            .collect::<Vec<_>>();
+        // `helper` is exported, but its module isn't.
+        assert_eq!(funcs, ["Shapes::origin"]);
+
+        let point = module.exported_types().next().unwrap().1;
+        match &point.definition {
+            TypeDefinition::Struct(fields) => {
+                assert!(fields["x"].export);
+                assert!(!fields["y"].export);
+            }
+            _ => panic!("expected a struct"),
+        }
+    }
+
+    const PRIVATE_SOURCE: &str = r#"
+export module Shapes {
+    export struct Point {
+        export x: i32,
+        y: i32
+    }
+
+    struct Hidden {
+        value: i32
+    }
+
+    export module Point {
+        fn origin(): Point {
+            return Point { x: 0, y: 0 };
+        }
+    }
+}
+
+fn hidden(_: Shapes::Hidden) { }
+
+fn origin(): Shapes::Point {
+    return Shapes::Point::origin();
+}
+
+fn y(point: Shapes::Point): i32 {
+    return point.y;
+}
+
+fn x(point: Shapes::Point): i32 {
+    return point.x;
+}"#;
+
+    #[test]
+    fn test_not_exported() {
+        // Only the references from outside of `Shapes` to what it doesn't
+        // export are reported.
+        let names = emitted(PRIVATE_SOURCE)
+            .into_iter()
+            .map(|(name, _, (line, _))| (name, line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                (DiagnosticName::NotExported, 19),
+                (DiagnosticName::NotExported, 22),
+                (DiagnosticName::NotExported, 26),
+            ]
+        );
+    }
+
+    const LITERAL_SOURCE: &str = r#"
+fn take(x: i64): i64 {
+    return x;
+}
+
+fn give(): i64 {
+    return take(1);
+}"#;
+
+    #[test]
+    fn test_literal_types() {
+        let root = of(LITERAL_SOURCE).unwrap();
+        let mut reduce = Reduce::new(DiagnosticSync::default());
+        reduce.push(&root);
+        reduce.reduce().unwrap();
+
+        // The literal takes on the type of the parameter it is passed to.
+        let give = id_of(&reduce, &["give"]);
+        let func = reduce.module().get_function(give).unwrap();
+        let take = reduce
+            .module()
+            .get_function(id_of(&reduce, &["take"]))
+            .unwrap();
+        let literal = calls(&reduce, &["give"])
+            .iter()
+            .find_map(|op| match op {
+                Operation::Constant(local, _) => Some(*local),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(func.locals[literal.index()], take.parameters[0]);
+    }
+
+    const MISMATCH_SOURCE: &str = r#"
+fn take(x: i64): i64 {
+    return x;
+}
+
+fn check(a: i32, b: bool): i32 {
+    if a {
+        return 1;
+    }
+    let c: bool = a;
+    take(c);
+    return b;
+}
+
+fn fine(a: bool, b: i64): i64 {
+    let c: bool = a;
+    if c && true {
+        return take(b);
+    }
+    return take(1);
+}
+
+fn nothing() { }
+
+fn void(): i32 {
+    let a: i32 = nothing();
+    return missing + a;
+}"#;
+
+    #[test]
+    fn test_mismatched_types() {
+        let mismatch = |name, expected, found, start| {
+            let message = format!("mismatched types: expected {}, found {}", expected, found);
+            (name, message, start)
+        };
+        assert_eq!(
+            emitted(MISMATCH_SOURCE),
+            [
+                mismatch(DiagnosticName::MismatchedCondition, "bool", "i32", (7, 8)),
+                mismatch(DiagnosticName::MismatchedLet, "bool", "i32", (10, 19)),
+                mismatch(DiagnosticName::MismatchedArgument, "i64", "bool", (11, 10)),
+                mismatch(DiagnosticName::MismatchedReturn, "i32", "bool", (12, 12)),
+                // A call to a function without a return type has no value,
+                // but an undefined local has already been reported.
+                mismatch(DiagnosticName::MismatchedLet, "i32", "void", (26, 18)),
+                (
+                    DiagnosticName::UndefinedLocal,
+                    "undefined local missing".to_string(),
+                    (27, 12),
+                ),
+            ]
+        );
+    }
+
+    const LOCAL_SOURCE: &str = r#"
+fn locals(a: i32, b: i32): i32 {
+    let unused = a;
+    let b = a;
+    let c = missing;
+    return b;
+}"#;
+
+    #[test]
+    fn test_local_diagnostics() {
+        let names = emitted(LOCAL_SOURCE)
+            .into_iter()
+            .map(|(name, _, start)| (name, start))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                (DiagnosticName::ShadowedLocal, (4, 9)),
+                (DiagnosticName::UndefinedLocal, (5, 13)),
+                (DiagnosticName::UnusedLocal, (3, 9)),
+                (DiagnosticName::UnusedLocal, (5, 9)),
+                (DiagnosticName::UnusedLocal, (2, 19)),
+            ]
+        );
+    }
+
+    const GENERIC_SOURCE: &str = r#"
+struct Wrap<T> {
+    value: T
+}
+
+fn unwrap<T>(wrap: Wrap<T>): T {
+    return wrap.value;
+}
+
+fn wrap<T>(value: T): Wrap<T>
+
+fn grow<T>(value: T) {
+    grow(wrap(value));
+}
+
+export fn first(wrap: Wrap<i32>): i32 {
+    return unwrap(wrap);
+}"#;
+
+    #[test]
+    fn test_monomorphization() {
+        use slip_typal::mono::{monomorphize, MonoError, Monomorphize};
+
+        let root = of(GENERIC_SOURCE).unwrap();
+        let mut reduce = Reduce::new(DiagnosticSync::default());
+        reduce.push(&root);
+        reduce.reduce().unwrap();
+
+        let module = monomorphize(reduce.module().module()).unwrap();
+        let instance = module
+            .funcs
+            .iter()
+            .find(|(_, f)| f.name == Name::from("unwrap<i32>"))
+            .map(|(id, _)| *id)
+            .unwrap();
+        let first = id_of(&reduce, &["first"]);
+        assert!(module.funcs[&first]
+            .blocks
+            .values()
+            .flatten()
+            .any(|op| matches!(op, Operation::Call(_, id, _) if *id == instance)));
+        // Nothing generic is reachable from `first` other than `unwrap`.
+        assert!(module.funcs.values().all(|f| f.generics.is_empty()));
+        assert!(!module.funcs.values().any(|f| f.name == Name::from("grow")));
+        assert!(module
+            .types
+            .values()
+            .any(|t| t.name == Name::from("Wrap<i32>")));
+
+        // Once `grow` is reachable, it needs an instance for `Wrap<i32>`,
+        // `Wrap<Wrap<i32>>`, and so on.
+        let source = format!(
+            "{}\n\nexport fn start(value: i32) {{\n    grow(value);\n}}",
+            GENERIC_SOURCE
+        );
+        let root = of(&source).unwrap();
+        let mut reduce = Reduce::new(DiagnosticSync::default());
+        reduce.push(&root);
+        reduce.reduce().unwrap();
+
+        let result = Monomorphize::new(reduce.module().module()).limit(8).run();
+        assert!(matches!(result, Err(MonoError::DepthLimit(_, 8))));
+    }
+
+    const DEFAULT_SOURCE: &str = r#"
+struct Pair<A, B = i32> {
+    first: A,
+    second: B
+}
+
+fn take(_: Pair<i64>) { }"#;
+
+    #[test]
+    fn test_generic_defaults() {
+        let root = of(DEFAULT_SOURCE).unwrap();
+        let (reduce, memory) = reduced(&root);
+        assert_eq!(names(&memory), []);
+
+        let take = id_of(&reduce, &["take"]);
+        let parameter = &reduce.module().get_function(take).unwrap().parameters[0];
+        let generics = match parameter {
+            TypeReference::Mix(_, generics) => generics
+                .iter()
+                .map(|kind| match kind {
+                    TypeReference::Absolute(id) => {
+                        reduce.module().get_type(*id).unwrap().name.clone()
+                    }
+                    other => panic!("expected an absolute type, got {:?}", other),
+                })
+                .collect::<Vec<_>>(),
+            other => panic!("expected a mixed type, got {:?}", other),
+        };
+        assert_eq!(generics, [Name::from("i64"), Name::from("i32")]);
+    }
+
+    const BOUND_SOURCE: &str = r#"
+struct Pair<A, B = i32> {
+    first: A,
+    second: B
+}
+
+struct Point {
+    x: i32
+}
+
+struct Sorted<T: Compare> {
+    value: T
+}
+
+fn sort<T: Sortable>(_: T) { }
+
+fn many(_: Pair<i32, i32, i32>) { }
+
+fn point(_: Sorted<Point>) { }
+
+fn number(_: Sorted<i32>) { }
+
+fn few(_: Sorted) { }
+
+fn bare(_: Pair) { }
+
+fn make<T>(): T
+
+fn unknown(): i32 {
+    return make();
+}"#;
+
+    #[test]
+    fn test_generic_errors() {
+        let names = emitted(BOUND_SOURCE)
+            .into_iter()
+            .map(|(name, _, (line, _))| (name, line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                (DiagnosticName::UnknownCapability, 15),
+                (DiagnosticName::Generics, 17),
+                (DiagnosticName::MissingCapability, 19),
+                (DiagnosticName::Generics, 23),
+                (DiagnosticName::Generics, 25),
+                (DiagnosticName::Generics, 30),
+            ]
+        );
+    }
+
+    const DEPENDENCY_SOURCE: &str = r#"
+fn start(): Geo::Point {
+    return Geo::Point::origin();
+}
+
+fn x(point: Geo::Point): i32 {
+    return point.x;
+}"#;
+
+    #[test]
+    fn test_dependency() {
+        let mut geo = ModuleSpec::new("geo", "1.2.0".parse().unwrap());
+        let field = Field {
+            kind: TypeReference::Absolute(geo.primitive_type(32)),
+            export: true,
+        };
+        let fields = vec![("x".to_string(), field.clone()), ("y".to_string(), field)];
+        let point = geo.struct_type(vec!["Geo", "Point"], vec![], fields);
+        geo.update_type(point, |kind| kind.export = true);
+        let retval = Some(TypeReference::Absolute(point));
+        let exported = geo.stub_function(vec!["Geo", "Point", "origin"], vec![], vec![], retval);
+        geo.update_function(exported, |func| func.export = true);
+
+        let mut bytes = vec![];
+        geo.module().write(&mut bytes).unwrap();
+        let geo = Module::read(&bytes[..]).unwrap();
+
+        let root = of(DEPENDENCY_SOURCE).unwrap();
+        let mut reduce = Reduce::new(DiagnosticSync::default());
+        reduce.depend(geo);
+        reduce.push(&root);
+        reduce.reduce().unwrap();
+
+        let requirements = &reduce.module().module().requirements;
+        assert_eq!(requirements.len(), 1);
+        assert_eq!(requirements[0].name, "geo");
+
+        let origin = id_of(&reduce, &["Geo", "Point", "origin"]);
+        let imported = reduce.module().get_function(origin).unwrap();
+        let expected = Origin {
+            requirement: "geo".to_string(),
+            id: exported,
+        };
+        assert_eq!(imported.origin, Some(expected));
+        let start = calls(&reduce, &["start"]);
+        assert!(start
+            .iter()
+            .any(|op| matches!(op, Operation::Call(_, id, _) if *id == origin)));
+    }
+
+    #[test]
+    fn test_outdated_module() {
+        let geo = ModuleSpec::new("geo", "1.2.0".parse().unwrap());
+        let mut bytes = vec![];
+        geo.module().write(&mut bytes).unwrap();
+        // The format version, just after the magic.
+        bytes[5] = bytes[5].wrapping_sub(1);
+        let error = Module::read(&bytes[..]).unwrap_err();
+        assert!(error.to_string().contains("needs to be compiled again"));
+        // Modules from before the format was versioned start with the name.
+        let error = Module::read(&b"\x03\0\0\0\0\0\0\0geo"[..]).unwrap_err();
+        assert!(error.to_string().contains("needs to be compiled again"));
+    }
+
+    const USE_SOURCE: &str = r#"
+export module Geo {
+    export struct Point {
+        x: i32
+    }
+
+    export module Shapes {
+        export struct Circle {
+            radius: i32
+        }
+
+        export struct Square {
+            side: i32
+        }
+    }
+}
+
+module Uses {
+    use Geo::Point;
+    use Geo::{Shapes::{Circle as Round, self}};
+    use Geo::Shapes::*;
+
+    fn point(point: Point)
+    fn round(circle: Round)
+    fn circle(circle: Shapes::Circle)
+    fn square(square: Square)
+}"#;
+
+    #[test]
+    fn test_use_resolution() {
+        let root = of(USE_SOURCE).unwrap();
+        let mut reduce = Reduce::new(DiagnosticSync::default());
+        reduce.push(&root);
+        reduce.reduce().unwrap();
+
+        let parameter = |name: &str| {
+            let func = id_of(&reduce, &["Uses", name]);
+            match reduce.module().get_function(func).unwrap().parameters[0] {
+                TypeReference::Absolute(id) => {
+                    reduce.module().get_type(id).unwrap().name.to_string()
+                }
+                ref other => panic!("expected an absolute type, got {:?}", other),
+            }
+        };
+        assert_eq!(parameter("point"), "Geo::Point");
+        assert_eq!(parameter("round"), "Geo::Shapes::Circle");
+        assert_eq!(parameter("circle"), "Geo::Shapes::Circle");
+        assert_eq!(parameter("square"), "Geo::Shapes::Square");
+    }
+
+    const IMPORT_SOURCE: &str = r#"
+export module Geo {
+    export struct Point {
+        x: i32
+    }
+
+    export struct Line {
+        length: i32
+    }
+
+    export struct Circle {
+        radius: i32
+    }
+}
+
+module Uses {
+    use Geo::Point;
+    use Geo::{Point, Line};
+    use Geo::Circle;
+
+    struct Circle {
+        radius: i32
+    }
+
+    fn point(_: Point)
+}"#;
+
+    #[test]
+    fn test_import_lints() {
+        let names = emitted(IMPORT_SOURCE)
+            .into_iter()
+            .map(|(name, _, start)| (name, start))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                (DiagnosticName::DuplicateImport, (18, 15)),
+                (DiagnosticName::UnusedImport, (18, 22)),
+                (DiagnosticName::ShadowedImport, (19, 9)),
+            ]
+        );
+    }
+
+    const CONSTANT_SOURCE: &str = r#"
+const MAX: i32 = (BASE << 2) | 1;
+const BASE: i32 = 0x10;
+const NEGATIVE: i64 = -MAX;
+const FLAG: bool = MAX > BASE && BASE != 0;
+
+enum Color {
+    Red = Limits::LOW,
+    Green,
+    Blue = MAX
+}
+
+export module Limits {
+    export const LOW: i32 = -2;
+}
+
+fn get(): i32 {
+    return MAX + Limits::LOW;
+}"#;
+
+    #[test]
+    fn test_constant_evaluation() {
+        let root = of(CONSTANT_SOURCE).unwrap();
+        let mut reduce = Reduce::new(DiagnosticSync::default());
+        reduce.push(&root);
+        reduce.reduce().unwrap();
+
+        let value = |name: &[&str]| {
+            let name = Name::from(name.to_vec());
+            let consts = &reduce.module().module().consts;
+            let const_ = consts.values().find(|c| c.name == name).unwrap();
+            const_.value.clone()
+        };
+        assert_eq!(value(&["MAX"]), Constant::Integer(65));
+        assert_eq!(value(&["NEGATIVE"]), Constant::Integer(-65i64 as u64));
+        assert_eq!(value(&["FLAG"]), Constant::Bool(true));
+        assert_eq!(value(&["Limits", "LOW"]), Constant::Integer(-2i64 as u64));
+
+        let color = reduce
+            .module()
+            .module()
+            .types
+            .values()
+            .find(|t| t.name == Name::from(vec!["Color"]))
+            .unwrap();
+        let expected = vec![
+            ("Red".to_string(), -2i64 as u64),
+            ("Green".to_string(), -1i64 as u64),
+            ("Blue".to_string(), 65),
+        ];
+        assert_eq!(
+            color.definition,
+            TypeDefinition::Enum(Enum::Value(expected))
+        );
+
+        // References to constants are replaced with their values.
+        let get = calls(&reduce, &["get"]);
+        let constants = get
+            .iter()
+            .filter_map(|op| match op {
+                Operation::Constant(_, constant) => Some(constant.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            constants,
+            [Constant::Integer(65), Constant::Integer(-2i64 as u64)]
+        );
+    }
+
+    const TAIL_SOURCE: &str = r#"
+fn double(a: i32): i32 {
+    let b = a + a;
+    b
+}"#;
+
+    #[test]
+    fn test_tail_return() {
+        let root = of(TAIL_SOURCE).unwrap();
+        let mut reduce = Reduce::new(DiagnosticSync::default());
+        reduce.push(&root);
+        reduce.reduce().unwrap();
+
+        // The tail of the body is returned, so nothing falls off of the end.
+        let double = calls(&reduce, &["double"]);
+        let returns = double
+            .iter()
+            .filter(|op| matches!(op, Operation::Return(_)))
+            .collect::<Vec<_>>();
+        assert!(matches!(returns[..], [Operation::Return(Some(_))]));
+    }
+
+    const ATTRIBUTE_SOURCE: &str = r#"
+#[allow(unused-local)]
+module Quiet {
+    fn one(): i32 {
+        let a = 1;
+        return 1;
+    }
+
+    #[warn(unused-local)]
+    fn two(): i32 {
+        let b = 2;
+        return 2;
+    }
+}
+
+#[deny(unused-local)]
+fn three(): i32 {
+    let c = 3;
+    return 3;
+}
+
+#[allow(unused-lcoal)]
+fn four(): i32 {
+    return 4;
+}
+
+#[allow(func-redefinition)]
+fn five() { }
+fn five() { }
+
+fn six() { }
+#[allow(func-redefinition)]
+fn six() { }"#;
+
+    #[test]
+    fn test_attribute_overrides() {
+        use crate::diag::Level;
+
+        let root = of(ATTRIBUTE_SOURCE).unwrap();
+        let (_, memory) = reduced(&root);
+
+        // Only the unused locals outside of an `allow` are emitted, at the
+        // level that the innermost attribute sets them to.  Only the last
+        // definition of an item controls its redefinition.
+        let mut emissions = memory
+            .emissions()
+            .iter()
+            .map(|emission| (emission.name(), emission.level()))
+            .collect::<Vec<_>>();
+        emissions.sort();
+        assert_eq!(
+            emissions,
+            [
+                (DiagnosticName::UnusedLocal, Level::Warning),
+                (DiagnosticName::UnusedLocal, Level::Error),
+                (DiagnosticName::FuncRedefinition, Level::Error),
+                (DiagnosticName::UnknownDiagnostic, Level::Warning),
+            ]
+        );
+        assert!(memory
+            .emissions()
+            .iter()
+            .any(|emission| emission.message().ends_with("five already defined")));
+    }
+}
@@ -92,6 +92,23 @@ impl<'s> Path<'s> {
     pub fn is_func(&self) -> bool {
         self.fname.is_some()
     }
+
+    /// The path of the module the item is defined in.  For a function, this
+    /// is the base of the path; for a type, this is the base of the path
+    /// without the name of the type itself.
+    pub fn module(&self) -> &[Cow<'s, str>] {
+        if self.is_func() {
+            &self.base
+        } else {
+            &self.base[..self.base.len().saturating_sub(1)]
+        }
+    }
+
+    /// Whether or not this path is within the given module, or is the
+    /// module itself.
+    pub fn is_within(&self, module: &[Cow<'_, str>]) -> bool {
+        self.base.starts_with(module)
+    }
 }
 
 impl fmt::Display for Path<'_> {
@@ -0,0 +1,118 @@
+use super::{constant, resolve};
+use crate::diag::Name;
+use crate::error::Error;
+use crate::reduce::{Annotation, Reduce};
+use crate::stream::Token;
+use crate::syn::function::expression::Expression;
+use crate::syn::{BasicNode, Enum as SynEnum, EnumVariant, Type};
+use slip_typal::module::{Enum, TypeDefinition};
+use std::sync::Arc;
+
+pub(super) fn build<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: Arc<Annotation<'s>>,
+    enum_: &'s SynEnum,
+) -> Result<(), Error> {
+    let name = annotation.to_path().to_name();
+    let generics = annotation
+        .generic_list()
+        .map(Type::to_name)
+        .collect::<Vec<_>>();
+    let id = reduce.module.stub_type(name, generics);
+    reduce.types.insert(annotation.to_path(), id);
+
+    let has_payload = enum_
+        .variants()
+        .iter()
+        .any(|v| matches!(v, EnumVariant::Unit(_, _, _)));
+    let has_value = enum_
+        .variants()
+        .iter()
+        .any(|v| matches!(v, EnumVariant::Value(_, _, _)));
+
+    let definition = if has_payload {
+        if has_value {
+            reduce.set.emit(
+                Name::Unsupported,
+                enum_.span(),
+                "enums with both values and payloads are not currently supported",
+            );
+        }
+
+        // Variants without a payload just have an empty one.
+        let variants = enum_
+            .variants()
+            .iter()
+            .map(|variant| match variant {
+                EnumVariant::Unit(tok, payload, _) => {
+                    let payload = payload
+                        .iter()
+                        .map(|kind| resolve::kind(reduce, &annotation, kind))
+                        .collect();
+                    (variant_name(tok), payload)
+                }
+                EnumVariant::Name(tok) | EnumVariant::Value(tok, _, _) => {
+                    (variant_name(tok), vec![])
+                }
+            })
+            .collect();
+        Enum::Unit(variants)
+    } else if has_value {
+        // The values are constant expressions; a variant without one is one
+        // more than the variant before it.
+        let mut next = 0u64;
+        let mut variants = vec![];
+        for variant in enum_.variants() {
+            let (tok, value) = match variant {
+                EnumVariant::Value(tok, expr, _) => (tok, discriminant(reduce, &annotation, expr)),
+                EnumVariant::Name(tok) | EnumVariant::Unit(tok, _, _) => (tok, None),
+            };
+            let value = value.unwrap_or(next);
+            next = value.wrapping_add(1);
+            variants.push((variant_name(tok), value));
+        }
+        Enum::Value(variants)
+    } else {
+        let variants = enum_
+            .variants()
+            .iter()
+            .map(|variant| match variant {
+                EnumVariant::Name(tok)
+                | EnumVariant::Value(tok, _, _)
+                | EnumVariant::Unit(tok, _, _) => variant_name(tok),
+            })
+            .collect();
+        Enum::Simple(variants)
+    };
+
+    let export = annotation.is_exported();
+    reduce.module.update_type(id, |type_| {
+        type_.definition = TypeDefinition::Enum(definition);
+        type_.export = export;
+    });
+
+    Ok(())
+}
+
+/// Evaluates the discriminant of a variant, which has to be an integer.
+fn discriminant<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: &Annotation<'s>,
+    expr: &'s Expression,
+) -> Option<u64> {
+    match constant::evaluate(reduce, annotation, expr)? {
+        constant::Value::Integer(value) => Some(value as u64),
+        value => {
+            let message = format!(
+                "expected an integer discriminant, found {}",
+                value.describe()
+            );
+            reduce.set.emit(Name::MismatchedType, expr.span(), message);
+            None
+        }
+    }
+}
+
+fn variant_name(tok: &Token) -> String {
+    tok.value().map(str::to_string).unwrap()
+}
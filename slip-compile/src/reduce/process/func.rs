@@ -0,0 +1,51 @@
+use super::resolve;
+use crate::error::Error;
+use crate::reduce::{Annotation, Reduce};
+use crate::syn::function::FunctionParameter;
+use crate::syn::{Function, Type};
+use slip_typal::module::FunctionId;
+use std::sync::Arc;
+
+pub(super) fn build<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: Arc<Annotation<'s>>,
+    func: &'s Function,
+) -> Result<FunctionId, Error> {
+    let path = annotation.to_path();
+    let generics = annotation
+        .generic_list()
+        .map(Type::to_name)
+        .collect::<Vec<_>>();
+
+    let parameters = func
+        .parameters()
+        .iter()
+        .map(|param| match param {
+            FunctionParameter::Static(_, kind) | FunctionParameter::Ignore(_, kind) => {
+                resolve::kind(reduce, &annotation, kind)
+            }
+            FunctionParameter::This(tok) => resolve::this(reduce, &annotation, tok.span()),
+        })
+        .collect::<Vec<_>>();
+    let retval = func
+        .retval()
+        .as_ref()
+        .map(|kind| resolve::kind(reduce, &annotation, kind));
+
+    let id = reduce
+        .module
+        .stub_function(path.to_name(), generics, parameters, retval);
+    let export = annotation.is_exported();
+    reduce.module.update_function(id, |f| f.export = export);
+    // An operator can have more than one definition; the path refers to
+    // the first of them.
+    reduce.funcs.entry(path).or_insert(id);
+
+    // The body can only be built once all of the other functions have been
+    // defined, as it may call any of them.
+    if func.body().is_some() {
+        reduce.bodies.push((id, annotation));
+    }
+
+    Ok(id)
+}
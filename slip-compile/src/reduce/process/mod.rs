@@ -1,19 +1,26 @@
 use std::sync::Arc;
 
-use super::resolve;
+use super::{attribute, bound, constant, resolve};
 use super::{Annotation, Reduce};
-use crate::diag::Name as DiagnosticName;
+use crate::diag::{Emission, Name as DiagnosticName};
 use crate::error::Error;
+use crate::syn::function::FunctionName;
 use crate::syn::{BasicNode, Item};
+use slip_typal::module::FunctionId;
+use std::collections::HashSet;
 
+mod enum_;
+mod func;
 mod struct_;
 
 pub(super) fn kind<'s>(
     reduce: &mut Reduce<'s>,
     annotation: Arc<Annotation<'s>>,
 ) -> Result<(), Error> {
+    bound::declare(reduce, &annotation);
     match annotation.item() {
         Item::Struct(struct_) => struct_::build(reduce, annotation, struct_),
+        Item::Enum(enum_) => enum_::build(reduce, annotation, enum_),
 
         _ => unreachable!(),
     }
@@ -23,28 +30,82 @@ pub(super) fn func<'s>(
     reduce: &mut Reduce<'s>,
     annotation: Arc<Annotation<'s>>,
 ) -> Result<(), Error> {
-    unimplemented!()
+    bound::declare(reduce, &annotation);
+    match annotation.item() {
+        Item::Function(func) => func::build(reduce, annotation.clone(), func).map(|_| ()),
+
+        _ => unreachable!(),
+    }
+}
+
+/// Builds the function for a definition of an operator other than the
+/// first.  Operators can be overloaded on the number of operands they take,
+/// so each of their definitions is a function of its own; only the first is
+/// found by its path.  See [`Reduce::overload()`].
+pub(super) fn overload<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: Arc<Annotation<'s>>,
+) -> Result<FunctionId, Error> {
+    bound::declare(reduce, &annotation);
+    match annotation.item() {
+        Item::Function(func) => func::build(reduce, annotation.clone(), func),
+
+        _ => unreachable!(),
+    }
 }
 
+pub(super) fn constant<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: Arc<Annotation<'s>>,
+) -> Result<(), Error> {
+    match annotation.item() {
+        Item::Const(const_) => constant::build(reduce, annotation.clone(), const_),
+
+        _ => unreachable!(),
+    }
+}
+
+/// Reports every item that's defined more than once.  The diagnostic points
+/// at the last definition, so only the attributes of that definition (and
+/// of the modules it's in) can change its level; the attributes of the
+/// earlier definitions are ignored.
 pub(super) fn verify_singluar_items(reduce: &Reduce<'_>) {
-    for (name, items) in reduce.annotated.iter().filter(|(_, i)| i.len() > 1) {
+    let repeated = reduce.annotated().filter(|(_, items)| items.len() > 1);
+    for (name, items) in repeated.filter(|(_, items)| !overloaded(items)) {
         let diagname = if name.is_func() {
             DiagnosticName::FuncRedefinition
         } else {
             DiagnosticName::TypeRedefinition
         };
-        reduce.set.emit(
+        let last = items.last().unwrap();
+        let mut emission = Emission::new(
             diagname,
-            items.last().unwrap().span(),
+            last.span(),
             format!("item {} already defined", name),
         );
         for prev in items.iter().take(items.len() - 1) {
-            reduce.set.emit_if(
-                diagname,
-                DiagnosticName::Note,
-                prev.span(),
-                "note: previous definition here",
-            );
+            emission = emission.with_label(prev.span(), "previous definition here");
         }
+        reduce
+            .set
+            .push_overrides(attribute::overrides(last.attributes()));
+        reduce.set.report(emission);
+        reduce.set.pop_overrides();
     }
 }
+
+/// Whether or not the given definitions are of an operator that's
+/// overloaded on the number of operands - that is, that no two of them take
+/// the same number of operands.
+fn overloaded(items: &[Arc<Annotation<'_>>]) -> bool {
+    let mut arities = HashSet::new();
+    items.iter().all(|item| match item.item() {
+        Item::Function(func) => match func.name() {
+            FunctionName::Operator(_) | FunctionName::Brackets(..) => {
+                arities.insert(func.parameters().len())
+            }
+            _ => false,
+        },
+        _ => false,
+    })
+}
@@ -2,8 +2,8 @@ use super::resolve;
 use crate::error::Error;
 use crate::reduce::{Annotation, Reduce};
 use crate::syn::{Struct, Type};
-use slip_typal::module::TypeDefinition;
-use std::collections::HashMap;
+use slip_typal::module::{Field, TypeDefinition};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 pub(super) fn build<'s>(
@@ -17,6 +17,7 @@ pub(super) fn build<'s>(
         .map(Type::to_name)
         .collect::<Vec<_>>();
     let id = reduce.module.stub_type(name.clone(), generics.clone());
+    reduce.types.insert(annotation.to_path(), id);
 
     let definitions = struct_
         .elements()
@@ -28,12 +29,18 @@ pub(super) fn build<'s>(
                 &annotation,
                 el.kind(),
             );
-            (name, tyid)
+            let field = Field {
+                kind: tyid,
+                export: el.export(),
+            };
+            (name, field)
         })
-        .collect::<HashMap<_, _>>();
+        .collect::<BTreeMap<_, _>>();
 
+    let export = annotation.is_exported();
     reduce.module.update_type(id, |type_| {
         type_.definition = TypeDefinition::Struct(definitions);
+        type_.export = export;
     });
 
     Ok(())
@@ -30,11 +30,14 @@
 //! developer - or, if we come down with no possible type, we can throw an
 //! error.
 
-use crate::diag::{DiagnosticSync, Name, Span};
-use crate::reduce::{Path, Reduce, Scope, Annotation};
-use crate::syn::{BasicNode, Type};
-use slip_typal::module::{TypeId, TypeReference};
+use crate::diag::{closest, Applicability, DiagnosticSync, Emission, Name, Span};
+use crate::reduce::{bound, dependency, Path, Reduce, Scope, Annotation};
+use crate::stream::Token;
+use crate::syn::{BasicNode, Roll, Type, Use};
+use slip_typal::module::{FunctionId, TypeId, TypeReference};
 use slip_typal::spec::ModuleSpec;
+use std::borrow::Cow;
+use std::collections::HashSet;
 
 pub(super) fn kind<'s>(
     reduce: &mut Reduce<'s>,
@@ -54,7 +57,7 @@ pub(super) fn kind<'s>(
     // a generic (our earlier check failed), we can now check all of the types
     // in scope, to see if they match.
     let base = collect_applicable(reduce, annotation.scope(), type_)
-        .unwrap_or_else(|| reduce.module.void_type());
+        .unwrap_or_else(|| reduce.module.error_type());
 
     // The next step is to take all of the generics we've applied to the type,
     // and try to find where that type is located.  This will resolve types
@@ -71,6 +74,12 @@ pub(super) fn kind<'s>(
             kind(reduce, annotation, name)
         })
         .collect::<Vec<_>>();
+    // Any generics that weren't given take on their defaults, and each of
+    // them has to satisfy its bounds; see [`super::bound`].
+    let generics = match bound::type_generics(reduce, base, generics, type_.span()) {
+        Some(generics) => generics,
+        None => return TypeReference::Absolute(reduce.module.error_type()),
+    };
     if generics.is_empty() {
         // If there were no generics, then we'll just create the absolute
         // reference.
@@ -80,6 +89,100 @@ pub(super) fn kind<'s>(
     }
 }
 
+/// Resolves the type of `self` within a function.  This is the type that is
+/// defined at the same path as the module the function is in; for example,
+/// for the function `+` in `module Point`, this is the type `Point`.  The
+/// generics of that module are passed along to the type.
+pub(super) fn this<'s>(
+    reduce: &mut Reduce<'s>,
+    annotation: &Annotation<'s>,
+    span: Span,
+) -> TypeReference {
+    let base = annotation.scope().base();
+    let path = Path::from_syn(base.to_vec(), None);
+    let id = match reduce.demand(&path) {
+        Some(id) => id,
+        None => {
+            reduce.set.emit(
+                Name::UnknownType,
+                span,
+                format!("no type {} to use as self", path),
+            );
+            return TypeReference::Absolute(reduce.module.error_type());
+        }
+    };
+
+    // The generics of the outer modules come first in the generic list, so
+    // we have to skip past them to get to the generics of our module.
+    let offset = base
+        .iter()
+        .take(base.len().saturating_sub(1))
+        .filter_map(|v| v.generics().as_ref())
+        .map(Roll::len)
+        .sum::<usize>();
+    let count = base
+        .last()
+        .and_then(|v| v.generics().as_ref())
+        .map(Roll::len)
+        .unwrap_or(0);
+
+    if count == 0 {
+        TypeReference::Absolute(id)
+    } else {
+        let generics = (offset..(offset + count))
+            .map(|i| TypeReference::Generic(i as u64))
+            .collect();
+        TypeReference::Mix(id, generics)
+    }
+}
+
+/// Resolves a function by its name, from within the given scope.  Unlike
+/// types, functions cannot be imported with a `use`; so we only look at the
+/// modules the scope is within, with the innermost definition taking
+/// precedence over the outer ones.
+pub(super) fn func<'s>(
+    reduce: &mut Reduce<'s>,
+    scope: &Scope<'s>,
+    name: &str,
+) -> Option<(Path<'s>, FunctionId)> {
+    (0..=scope.base().len()).rev().find_map(|len| {
+        let path = Path::from_syn(scope.base()[..len].to_vec(), None)
+            .with_fname(Some(name.to_string()));
+        reduce.func(&path).map(|id| (path, id))
+    })
+}
+
+/// Whether or not the item at the given path can be referenced from within
+/// the given scope.  An item can always be referenced from within the module
+/// it is defined in, including any modules nested within that module; from
+/// anywhere else, it has to be exported.  Items that weren't defined in the
+/// source (e.g. the primitive types) can be referenced from anywhere.
+pub(super) fn visible<'s>(reduce: &Reduce<'s>, scope: &Scope<'s>, path: &Path<'s>) -> bool {
+    within(scope, path)
+        || reduce
+            .annotated
+            .get(path)
+            .and_then(|a| a.first())
+            .map(|a| a.is_exported())
+            .unwrap_or(true)
+}
+
+/// Whether or not the given scope is within the module the item at the given
+/// path is defined in.
+pub(super) fn within(scope: &Scope<'_>, path: &Path<'_>) -> bool {
+    Path::from_syn(scope.base().to_vec(), None).is_within(path.module())
+}
+
+/// Reports a reference to an item that isn't visible from where it was
+/// referenced; see [`visible`].
+pub(super) fn not_exported(set: &DiagnosticSync<'_>, span: Span, what: String) {
+    set.emit(
+        Name::NotExported,
+        span,
+        format!("{} is not exported from its module", what),
+    );
+}
+
 /// This function gives me a headache.
 fn find_generic(
     annotation: &Annotation<'_>,
@@ -108,15 +211,21 @@ fn collect_applicable<'r, 's: 'r>(
     }
 
     // Now we'll generate the possible references that our type can refer to.
-    // `generate_possible_references` returns only potential references that
-    // the `use` statements may refer to; we'll actually perform the check
-    // ourselves by checking our type table.
-    let possible_references = generate_possible_references(scope, type_)
-        // This reduces our possible reference set to the types that exist.
-        // We do the lookup, and attempt to return the type id.  If that
-        // succeeds, then we have the type, up to and including the type id.
-        .filter_map(|(span, path)| reduce.types.get(&path).map(|id| (span, path, *id)))
-        .collect::<Vec<_>>();
+    // `in_scope_references` and `imported_references` return only potential
+    // references; we'll actually perform the check ourselves by checking our
+    // type table.  Types that are in scope shadow any imports of the same
+    // name, so we only look at the imports if nothing in scope matched.
+    let mut possible_references = existing(reduce, in_scope_references(scope, type_));
+    if possible_references.is_empty() {
+        possible_references = existing(reduce, imported_references(scope, type_));
+        // Importing the same type more than once doesn't make it ambiguous.
+        let mut seen = HashSet::new();
+        possible_references.retain(|(_, _, id)| seen.insert(*id));
+        // Any import that the type was found through has been used.
+        reduce
+            .used_imports
+            .extend(possible_references.iter().map(|(span, _, _)| *span));
+    }
     if possible_references.len() > 1 {
         // If we ended up with more than one type, then we're dealing with an
         // ambiguous type scenario; this isn't life-ending for the resolution,
@@ -128,32 +237,93 @@ fn collect_applicable<'r, 's: 'r>(
         );
     }
 
-    if let Some((_, _, id)) = possible_references.get(0) {
+    if let Some((_, path, id)) = possible_references.first() {
+        if !visible(reduce, scope, path) {
+            not_exported(&reduce.set, type_.span(), format!("type {}", path));
+        }
         Some(*id)
     } else  {
         // Since we checked our static types, and we checked the uses and
         // there's nothing, then we'll just have to give up.
-        missing_type_error(&reduce.set, scope, type_);
+        missing_type_error(reduce, scope, type_);
         None
     }
 }
 
-fn missing_type_error(set: &DiagnosticSync<'_>, scope: &Scope<'_>, kind: &Type) {
-    set.emit(
+/// Reduces the given possible references to the types that exist.  We do
+/// the lookup, and attempt to return the type id.  If that succeeds, then we
+/// have the type, up to and including the type id.  Types that aren't defined
+/// in the source may still be exported by one of our dependencies.
+fn existing<'s>(
+    reduce: &mut Reduce<'s>,
+    references: impl Iterator<Item = (Span, Path<'s>)>,
+) -> Vec<(Span, Path<'s>, TypeId)> {
+    references
+        .filter_map(|(span, path)| {
+            reduce
+                .demand(&path)
+                .or_else(|| dependency::kind(reduce, &path))
+                .map(|id| (span, path, id))
+        })
+        .collect()
+}
+
+fn missing_type_error(reduce: &Reduce<'_>, scope: &Scope<'_>, kind: &Type) {
+    let mut emission = Emission::new(
         Name::UnknownType,
         kind.span(),
         format!("unidentified type {}", kind),
     );
-    if set.active(Name::UnknownType) {
+    if let Some(path) = similar_type(reduce, kind) {
+        let span = kind
+            .parts()
+            .iter()
+            .map(Token::span)
+            .fold(Span::identity(), |a, b| a | b);
+        emission = emission.with_suggestion(
+            span,
+            path.to_string(),
+            format!("did you mean `{}`?", path),
+            Applicability::MaybeIncorrect,
+        );
+    }
+    if reduce.set.active(Name::TypeTrace) {
         for refer in generate_possible_references(scope, kind) {
-            set.emit(
-                Name::TypeTrace,
-                refer.0,
-                "note: type resolution attempted against",
-            )
+            emission = emission.with_label(refer.0, "type resolution attempted against");
         }
     }
-    unimplemented!()
+    reduce.set.report(emission);
+}
+
+/// Finds a type whose name is close enough to the given type's that it
+/// might be what was meant.  Only as many trailing components of the
+/// names of the types as the given type has are compared, so that e.g.
+/// `Lsit` can suggest `Slip::List`.  Since types are only added to the
+/// reduction once they're used, the types in the source that haven't been
+/// used yet are considered too.
+fn similar_type<'r, 's>(reduce: &'r Reduce<'s>, kind: &Type) -> Option<&'r Path<'s>> {
+    let parts = kind
+        .parts()
+        .iter()
+        .flat_map(Token::value)
+        .collect::<Vec<_>>();
+    let name = parts.join("::");
+    let annotated = reduce
+        .annotated
+        .iter()
+        .filter(|(_, items)| items.iter().any(|item| item.is_type()))
+        .map(|(path, _)| path);
+    // Sorted, so that which of two equally close types is suggested doesn't
+    // depend on the order of the maps.
+    let mut paths = reduce.types.keys().chain(annotated).collect::<Vec<_>>();
+    paths.sort_by_cached_key(|path| path.to_string());
+    paths.dedup();
+    let candidates = paths
+        .into_iter()
+        .filter(|path| path.base.len() >= parts.len())
+        .map(|path| (path, path.base[path.base.len() - parts.len()..].join("::")))
+        .filter(|(path, suffix)| path.base.len() > parts.len() || *suffix != name);
+    closest(&name, candidates, |(_, suffix)| suffix).map(|(path, _)| path)
 }
 
 fn ambiguous_type_error(
@@ -161,28 +331,33 @@ fn ambiguous_type_error(
     mut applicable: impl Iterator<Item = Span>, //&[(Span, &Path<'_>, TypeId)],
     kind: &Type,
 ) {
-    reduce.set.emit(
+    let mut emission = Emission::new(
         Name::AmbiguousType,
         kind.span(),
         format!("ambiguous type {}", kind),
     );
-
-    if reduce.set.active(Name::AmbiguousType) {
-        let first = applicable.next().unwrap();
-        reduce
-            .set
-            .emit(Name::AcceptedType, first, "note: accepted type");
+    let first = applicable.next().unwrap();
+    if reduce.set.active(Name::AcceptedType) {
+        emission = emission.with_label(first, "accepted type");
+    }
+    if reduce.set.active(Name::PossibleType) {
         for given in applicable {
-            reduce
-                .set
-                .emit(Name::PossibleType, given, "note: possible type");
+            emission = emission.with_label(given, "possible type");
         }
     }
+    reduce.set.report(emission);
 }
 
 fn generate_possible_references<'r, 's: 'r>(
     scope: &'r Scope<'s>,
     kind: &'s Type,
+) -> impl Iterator<Item = (Span, Path<'s>)> + 'r {
+    in_scope_references(scope, kind).chain(imported_references(scope, kind))
+}
+
+pub(super) fn in_scope_references<'r, 's: 'r>(
+    scope: &'r Scope<'s>,
+    kind: &'s Type,
 ) -> impl Iterator<Item = (Span, Path<'s>)> + 'r {
     let in_scope = scope
         .base()
@@ -206,31 +381,36 @@ fn generate_possible_references<'r, 's: 'r>(
             out.push(kind);
             Some((typ.span(), out))
         });
-    let use_scope = scope
+    // The scan above only covers the modules we're in; `::V` is the root.
+    std::iter::once((kind.span(), vec![kind]))
+        .chain(in_scope)
+        .map(|(span, types)| (span, Path::from_syn(types, None)))
+}
+
+pub(super) fn imported_references<'r, 's: 'r>(
+    scope: &'r Scope<'s>,
+    kind: &'s Type,
+) -> impl Iterator<Item = (Span, Path<'s>)> + 'r {
+    scope
         // First, list all of the `use`s in scope.
         .uses()
         .iter()
         .cloned()
-        .flat_map(move |use_| {
-            // Then, for each use, we'll split out the trails.  Each trail
-            // will tell us how to import a specific subset of types.
-            use_.trails().iter().map(move |trail| (use_, trail))
+        // Then, for each use, we'll flatten out the tree.  Each import
+        // in the tree brings in a single item (or, for a glob import,
+        // every item in a module).
+        .flat_map(Use::imports)
+        // Now we'll see what our type would refer to through each import,
+        // discarding the ones it can't refer to anything through.  If we
+        // have `use A::B`, then if we're looking at type `C`, we can safely
+        // discard it; but `B::C` would refer to `A::B::C`.  This is more
+        // important for renaming uses (`use A::B as C`) or glob uses
+        // (`use A::*`).
+        .filter_map(move |import| {
+            let path = import.resolve(kind)?;
+            let path = Path::new(path.into_iter().map(Cow::Borrowed).collect(), None);
+            Some((import.span(), path))
         })
-        // So we need to filter out all of the trails that don't apply
-        // to this specific subset.  Basically, if we have `use A::B`,
-        // then if we're looking at type `C`, we can safely discard
-        // this.
-        .filter(move |(_, trail)| trail.applies(kind))
-        // Now we know that the use works, so we're now going to combine
-        // our type with the prefix from the `use`.  Using the same
-        // example as above, we'll attach `B` onto `A`, to make `A::B`;
-        // this is more important for renaming uses (`use A::B as C`)
-        // or star uses (`use A::*`).
-        .map(move |(use_, trail)| (trail.span(), trail.combine(use_.prefix(), kind)));
-
-    in_scope
-        .chain(use_scope)
-        .map(|(span, types)| (span, Path::from_syn(types, None)))
 }
 
 fn void_prime(r: &mut ModuleSpec) -> TypeId {
@@ -254,6 +434,12 @@ fn i64_prime(r: &mut ModuleSpec) -> TypeId {
 fn size_prime(r: &mut ModuleSpec) -> TypeId {
     r.size_type()
 }
+fn f32_prime(r: &mut ModuleSpec) -> TypeId {
+    r.float_type(32)
+}
+fn f64_prime(r: &mut ModuleSpec) -> TypeId {
+    r.float_type(64)
+}
 fn ptr_prime(r: &mut ModuleSpec) -> TypeId {
     r.pointer_type()
 }
@@ -273,6 +459,8 @@ static STATIC_TYPES: &[(Path<'static>, StaticTypeGen)] = &[
     (slip_path!(u64), i64_prime),
     (slip_path!(isize), size_prime),
     (slip_path!(usize), size_prime),
+    (slip_path!(f32), f32_prime),
+    (slip_path!(f64), f64_prime),
     (slip_path!(["$slip"]::["ptr"]), ptr_prime),
 ];
 
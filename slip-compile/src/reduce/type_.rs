@@ -1,4 +1,4 @@
-use crate::syn::{Item, Module, Root, Type, Use};
+use crate::syn::{Attribute, Item, Module, Root, Type, Use};
 
 /// Information about the current position in the type structure of the
 /// associated item.  This includes the current path to the item (i.e. module
@@ -10,11 +10,15 @@ use crate::syn::{Item, Module, Root, Type, Use};
 pub struct Scope<'s> {
     base: Vec<&'s Type>,
     uses: Vec<&'s Use>,
+    exported: bool,
+    attributes: Vec<&'s Attribute>,
 }
 
 struct Stack<'s> {
     typ_: Vec<&'s Type>,
     use_: Vec<Vec<&'s Use>>,
+    export: Vec<bool>,
+    attr_: Vec<&'s [Attribute]>,
 }
 
 impl<'s> Scope<'s> {
@@ -46,6 +50,19 @@ impl<'s> Scope<'s> {
     pub fn uses(&self) -> &[&'s Use] {
         &self.uses[..]
     }
+
+    /// Whether or not every module along the path to the current item is
+    /// exported.  If one of them isn't, then nothing within it can be
+    /// referenced from outside of it, even if it is exported itself.
+    pub fn exported(&self) -> bool {
+        self.exported
+    }
+
+    /// The attributes of every module along the path to the current item,
+    /// outermost first.
+    pub fn attributes(&self) -> &[&'s Attribute] {
+        &self.attributes
+    }
 }
 
 impl<'s> Stack<'s> {
@@ -53,15 +70,21 @@ impl<'s> Stack<'s> {
         Stack {
             typ_: vec![],
             use_: vec![vec![]],
+            export: vec![],
+            attr_: vec![],
         }
     }
 
-    fn push(&mut self, typ: &'s Type) {
+    fn push(&mut self, typ: &'s Type, export: bool, attributes: &'s [Attribute]) {
         self.typ_.push(typ);
         self.use_.push(vec![]);
+        self.export.push(export);
+        self.attr_.push(attributes);
     }
 
     fn pop(&mut self) {
+        self.attr_.pop();
+        self.export.pop();
         self.use_.pop();
         self.typ_.pop();
     }
@@ -75,7 +98,14 @@ impl<'s> Stack<'s> {
         //     .unwrap_or_else(Span::identity);
         let base = self.typ_.clone();
         let uses = self.use_.iter().flatten().cloned().collect::<Vec<_>>();
-        Scope { base, uses }
+        let exported = self.export.iter().all(|e| *e);
+        let attributes = self.attr_.iter().cloned().flatten().collect::<Vec<_>>();
+        Scope {
+            base,
+            uses,
+            exported,
+            attributes,
+        }
     }
 
     fn visit(&mut self, item: &'s Item) -> Box<dyn Iterator<Item = (Scope<'s>, &'s Item)> + 's> {
@@ -85,6 +115,7 @@ impl<'s> Stack<'s> {
             Item::Module(mod_) => self.visit_mod(mod_),
             Item::Struct(_) => self.visit_basic(item),
             Item::Use(use_) => self.visit_use(use_),
+            Item::Const(_) => self.visit_basic(item),
         }
     }
 
@@ -99,7 +130,7 @@ impl<'s> Stack<'s> {
         &mut self,
         mod_: &'s Module,
     ) -> Box<dyn Iterator<Item = (Scope<'s>, &'s Item)> + 's> {
-        self.push(mod_.kind());
+        self.push(mod_.kind(), mod_.export(), mod_.attributes());
         let result = mod_
             .items()
             .iter()
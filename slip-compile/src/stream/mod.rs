@@ -28,18 +28,27 @@ use crate::error::Error;
 ///     TokenKind::Semicolon]);
 /// # }
 /// ```
-pub struct TokenStream<'c> {
+pub struct TokenStream<'c, 'd> {
     content: &'c str,
     source: SourceId,
     offset: usize,
     position: Position,
-    diag: DiagnosticSync<'c>,
+    diag: DiagnosticSync<'d>,
     next: Option<Result<Token, Error>>,
+    restricted: bool,
+    /// Where the last token that was taken from the stream ended.  Unlike
+    /// `position`, this doesn't include any token that's only been peeked.
+    end: Position,
 }
 
-impl<'c> TokenStream<'c> {
-    /// Creates a new lexer from the given source.
-    pub fn new(content: &'c str, source: SourceId, diag: DiagnosticSync<'c>) -> TokenStream<'c> {
+impl<'c, 'd> TokenStream<'c, 'd> {
+    /// Creates a new lexer from the given source.  The source only needs to
+    /// outlive the lexer, not the diagnostics.
+    pub fn new(
+        content: &'c str,
+        source: SourceId,
+        diag: DiagnosticSync<'d>,
+    ) -> TokenStream<'c, 'd> {
         TokenStream {
             content,
             source,
@@ -47,9 +56,34 @@ impl<'c> TokenStream<'c> {
             position: Position::default(),
             diag,
             next: None,
+            restricted: false,
+            end: Position::default(),
         }
     }
 
+    /// Whether or not expressions are currently restricted.  A restricted
+    /// expression is one that is directly followed by a block - e.g. the
+    /// condition of an `if` - and so cannot contain anything that starts
+    /// with a `{`, as that would be ambiguous with the block.  Anything
+    /// that is delimited (e.g. in parenthesis) is never restricted, even
+    /// within a restricted expression.
+    pub fn restricted(&self) -> bool {
+        self.restricted
+    }
+
+    /// Runs the given function with expressions restricted, or unrestricted,
+    /// restoring the previous restriction afterwards.  See
+    /// [`TokenStream::restricted`] for more information.
+    pub fn restrict<T, F>(&mut self, restricted: bool, func: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let previous = std::mem::replace(&mut self.restricted, restricted);
+        let result = func(self);
+        self.restricted = previous;
+        result
+    }
+
     /// Retrieves the span of where the lexer is.  By the nature of
     /// a span, this means that this span has no width - it is where
     /// the lexer is in terms of parsing.  This is useful for
@@ -74,7 +108,7 @@ impl<'c> TokenStream<'c> {
     /// you don't have to call [`std::iter::Iterator::peekable`].
     pub fn peek(&mut self) -> Option<&Result<Token, Error>> {
         if self.next.is_none() {
-            let result = self.next();
+            let result = self.lex();
             self.next = result;
         }
 
@@ -104,18 +138,37 @@ impl<'c> TokenStream<'c> {
     /// takes one token kind, and if the next token isn't that token, it
     /// errors; or, if there are no more tokens, it errors.  Note that this
     /// passes errors through from the iterator.
+    ///
+    /// If the token is a `;`, the error suggests inserting it after the
+    /// previous token.
     pub fn expect_one(&mut self, kind: TokenKind) -> Result<Token, Error> {
-        match self.next() {
+        let end = Span::new(self.end, self.end, Some(self.source));
+        let (current, span) = match self.next() {
             Some(Ok(token)) => {
                 if token.kind == kind {
-                    Ok(token)
-                } else {
-                    error(&self.diag, token.kind, &[kind], token.span).map(|t| t)
+                    return Ok(token);
                 }
+                (token.kind, token.span)
             }
-            Some(Err(e)) => Err(e),
-            None => error(&self.diag, TokenKind::Eof, &[kind], self.span()).map(|t| t),
+            Some(Err(e)) => return Err(e),
+            None => (TokenKind::Eof, self.span()),
+        };
+
+        let mut emission = unexpected(current, &[kind], span);
+        if kind == TokenKind::Semicolon {
+            emission = emission.with_suggestion(
+                end,
+                ";",
+                "insert a `;` here",
+                Applicability::MachineApplicable,
+            );
         }
+        self.diag.report(emission);
+        Err(Error::UnexpectedTokenError {
+            current,
+            expected: vec![kind],
+            area: span,
+        })
     }
 
     /// This returns the type of the next token, if there is a next
@@ -224,14 +277,9 @@ impl<'c> TokenStream<'c> {
     }
 }
 
-impl<'c> Iterator for TokenStream<'c> {
-    type Item = Result<Token, Error>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.next.is_some() {
-            return self.next.take();
-        }
-
+impl TokenStream<'_, '_> {
+    /// Lexes the next token from the content, skipping any ignored tokens.
+    fn lex(&mut self) -> Option<Result<Token, Error>> {
         if self.offset >= self.content.len() {
             return None;
         }
@@ -248,18 +296,18 @@ impl<'c> Iterator for TokenStream<'c> {
                     .map(|v| value.len() - v)
                     .unwrap_or(self.position.column() + value.len());
                 let position = Position::new(self.offset + value.len(), line, column);
-                let content = if kind.has_value() {
-                    Some(self.source)
-                } else {
-                    None
-                };
-                let span = Span::new(self.position, position, content);
+                // Every token is given its source, so that diagnostics at
+                // any of them (e.g. an unexpected keyword) can be shown in it.
+                let span = Span::new(self.position, position, Some(self.source));
                 self.offset += value.len();
                 self.position = position;
                 let token = Token::new(kind, span, Some(value));
+                if kind == TokenKind::Line {
+                    self.directive(value, span);
+                }
 
                 if kind.ignore() {
-                    self.next()
+                    self.lex()
                 } else {
                     Some(Ok(token))
                 }
@@ -275,6 +323,33 @@ impl<'c> Iterator for TokenStream<'c> {
             }
         }
     }
+
+    /// Handles a `#line` directive, e.g. `#line 40 "shapes.in"`, which
+    /// remaps the lines after it; see [`SourceMap::remap()`].
+    fn directive(&self, value: &str, span: Span) {
+        let rest = value.trim_start_matches("#line").trim();
+        let (line, name) = match rest.find(char::is_whitespace) {
+            Some(index) => (&rest[..index], Some(rest[index..].trim())),
+            None => (rest, None),
+        };
+        let name = name.map(|name| name.trim_matches('"').to_string());
+        if let Ok(to) = line.parse() {
+            let from = span.start().line() + 1;
+            self.diag.remap(self.source, from, name, to);
+        }
+    }
+}
+
+impl Iterator for TokenStream<'_, '_> {
+    type Item = Result<Token, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.next.take().or_else(|| self.lex());
+        if let Some(Ok(token)) = &next {
+            self.end = token.span.end();
+        }
+        next
+    }
 }
 
 fn find_next(source: &str) -> Option<(TokenKind, ::regex::Match)> {
@@ -298,14 +373,18 @@ fn error(
     expected: &[TokenKind],
     span: Span,
 ) -> Result<!, Error> {
-    diag.emit(
-        Name::UnexpectedToken,
-        span,
-        format!("found token {}, expected one of {:?}", current, expected),
-    );
+    diag.report(unexpected(current, expected, span));
     Err(Error::UnexpectedTokenError {
         current,
         expected: expected.to_owned(),
         area: span,
     })
 }
+
+fn unexpected(current: TokenKind, expected: &[TokenKind], span: Span) -> Emission {
+    Emission::new(
+        Name::UnexpectedToken,
+        span,
+        format!("found token {}, expected one of {:?}", current, expected),
+    )
+}
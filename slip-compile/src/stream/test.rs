@@ -22,3 +22,12 @@ fn it_lexes() {
         ]
     );
 }
+
+#[test]
+fn it_gives_every_token_its_source() {
+    let diag = DiagnosticSync::default();
+    let source = diag.push("(text)", Some("let a = 1;"));
+    let lexer = TokenStream::new("let a = 1;", source, diag);
+    let mut tokens = lexer.map(Result::unwrap);
+    assert!(tokens.all(|token| token.span().source() == Some(source)));
+}
@@ -230,14 +230,17 @@ define_tokens! {
         Struct(Some(false), r"\Astruct", r#""struct""#),
         Unless(Some(false), r"\Aunless", r#""unless""#),
         Catch(Some(false), r"\Acatch", r#""catch""#),
+        Const(Some(false), r"\Aconst", r#""const""#),
         Elsif(Some(false), r"\Aelsif", r#""elsif""#),
         Match(Some(false), r"\Amatch", r#""match""#),
         Trait(Some(false), r"\Atrait", r#""trait""#),
         While(Some(false), r"\Awhile", r#""while""#),
+        False(Some(false), r"\Afalse", r#""false""#),
         Else(Some(false), r"\Aelse", r#""else""#),
         Enum(Some(false), r"\Aenum", r#""enum""#),
         This(Some(false), r"\Aself", r#""self""#),
         When(Some(false), r"\Awhen", r#""when""#),
+        True(Some(false), r"\Atrue", r#""true""#),
         For(Some(false), r"\Afor", r#""for""#),
         End(Some(false), r"\Aend", r#""end""#),
         Let(Some(false), r"\Alet", r#""let""#),
@@ -278,6 +281,7 @@ define_tokens! {
         Modulo(Some(false), r"\A%", r#""%""#),
         Star(Some(false), r"\A\*", r#""*""#),
         Period(Some(false), r"\A\.", r#"".""#),
+        Pound(Some(false), r"\A#", r##""#""##),
         Plus(Some(false), r"\A\+", r#""+""#),
         GreaterThan(Some(false), r"\A>", r#"">""#),
         RightBrace(Some(false), r"\A\}", r#""}""#),
@@ -290,6 +294,7 @@ define_tokens! {
         Integer(Some(true), r"\A(0x[[:xdigit:]]+|\d+|0[0-8]+|0b[01]+)", "Integer"),
         Float(Some(true), r"\A\d+\.\d+([eE][+-]?\d+)?", "Float"),
         Comment(None as Option<bool>, r"\A//.+\n", "Comment"),
+        Line(None as Option<bool>, r#"\A#line[ \t]+\d+(?:[ \t]+"[^"\n]*")?"#, "Line"),
         DoubleString(Some(true), r#"\A"([^"]|\\")*""#, "DoubleString"),
         SingleString(Some(true), r"\A'[^']*'", "SingleString"),
         Escape(Some(true), r"\A\\[a-z][a-zA-Z\d_-]*[!?]?", "Escape"),
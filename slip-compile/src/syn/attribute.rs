@@ -0,0 +1,94 @@
+use super::{BasicNode, Node, Roll};
+use crate::diag::Span;
+use crate::error::*;
+use crate::stream::{Token, TokenKind, TokenStream};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// An attribute on an item, e.g. `#[allow(unused-local)]`.  The arguments
+/// to an attribute are names; see [`AttributeName`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attribute {
+    name: Token,
+    arguments: Roll<AttributeName>,
+    area: Span,
+}
+
+impl Attribute {
+    pub fn name(&self) -> &Token {
+        &self.name
+    }
+
+    pub fn arguments(&self) -> &[AttributeName] {
+        self.arguments.value()
+    }
+}
+
+impl Node for Attribute {
+    fn parse(stream: &mut TokenStream) -> Result<Attribute, Error> {
+        let mut span = stream.expect_one(TokenKind::Pound)?.span();
+        span |= stream.expect_one(TokenKind::LeftBracket)?.span();
+        let name = stream.expect_one(TokenKind::Identifier)?;
+        let arguments = Roll::with_terminate_trail(
+            stream,
+            TokenKind::LeftParen,
+            TokenKind::Comma,
+            TokenKind::RightParen,
+        )?;
+        span |= stream.expect_one(TokenKind::RightBracket)?.span();
+
+        Ok(Attribute {
+            area: span | name.span() | arguments.span(),
+            name,
+            arguments,
+        })
+    }
+}
+
+impl BasicNode for Attribute {
+    fn span(&self) -> Span {
+        self.area
+    }
+}
+
+/// A name given as an argument to an attribute.  This is made of one or
+/// more identifiers separated by `.`, e.g. `type-trace.possible`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeName {
+    parts: Vec<Token>,
+    area: Span,
+}
+
+impl AttributeName {
+    pub fn parts(&self) -> &[Token] {
+        &self.parts
+    }
+}
+
+impl Node for AttributeName {
+    fn parse(stream: &mut TokenStream) -> Result<AttributeName, Error> {
+        let first = stream.expect_one(TokenKind::Identifier)?;
+        let mut span = first.span();
+        let mut parts = vec![first];
+        while stream.peek_one(TokenKind::Period) {
+            span |= stream.expect_one(TokenKind::Period)?.span();
+            let part = stream.expect_one(TokenKind::Identifier)?;
+            span |= part.span();
+            parts.push(part);
+        }
+
+        Ok(AttributeName { parts, area: span })
+    }
+}
+
+impl BasicNode for AttributeName {
+    fn span(&self) -> Span {
+        self.area
+    }
+}
+
+impl Display for AttributeName {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let parts = self.parts.iter().flat_map(Token::value).collect::<Vec<_>>();
+        write!(f, "{}", parts.join("."))
+    }
+}
@@ -0,0 +1,88 @@
+use super::function::expression::Expression;
+use super::{Attribute, BasicNode, Node, Type};
+use crate::diag::Span;
+use crate::error::*;
+use crate::stream::{TokenKind, TokenStream};
+
+/// A named value that is computed at compile time, e.g.
+/// `const MAX: i32 = 1 << 8;`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Const {
+    attributes: Vec<Attribute>,
+    export: bool,
+    name: Type,
+    kind: Type,
+    value: Expression,
+    area: Span,
+}
+
+impl Const {
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn export(&self) -> bool {
+        self.export
+    }
+
+    /// The name of the constant.  It is a type, so that it can be placed in
+    /// a path like any other item; it is only ever a single name, without
+    /// generics.
+    pub fn name(&self) -> &Type {
+        &self.name
+    }
+
+    pub fn kind(&self) -> &Type {
+        &self.kind
+    }
+
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+}
+
+impl Node for Const {
+    fn parse(stream: &mut TokenStream) -> Result<Const, Error> {
+        let attributes = super::attributes(stream)?;
+        let export = super::export(stream)?;
+        Const::parse_export(stream, attributes, export)
+    }
+}
+
+impl Const {
+    pub(super) fn parse_export(
+        stream: &mut TokenStream,
+        attributes: Vec<Attribute>,
+        export: Option<Span>,
+    ) -> Result<Const, Error> {
+        let mut span = stream.expect_one(TokenKind::Const)?.span();
+        if let Some(s) = export {
+            span |= s;
+        }
+        let name = stream.expect_one(TokenKind::ModuleName)?;
+        let name_span = name.span();
+        span |= name_span;
+        span |= stream.expect_one(TokenKind::Colon)?.span();
+        let kind = Type::parse(stream)?;
+        span |= kind.span();
+        span |= stream.expect_one(TokenKind::Equals)?.span();
+        let value = Expression::parse(stream)?;
+        span |= value.span();
+        span |= stream.expect_one(TokenKind::Semicolon)?.span();
+
+        Ok(Const {
+            attributes,
+            export: export.is_some(),
+            name: Type::new(vec![name], None, name_span),
+            kind,
+            value,
+            area: span,
+        })
+    }
+}
+
+impl BasicNode for Const {
+    fn span(&self) -> Span {
+        self.area
+    }
+}
@@ -1,25 +1,48 @@
 use super::function::expression::Expression;
-use super::{BasicNode, Node, Roll, Type};
+use super::{Attribute, BasicNode, Node, Roll, Type};
 use crate::diag::Span;
 use crate::error::Error;
 use crate::stream::{Token, TokenKind, TokenStream};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Enum(Type, Roll<EnumVariant>, Span);
+pub struct Enum(bool, Type, Roll<EnumVariant>, Span, Vec<Attribute>);
 
 impl Enum {
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.4
+    }
+
+    pub fn export(&self) -> bool {
+        self.0
+    }
+
     pub fn kind(&self) -> &Type {
-        &self.0
+        &self.1
     }
 
     pub fn variants(&self) -> &[EnumVariant] {
-        self.1.value()
+        self.2.value()
     }
 }
 
 impl Node for Enum {
     fn parse(stream: &mut TokenStream) -> Result<Enum, Error> {
+        let attributes = super::attributes(stream)?;
+        let export = super::export(stream)?;
+        Enum::parse_export(stream, attributes, export)
+    }
+}
+
+impl Enum {
+    pub(super) fn parse_export(
+        stream: &mut TokenStream,
+        attributes: Vec<Attribute>,
+        export: Option<Span>,
+    ) -> Result<Enum, Error> {
         let mut span = stream.expect_one(TokenKind::Enum)?.span();
+        if let Some(s) = export {
+            span |= s;
+        }
         let kind = Type::parse(stream)?;
         span |= kind.span();
 
@@ -32,13 +55,13 @@ impl Node for Enum {
 
         span |= contents.span();
 
-        Ok(Enum(kind, contents, span))
+        Ok(Enum(export.is_some(), kind, contents, span, attributes))
     }
 }
 
 impl BasicNode for Enum {
     fn span(&self) -> Span {
-        self.2
+        self.3
     }
 }
 
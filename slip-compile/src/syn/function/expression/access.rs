@@ -9,6 +9,14 @@ use crate::syn::{BasicNode, Node};
 pub struct Access(pub(super) Box<Expression>, pub(super) FunctionName, Span);
 
 impl Access {
+    pub fn base(&self) -> &Expression {
+        &self.0
+    }
+
+    pub fn name(&self) -> &FunctionName {
+        &self.1
+    }
+
     pub fn parse(stream: &mut TokenStream, left: Expression) -> Result<Access, Error> {
         let mut span = left.span();
         span |= stream.expect_one(TokenKind::Period)?.span();
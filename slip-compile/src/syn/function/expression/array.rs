@@ -9,12 +9,14 @@ pub struct Array(Roll<Expression>);
 
 impl Node for Array {
     fn parse(stream: &mut TokenStream) -> Result<Array, Error> {
-        let contents = Roll::with_terminate_trail(
-            stream,
-            TokenKind::LeftBracket,
-            TokenKind::Comma,
-            TokenKind::RightBracket,
-        )?;
+        let contents = stream.restrict(false, |stream| {
+            Roll::with_terminate_trail(
+                stream,
+                TokenKind::LeftBracket,
+                TokenKind::Comma,
+                TokenKind::RightBracket,
+            )
+        })?;
         Ok(Array(contents))
     }
 }
@@ -1,4 +1,4 @@
-use super::{Array, Map, Tuple};
+use super::{Array, Construct, Map, Member, Tuple};
 use crate::diag::Span;
 use crate::error::*;
 use crate::stream::{Token, TokenKind, TokenStream};
@@ -9,12 +9,17 @@ use crate::syn::{BasicNode, Node, Type};
 /// expression, which must yield a value.
 pub enum Atom {
     Underscore(Token),
+    This(Token),
     Ident(Token),
     SingleString(Token),
     DoubleString(Token),
     Integer(Token),
     Float(Token),
+    /// Either `true` or `false`.
+    Bool(Token),
     Type(Type),
+    Construct(Construct),
+    Member(Member),
     Map(Map),
     Array(Array),
     Tuple(Tuple),
@@ -31,14 +36,32 @@ impl Node for Atom {
             )),
             Some(TokenKind::Integer) => Ok(Atom::Integer(stream.expect_one(TokenKind::Integer)?)),
             Some(TokenKind::Float) => Ok(Atom::Float(stream.expect_one(TokenKind::Float)?)),
+            Some(TokenKind::True) => Ok(Atom::Bool(stream.expect_one(TokenKind::True)?)),
+            Some(TokenKind::False) => Ok(Atom::Bool(stream.expect_one(TokenKind::False)?)),
             Some(TokenKind::Identifier) => {
                 Ok(Atom::Ident(stream.expect_one(TokenKind::Identifier)?))
             }
             Some(TokenKind::Underscore) => {
                 Ok(Atom::Underscore(stream.expect_one(TokenKind::Underscore)?))
             }
-            Some(TokenKind::ModuleName) => Ok(Atom::Type(Type::parse(stream)?)),
-            Some(TokenKind::LeftBrace) => Ok(Atom::Map(Map::parse(stream)?)),
+            Some(TokenKind::This) => Ok(Atom::This(stream.expect_one(TokenKind::This)?)),
+            Some(TokenKind::ModuleName) => match Type::parse_member(stream)? {
+                (kind, Some(name)) => Ok(Atom::Member(Member::new(kind, name))),
+                (kind, None) => {
+                    if !stream.restricted() && stream.peek_kind() == Some(TokenKind::LeftBrace) {
+                        Ok(Atom::Construct(Construct::parse(stream, kind)?))
+                    } else {
+                        // A type with more than one part can't be a value on
+                        // its own, so it has to be a member of a type, e.g.
+                        // `Color::Red`.
+                        match Member::from_type(&kind) {
+                            Some(member) => Ok(Atom::Member(member)),
+                            None => Ok(Atom::Type(kind)),
+                        }
+                    }
+                }
+            },
+            Some(TokenKind::LeftBrace) if !stream.restricted() => Ok(Atom::Map(Map::parse(stream)?)),
             Some(TokenKind::LeftBracket) => Ok(Atom::Array(Array::parse(stream)?)),
             Some(TokenKind::LeftParen) => Ok(Atom::Tuple(Tuple::parse(stream)?)),
             _ => stream
@@ -47,8 +70,11 @@ impl Node for Atom {
                     TokenKind::DoubleString,
                     TokenKind::Integer,
                     TokenKind::Float,
+                    TokenKind::True,
+                    TokenKind::False,
                     TokenKind::Identifier,
                     TokenKind::Underscore,
+                    TokenKind::This,
                     TokenKind::ModuleName,
                     TokenKind::DoublePlus,
                     TokenKind::DoubleMinus,
@@ -69,12 +95,16 @@ impl BasicNode for Atom {
     fn span(&self) -> Span {
         match self {
             Atom::Underscore(token) => token.span(),
+            Atom::This(token) => token.span(),
             Atom::Ident(token) => token.span(),
             Atom::SingleString(token) => token.span(),
             Atom::DoubleString(token) => token.span(),
             Atom::Integer(token) => token.span(),
             Atom::Float(token) => token.span(),
+            Atom::Bool(token) => token.span(),
             Atom::Type(kind) => kind.span(),
+            Atom::Construct(construct) => construct.span(),
+            Atom::Member(member) => member.span(),
             Atom::Map(map) => map.span(),
             Atom::Array(array) => array.span(),
             Atom::Tuple(tuple) => tuple.span(),
@@ -1,5 +1,5 @@
 use super::super::FunctionName;
-use super::{Atom, Expression};
+use super::{Atom, Expression, Member};
 use crate::diag::Span;
 use crate::error::*;
 use crate::stream::{Token, TokenKind, TokenStream};
@@ -9,17 +9,20 @@ use crate::syn::{BasicNode, Roll};
 pub enum Call {
     Unified(Box<Unified>),
     Standard(Box<Standard>),
+    Static(Box<Static>),
     Expression(Box<Expr>),
 }
 
 impl Call {
     pub fn parse(stream: &mut TokenStream, left: Expression) -> Result<Call, Error> {
-        let arguments = Roll::with_terminate_trail(
-            stream,
-            TokenKind::LeftParen,
-            TokenKind::Comma,
-            TokenKind::RightParen,
-        )?;
+        let arguments = stream.restrict(false, |stream| {
+            Roll::with_terminate_trail(
+                stream,
+                TokenKind::LeftParen,
+                TokenKind::Comma,
+                TokenKind::RightParen,
+            )
+        })?;
         let span = left.span() | arguments.span();
 
         match left {
@@ -29,6 +32,9 @@ impl Call {
             Expression::Atom(Atom::Ident(tok)) => Ok(Call::Standard(Box::new(Standard::new(
                 tok, arguments, span,
             )))),
+            Expression::Atom(Atom::Member(member)) => Ok(Call::Static(Box::new(Static::new(
+                member, arguments, span,
+            )))),
             v => Ok(Call::Expression(Box::new(Expr::new(v, arguments, span)))),
         }
     }
@@ -39,6 +45,7 @@ impl BasicNode for Call {
         match self {
             Call::Unified(un) => un.span,
             Call::Standard(std) => std.span,
+            Call::Static(st) => st.span,
             Call::Expression(expr) => expr.span,
         }
     }
@@ -80,6 +87,22 @@ impl Standard {
     }
 }
 
+/// A call to a member of a type; either the construction of an enum variant,
+/// e.g. `Shape::Circle(1.0)`, or a static function call, e.g.
+/// `Int::default()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Static {
+    pub base: Member,
+    pub params: Roll<Expression>,
+    pub span: Span,
+}
+
+impl Static {
+    fn new(base: Member, params: Roll<Expression>, span: Span) -> Static {
+        Static { base, params, span }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Expr {
     pub base: Expression,
@@ -0,0 +1,94 @@
+use super::Expression;
+use crate::diag::Span;
+use crate::error::*;
+use crate::stream::{Token, TokenKind, TokenStream};
+use crate::syn::{BasicNode, Node, Roll, Type};
+
+/// Constructs a value of a struct type, with a value for each of its fields;
+/// e.g. `Point { x: 1, y: 2 }`.  A field without a value takes the value of
+/// the local with the same name, so `Point { x, y }` is the same as
+/// `Point { x: x, y: y }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Construct {
+    kind: Type,
+    fields: Roll<ConstructField>,
+    area: Span,
+}
+
+impl Construct {
+    pub fn kind(&self) -> &Type {
+        &self.kind
+    }
+
+    pub fn fields(&self) -> &Roll<ConstructField> {
+        &self.fields
+    }
+
+    pub fn parse(stream: &mut TokenStream, kind: Type) -> Result<Construct, Error> {
+        let fields = stream.restrict(false, |stream| {
+            Roll::with_terminate_trail(
+                stream,
+                TokenKind::LeftBrace,
+                TokenKind::Comma,
+                TokenKind::RightBrace,
+            )
+        })?;
+        let span = kind.span() | fields.span();
+
+        Ok(Construct {
+            kind,
+            fields,
+            area: span,
+        })
+    }
+}
+
+impl BasicNode for Construct {
+    fn span(&self) -> Span {
+        self.area
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstructField {
+    name: Token,
+    value: Option<Expression>,
+    area: Span,
+}
+
+impl ConstructField {
+    pub fn name(&self) -> &Token {
+        &self.name
+    }
+
+    pub fn value(&self) -> Option<&Expression> {
+        self.value.as_ref()
+    }
+}
+
+impl Node for ConstructField {
+    fn parse(stream: &mut TokenStream) -> Result<ConstructField, Error> {
+        let name = stream.expect_one(TokenKind::Identifier)?;
+        let mut span = name.span();
+        let value = if stream.peek_kind() == Some(TokenKind::Colon) {
+            span |= stream.expect_one(TokenKind::Colon)?.span();
+            let value = Expression::parse(stream)?;
+            span |= value.span();
+            Some(value)
+        } else {
+            None
+        };
+
+        Ok(ConstructField {
+            name,
+            value,
+            area: span,
+        })
+    }
+}
+
+impl BasicNode for ConstructField {
+    fn span(&self) -> Span {
+        self.area
+    }
+}
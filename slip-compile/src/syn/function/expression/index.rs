@@ -8,13 +8,23 @@ use crate::syn::{BasicNode, Roll};
 pub struct Index(Box<Expression>, Roll<Expression>, Span);
 
 impl Index {
+    pub fn base(&self) -> &Expression {
+        &self.0
+    }
+
+    pub fn arguments(&self) -> &Roll<Expression> {
+        &self.1
+    }
+
     pub fn parse(stream: &mut TokenStream, left: Expression) -> Result<Index, Error> {
-        let arguments = Roll::with_terminate_trail(
-            stream,
-            TokenKind::LeftBrace,
-            TokenKind::Comma,
-            TokenKind::RightBrace,
-        )?;
+        let arguments = stream.restrict(false, |stream| {
+            Roll::with_terminate_trail_once(
+                stream,
+                TokenKind::LeftBracket,
+                TokenKind::Comma,
+                TokenKind::RightBracket,
+            )
+        })?;
         let span = left.span() | arguments.span();
 
         Ok(Index(Box::new(left), arguments, span))
@@ -8,6 +8,18 @@ use crate::syn::BasicNode;
 pub struct InfixOperation(Box<Expression>, Token, Box<Expression>, Span);
 
 impl InfixOperation {
+    pub fn left(&self) -> &Expression {
+        &self.0
+    }
+
+    pub fn operator(&self) -> &Token {
+        &self.1
+    }
+
+    pub fn right(&self) -> &Expression {
+        &self.2
+    }
+
     pub fn parse(stream: &mut TokenStream, left: Expression) -> Result<InfixOperation, Error> {
         let op = stream.next().unwrap().unwrap();
         let prec: Precedence = op.kind.into();
@@ -9,12 +9,14 @@ pub struct Map(Roll<MapPair>);
 
 impl Node for Map {
     fn parse(stream: &mut TokenStream) -> Result<Map, Error> {
-        let contents = Roll::with_terminate_trail(
-            stream,
-            TokenKind::LeftBrace,
-            TokenKind::Comma,
-            TokenKind::RightBrace,
-        )?;
+        let contents = stream.restrict(false, |stream| {
+            Roll::with_terminate_trail(
+                stream,
+                TokenKind::LeftBrace,
+                TokenKind::Comma,
+                TokenKind::RightBrace,
+            )
+        })?;
         Ok(Map(contents))
     }
 }
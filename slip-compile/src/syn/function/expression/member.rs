@@ -0,0 +1,60 @@
+use crate::diag::Span;
+use crate::stream::Token;
+use crate::syn::{BasicNode, Type};
+
+/// A member of a type; this is either a variant of an enum, e.g.
+/// `Color::Red`, or a function on the type, e.g. `Int::default`.  Which one
+/// it is can only be known once the type is resolved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Member {
+    kind: Type,
+    name: Token,
+    area: Span,
+}
+
+impl Member {
+    pub fn new(kind: Type, name: Token) -> Member {
+        let area = kind.span() | name.span();
+        Member { kind, name, area }
+    }
+
+    /// Splits the last part off of the given type, so that e.g. the type
+    /// `Some::Color::Red` becomes the member `Red` of `Some::Color`.  Any
+    /// generics on the type are kept on the base type, so `Option::Some<T>`
+    /// becomes the member `Some` of `Option<T>`.  If the type only has one
+    /// part, it can't be a member.
+    pub fn from_type(kind: &Type) -> Option<Member> {
+        if kind.parts().len() < 2 {
+            return None;
+        }
+
+        let area = kind.span();
+        let mut parts = kind.parts().to_vec();
+        let name = parts.pop().unwrap();
+        let base_area = parts
+            .iter()
+            .map(Token::span)
+            .fold(parts[0].span(), |a, b| a | b);
+        let base = Type::new(parts, kind.generics().clone(), base_area);
+
+        Some(Member {
+            kind: base,
+            name,
+            area,
+        })
+    }
+
+    pub fn kind(&self) -> &Type {
+        &self.kind
+    }
+
+    pub fn name(&self) -> &Token {
+        &self.name
+    }
+}
+
+impl BasicNode for Member {
+    fn span(&self) -> Span {
+        self.area
+    }
+}
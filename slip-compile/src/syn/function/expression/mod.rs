@@ -35,9 +35,11 @@ mod access;
 mod array;
 mod atom;
 mod call;
+mod construct;
 mod index;
 mod infix;
 mod map;
+mod member;
 mod precedence;
 mod prefix;
 mod suffix;
@@ -47,9 +49,11 @@ pub use self::access::Access;
 pub use self::array::Array;
 pub use self::atom::Atom;
 pub use self::call::Call;
+pub use self::construct::{Construct, ConstructField};
 pub use self::index::Index;
 pub use self::infix::InfixOperation;
 pub use self::map::{Map, MapPair};
+pub use self::member::Member;
 use self::precedence::Precedence;
 pub use self::prefix::PrefixOperation;
 pub use self::suffix::SuffixOperation;
@@ -78,7 +82,7 @@ impl Expression {
                     Expression::Access(Box::new(Access::parse(stream, base)?))
                 }
                 Some(TokenKind::LeftParen) => Expression::Call(Call::parse(stream, base)?),
-                Some(TokenKind::LeftBrace) => Expression::Index(Index::parse(stream, base)?),
+                Some(TokenKind::LeftBracket) => Expression::Index(Index::parse(stream, base)?),
                 Some(TokenKind::DoublePlus) | Some(TokenKind::DoubleMinus) => {
                     Expression::Suffix(SuffixOperation::parse(stream, base)?)
                 }
@@ -86,6 +90,7 @@ impl Expression {
                 | Some(TokenKind::LessThanEqual)
                 | Some(TokenKind::GreaterThan)
                 | Some(TokenKind::GreaterThanEqual)
+                | Some(TokenKind::Spaceship)
                 | Some(TokenKind::Compare)
                 | Some(TokenKind::Plus)
                 | Some(TokenKind::Minus)
@@ -112,6 +117,13 @@ impl Expression {
         Ok(base)
     }
 
+    /// Parses an expression that is directly followed by a block, such as
+    /// the condition of an `if` or a `while`.  See
+    /// [`TokenStream::restricted`] for more information.
+    pub fn parse_condition(stream: &mut TokenStream) -> Result<Expression, Error> {
+        stream.restrict(true, Expression::parse)
+    }
+
     fn parse_atom(stream: &mut TokenStream) -> Result<Expression, Error> {
         match stream.peek_kind() {
             Some(TokenKind::DoublePlus)
@@ -25,7 +25,7 @@ pub(super) enum Precedence {
     PlusMinus,
     /// Bitwise shifts (`>>`, `<<`).  Left-associative.
     Shift,
-    /// Ordering operations (`>`, `>=`, `<`, `<=`).  Left-associative.
+    /// Ordering operations (`>`, `>=`, `<`, `<=`, `<=>`).  Left-associative.
     Ord,
     /// Equality operations (`==`, `!=`). Left-associative.
     Eq,
@@ -74,6 +74,7 @@ impl Into<Precedence> for TokenKind {
     fn into(self) -> Precedence {
         match self {
             TokenKind::LeftParen
+            | TokenKind::LeftBracket
             | TokenKind::Period
             | TokenKind::DoublePlus
             | TokenKind::DoubleMinus => Precedence::SuffixPlusCallAccess,
@@ -83,7 +84,8 @@ impl Into<Precedence> for TokenKind {
             TokenKind::LessThan
             | TokenKind::LessThanEqual
             | TokenKind::GreaterThan
-            | TokenKind::GreaterThanEqual => Precedence::Ord,
+            | TokenKind::GreaterThanEqual
+            | TokenKind::Spaceship => Precedence::Ord,
             TokenKind::Compare | TokenKind::NotEqual => Precedence::Eq,
             TokenKind::BitwiseAnd => Precedence::BitwiseAnd,
             TokenKind::BitwiseXor => Precedence::BitwiseXor,
@@ -7,6 +7,16 @@ use crate::syn::{BasicNode, Node};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrefixOperation(Box<Expression>, Token, Span);
 
+impl PrefixOperation {
+    pub fn operand(&self) -> &Expression {
+        &self.0
+    }
+
+    pub fn operator(&self) -> &Token {
+        &self.1
+    }
+}
+
 impl Node for PrefixOperation {
     fn parse(stream: &mut TokenStream) -> Result<PrefixOperation, Error> {
         let op = stream.expect_any(&[
@@ -8,6 +8,14 @@ use crate::syn::BasicNode;
 pub struct SuffixOperation(Box<Expression>, Token, Span);
 
 impl SuffixOperation {
+    pub fn operand(&self) -> &Expression {
+        &self.0
+    }
+
+    pub fn operator(&self) -> &Token {
+        &self.1
+    }
+
     pub fn parse(stream: &mut TokenStream, left: Expression) -> Result<SuffixOperation, Error> {
         let op = stream.expect_any(&[TokenKind::DoublePlus, TokenKind::DoubleMinus])?;
         let span = left.span() | op.span();
@@ -7,14 +7,24 @@ use crate::syn::{BasicNode, Node, Roll};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tuple(Roll<Expression>);
 
+impl Tuple {
+    /// The elements of the tuple.  A tuple with a single element is also
+    /// how a parenthesized expression is parsed, e.g. `(a + b)`.
+    pub fn elements(&self) -> &[Expression] {
+        self.0.value()
+    }
+}
+
 impl Node for Tuple {
     fn parse(stream: &mut TokenStream) -> Result<Tuple, Error> {
-        let contents = Roll::with_terminate_trail(
-            stream,
-            TokenKind::LeftParen,
-            TokenKind::Comma,
-            TokenKind::RightParen,
-        )?;
+        let contents = stream.restrict(false, |stream| {
+            Roll::with_terminate_trail(
+                stream,
+                TokenKind::LeftParen,
+                TokenKind::Comma,
+                TokenKind::RightParen,
+            )
+        })?;
         Ok(Tuple(contents))
     }
 }
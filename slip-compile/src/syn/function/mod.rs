@@ -4,13 +4,14 @@ use self::statement::StatementGroup;
 use crate::diag::Span;
 use crate::error::*;
 use crate::stream::{Token, TokenKind, TokenStream};
-use crate::syn::{BasicNode, Node, Roll, Type};
+use crate::syn::{Attribute, BasicNode, Node, Roll, Type};
 
 pub mod expression;
 pub mod statement;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Function {
+    attributes: Vec<Attribute>,
     export: bool,
     name: FunctionName,
     generics: Roll<Type>,
@@ -21,6 +22,10 @@ pub struct Function {
 }
 
 impl Function {
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
     pub fn export(&self) -> bool {
         self.export
     }
@@ -72,11 +77,21 @@ impl Function {
 
 impl Node for Function {
     fn parse(stream: &mut TokenStream) -> Result<Function, Error> {
-        let export = if stream.peek_one(TokenKind::Export) {
-            Some(stream.expect_one(TokenKind::Export)?.span())
-        } else {
-            None
-        };
+        let attributes = super::attributes(stream)?;
+        let export = super::export(stream)?;
+        Function::parse_export(stream, attributes, export)
+    }
+}
+
+impl Function {
+    /// Parses the function, with the span of the `export` in front of it,
+    /// if there was one.  This is used by [`crate::syn::Item`], which has
+    /// to parse the `export` before it knows which item follows it.
+    pub(super) fn parse_export(
+        stream: &mut TokenStream,
+        attributes: Vec<Attribute>,
+        export: Option<Span>,
+    ) -> Result<Function, Error> {
         let mut span = stream.expect_one(TokenKind::Fn)?.span();
         if let Some(s) = export {
             span |= s;
@@ -84,12 +99,7 @@ impl Node for Function {
         let name = FunctionName::parse(stream)?;
         span |= name.span();
         let generics = if stream.peek_one(TokenKind::LessThan) {
-            let roll = Roll::with_terminate_once(
-                stream,
-                TokenKind::LessThan,
-                TokenKind::Comma,
-                TokenKind::GreaterThan,
-            )?;
+            let roll = Type::parse_generics(stream)?;
             span |= roll.span();
             roll
         } else {
@@ -119,6 +129,7 @@ impl Node for Function {
         };
 
         Ok(Function {
+            attributes,
             export: export.is_some(),
             name,
             generics,
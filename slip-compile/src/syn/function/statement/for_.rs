@@ -34,7 +34,7 @@ impl Node for For {
         let mut span = stream.expect_one(TokenKind::For)?.span();
         let token = stream.expect_one(TokenKind::Identifier)?;
         span |= token.span();
-        let condition = Expression::parse(stream)?;
+        let condition = Expression::parse_condition(stream)?;
         span |= condition.span();
         let body = StatementGroup::parse(stream)?;
         span |= body.span();
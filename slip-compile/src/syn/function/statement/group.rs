@@ -1,3 +1,4 @@
+use super::super::expression::Expression;
 use super::Statement;
 use crate::diag::Span;
 use crate::error::*;
@@ -7,14 +8,24 @@ use crate::syn::{BasicNode, Node};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatementGroup {
     contents: Vec<Statement>,
+    tail: Option<Expression>,
     area: Span,
 }
 
 impl StatementGroup {
+    /// The statements in the group.  This doesn't include the tail
+    /// expression, if there is one.
     pub fn statements(&self) -> &[Statement] {
         &self.contents
     }
 
+    /// The expression at the end of the group that isn't terminated by a
+    /// `;`, e.g. the `a + b` in `{ let a = 1; a + b }`.  This is the value of
+    /// the group.
+    pub fn tail(&self) -> Option<&Expression> {
+        self.tail.as_ref()
+    }
+
     pub fn len(&self) -> usize {
         self.contents.len()
     }
@@ -36,15 +47,34 @@ impl Node for StatementGroup {
     fn parse(stream: &mut TokenStream) -> Result<StatementGroup, Error> {
         let mut span = stream.expect_one(TokenKind::LeftBrace)?.span();
         let mut contents = vec![];
+        let mut tail = None;
         while !stream.peek_one(TokenKind::RightBrace) {
-            let stmt = Statement::parse(stream)?;
-            span |= stmt.span();
-            contents.push(stmt);
+            // An empty statement, e.g. a stray `;` after an `if`.
+            if stream.peek_one(TokenKind::Semicolon) {
+                span |= stream.expect_one(TokenKind::Semicolon)?.span();
+                continue;
+            }
+
+            if let Some(stmt) = Statement::parse_keyword(stream)? {
+                span |= stmt.span();
+                contents.push(stmt);
+                continue;
+            }
+
+            let expr = Expression::parse(stream)?;
+            span |= expr.span();
+            if stream.peek_one(TokenKind::RightBrace) {
+                tail = Some(expr);
+            } else {
+                span |= stream.expect_one(TokenKind::Semicolon)?.span();
+                contents.push(Statement::Expression(expr));
+            }
         }
 
         span |= stream.expect_one(TokenKind::RightBrace)?.span();
         Ok(StatementGroup {
             contents,
+            tail,
             area: span,
         })
     }
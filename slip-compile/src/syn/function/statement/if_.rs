@@ -29,7 +29,7 @@ impl If {
 impl Node for If {
     fn parse(stream: &mut TokenStream) -> Result<If, Error> {
         let mut span = stream.expect_one(TokenKind::If)?.span();
-        let condition = Expression::parse(stream)?;
+        let condition = Expression::parse_condition(stream)?;
         span |= condition.span();
         let base = StatementGroup::parse(stream)?;
         span |= base.span();
@@ -101,7 +101,7 @@ impl IfCondition {
 impl IfCondition {
     fn parse_elsif(stream: &mut TokenStream) -> Result<IfCondition, Error> {
         let mut span = stream.expect_one(TokenKind::Elsif)?.span();
-        let condition = Expression::parse(stream)?;
+        let condition = Expression::parse_condition(stream)?;
         span |= condition.span();
         let base = StatementGroup::parse(stream)?;
         span |= base.span();
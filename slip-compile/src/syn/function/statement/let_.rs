@@ -53,6 +53,7 @@ impl Node for Let {
         } else {
             None
         };
+        span |= stream.expect_one(TokenKind::Semicolon)?.span();
         Ok(Let(name, kind, value, span))
     }
 }
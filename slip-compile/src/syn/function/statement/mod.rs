@@ -24,6 +24,11 @@ pub use self::try_::Try;
 pub use self::unless::Unless;
 pub use self::while_::While;
 
+/// Statements are terminated by a `;`, except for the ones that end with a
+/// block (`if`, `unless`, `while`, `for`, and `try`), which need no
+/// terminator.  An expression at the end of a block without a `;` isn't a
+/// statement at all; it is the value of the block; see
+/// [`StatementGroup::tail`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Statement {
     Unless(Box<Unless>),
@@ -37,17 +42,31 @@ pub enum Statement {
     Expression(Expression),
 }
 
+impl Statement {
+    /// Parses a statement that starts with a keyword, e.g. `let` or `if`.
+    /// If the next statement doesn't start with a keyword, it is an
+    /// expression, and nothing is parsed.
+    pub(super) fn parse_keyword(stream: &mut TokenStream) -> Result<Option<Statement>, Error> {
+        let statement = match stream.peek_kind() {
+            Some(TokenKind::Unless) => Statement::Unless(Box::new(Unless::parse(stream)?)),
+            Some(TokenKind::Return) => Statement::Return(Box::new(Return::parse(stream)?)),
+            Some(TokenKind::While) => Statement::While(Box::new(While::parse(stream)?)),
+            Some(TokenKind::For) => Statement::For(Box::new(For::parse(stream)?)),
+            Some(TokenKind::Let) => Statement::Let(Box::new(Let::parse(stream)?)),
+            Some(TokenKind::Try) => Statement::Try(Box::new(Try::parse(stream)?)),
+            Some(TokenKind::If) => Statement::If(If::parse(stream)?),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(statement))
+    }
+}
+
 impl Node for Statement {
     fn parse(stream: &mut TokenStream) -> Result<Statement, Error> {
-        match stream.peek_kind() {
-            Some(TokenKind::Unless) => Ok(Statement::Unless(Box::new(Unless::parse(stream)?))),
-            Some(TokenKind::Return) => Ok(Statement::Return(Box::new(Return::parse(stream)?))),
-            Some(TokenKind::While) => Ok(Statement::While(Box::new(While::parse(stream)?))),
-            Some(TokenKind::For) => Ok(Statement::For(Box::new(For::parse(stream)?))),
-            Some(TokenKind::Let) => Ok(Statement::Let(Box::new(Let::parse(stream)?))),
-            Some(TokenKind::Try) => Ok(Statement::Try(Box::new(Try::parse(stream)?))),
-            Some(TokenKind::If) => Ok(Statement::If(If::parse(stream)?)),
-            _ => {
+        match Statement::parse_keyword(stream)? {
+            Some(statement) => Ok(statement),
+            None => {
                 let expr = Expression::parse(stream)?;
                 stream.expect_one(TokenKind::Semicolon)?;
                 Ok(Statement::Expression(expr))
@@ -26,7 +26,7 @@ impl Unless {
 impl Node for Unless {
     fn parse(stream: &mut TokenStream) -> Result<Unless, Error> {
         let mut span = stream.expect_one(TokenKind::Unless)?.span();
-        let condition = Expression::parse(stream)?;
+        let condition = Expression::parse_condition(stream)?;
         span |= condition.span();
         let body = StatementGroup::parse(stream)?;
         span |= body.span();
@@ -26,7 +26,7 @@ impl While {
 impl Node for While {
     fn parse(stream: &mut TokenStream) -> Result<While, Error> {
         let mut span = stream.expect_one(TokenKind::While)?.span();
-        let condition = Expression::parse(stream)?;
+        let condition = Expression::parse_condition(stream)?;
         span |= condition.span();
         let body = StatementGroup::parse(stream)?;
         span |= body.span();
@@ -1,9 +1,10 @@
+use super::const_::Const;
 use super::enum_::Enum;
 use super::function::Function;
 use super::module::Module;
 use super::struct_::Struct;
 use super::use_::Use;
-use super::{BasicNode, Node};
+use super::{Attribute, BasicNode, Node};
 use crate::diag::Span;
 use crate::error::*;
 use crate::stream::{TokenKind, TokenStream};
@@ -17,6 +18,7 @@ pub enum Item {
     Enum(Box<Enum>),
     Module(Box<Module>),
     Use(Box<Use>),
+    Const(Box<Const>),
 }
 
 impl Item {
@@ -28,6 +30,31 @@ impl Item {
         }
     }
 
+    /// Whether or not the item was marked with `export`.  Uses can't be
+    /// exported.
+    pub fn export(&self) -> bool {
+        match self {
+            Item::Function(func) => func.export(),
+            Item::Struct(struct_) => struct_.export(),
+            Item::Enum(enum_) => enum_.export(),
+            Item::Module(mod_) => mod_.export(),
+            Item::Use(_) => false,
+            Item::Const(const_) => const_.export(),
+        }
+    }
+
+    /// The attributes given before the item.
+    pub fn attributes(&self) -> &[Attribute] {
+        match self {
+            Item::Function(func) => func.attributes(),
+            Item::Struct(struct_) => struct_.attributes(),
+            Item::Enum(enum_) => enum_.attributes(),
+            Item::Module(mod_) => mod_.attributes(),
+            Item::Use(use_) => use_.attributes(),
+            Item::Const(const_) => const_.attributes(),
+        }
+    }
+
     pub fn generics(&self) -> Option<&Roll<Type>> {
         match self {
             Item::Struct(struct_) => struct_.kind().generics().as_ref(),
@@ -41,18 +68,46 @@ impl Item {
 
 impl Node for Item {
     fn parse(stream: &mut TokenStream) -> Result<Item, Error> {
+        // We can't know which item the attributes and `export` belong to
+        // until we've passed them, so we have to hand them off to the item.
+        let attributes = super::attributes(stream)?;
+        let export = super::export(stream)?;
         match stream.peek_kind() {
-            Some(TokenKind::Fn) => Ok(Item::Function(Box::new(Function::parse(stream)?))),
-            Some(TokenKind::Struct) => Ok(Item::Struct(Box::new(Struct::parse(stream)?))),
-            Some(TokenKind::Enum) => Ok(Item::Enum(Box::new(Enum::parse(stream)?))),
-            Some(TokenKind::Module) => Ok(Item::Module(Box::new(Module::parse(stream)?))),
-            Some(TokenKind::Use) => Ok(Item::Use(Box::new(Use::parse(stream)?))),
+            Some(TokenKind::Fn) => Ok(Item::Function(Box::new(Function::parse_export(
+                stream, attributes, export,
+            )?))),
+            Some(TokenKind::Struct) => Ok(Item::Struct(Box::new(Struct::parse_export(
+                stream, attributes, export,
+            )?))),
+            Some(TokenKind::Enum) => Ok(Item::Enum(Box::new(Enum::parse_export(
+                stream, attributes, export,
+            )?))),
+            Some(TokenKind::Module) => Ok(Item::Module(Box::new(Module::parse_export(
+                stream, attributes, export,
+            )?))),
+            Some(TokenKind::Const) => Ok(Item::Const(Box::new(Const::parse_export(
+                stream, attributes, export,
+            )?))),
+            Some(TokenKind::Use) if export.is_none() => Ok(Item::Use(Box::new(
+                Use::parse_attributes(stream, attributes)?,
+            ))),
+            _ if export.is_some() => stream
+                .error_from(&[
+                    TokenKind::Fn,
+                    TokenKind::Struct,
+                    TokenKind::Enum,
+                    TokenKind::Module,
+                    TokenKind::Const,
+                ])
+                .map(|_| unreachable!()),
             _ => stream
                 .error_from(&[
+                    TokenKind::Export,
                     TokenKind::Fn,
                     TokenKind::Struct,
                     TokenKind::Enum,
                     TokenKind::Module,
+                    TokenKind::Const,
                     TokenKind::Use,
                 ])
                 .map(|_| unreachable!()),
@@ -68,6 +123,7 @@ impl BasicNode for Item {
             Item::Enum(enum_) => enum_.span(),
             Item::Module(module) => module.span(),
             Item::Use(use_) => use_.span(),
+            Item::Const(const_) => const_.span(),
         }
     }
 }
@@ -9,6 +9,14 @@ pub struct Type {
     parts: Vec<Token>,
     #[serde(skip_serializing_if = "Option::is_none")]
     generics: Option<Roll<Type>>,
+    /// The bounds on a generic parameter, e.g. `Compare + Hash` in
+    /// `T: Compare + Hash`.  These are only given where the generic is
+    /// declared.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    bounds: Vec<Type>,
+    /// The default of a generic parameter, e.g. `i32` in `T = i32`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<Box<Type>>,
     area: Span,
 }
 
@@ -17,6 +25,8 @@ impl Type {
         Type {
             parts,
             generics,
+            bounds: vec![],
+            default: None,
             area,
         }
     }
@@ -36,6 +46,12 @@ impl Type {
     pub fn span_mut(&mut self) -> &mut Span {
         &mut self.area
     }
+    pub fn bounds(&self) -> &[Type] {
+        &self.bounds[..]
+    }
+    pub fn default(&self) -> Option<&Type> {
+        self.default.as_deref()
+    }
 
     pub fn to_name(&self) -> slip_typal::module::Name {
         self.parts.iter().flat_map(Token::value).collect()
@@ -70,42 +86,43 @@ impl Type {
                 }
             });
 
-        Type {
-            parts,
-            generics,
-            area,
-        }
+        Type::new(parts, generics, area)
     }
 
-    pub fn without_generics(&self) -> Self {
-        Type {
-            parts: self.parts.clone(),
-            generics: None,
-            area: self.area,
-        }
+    /// Parses a type that may be followed by the name of a function on that
+    /// type, e.g. `Int::default`.  Function names are lowercase, and so they
+    /// are lexed as identifiers; they end the type, and cannot be followed
+    /// by generics.
+    pub fn parse_member(stream: &mut TokenStream) -> Result<(Type, Option<Token>), Error> {
+        Type::parse_with(stream, true)
     }
-}
 
-impl Node for Type {
-    fn parse(stream: &mut TokenStream) -> Result<Type, Error> {
-        let start = stream.expect_one(TokenKind::ModuleName)?;
+    fn parse_with(stream: &mut TokenStream, member: bool) -> Result<(Type, Option<Token>), Error> {
+        // Primitive types (e.g. `i32`, `bool`) are lowercase, and so they are
+        // lexed as identifiers.  They are never nested within a module.
+        let start = stream.expect_any(&[TokenKind::ModuleName, TokenKind::Identifier])?;
+        let primitive = start.kind() == TokenKind::Identifier;
         let mut span = start.span();
         let mut contents = vec![start];
+        let allowed: &[TokenKind] = if member {
+            &[TokenKind::ModuleName, TokenKind::Identifier]
+        } else {
+            &[TokenKind::ModuleName]
+        };
 
-        while stream.peek_one(TokenKind::DoubleColon) {
-            span |= stream.expect_one(TokenKind::DoubleColon)?.span();
-            let result = stream.expect_one(TokenKind::ModuleName)?;
-            span |= result.span();
+        while !primitive && stream.peek_one(TokenKind::DoubleColon) {
+            let colon = stream.expect_one(TokenKind::DoubleColon)?;
+            let result = stream.expect_any(allowed)?;
+            if result.kind() == TokenKind::Identifier {
+                let kind = Type::new(contents, None, span);
+                return Ok((kind, Some(result)));
+            }
+            span |= colon.span() | result.span();
             contents.push(result);
         }
 
         let generics = if stream.peek_one(TokenKind::LessThan) {
-            Some(Roll::with_terminate_once(
-                stream,
-                TokenKind::LessThan,
-                TokenKind::Comma,
-                TokenKind::GreaterThan,
-            )?)
+            Some(Type::parse_generics(stream)?)
         } else {
             None
         };
@@ -114,11 +131,66 @@ impl Node for Type {
             span |= v.span();
         }
 
-        Ok(Type {
-            parts: contents,
-            generics,
-            area: span,
-        })
+        Ok((Type::new(contents, generics, span), None))
+    }
+
+    /// Parses a list of generics, e.g. `<K: Hash, V = i32>`.  Each of the
+    /// generics may have bounds, and a default.
+    pub fn parse_generics(stream: &mut TokenStream) -> Result<Roll<Type>, Error> {
+        let roll = Roll::<Generic>::with_terminate_once(
+            stream,
+            TokenKind::LessThan,
+            TokenKind::Comma,
+            TokenKind::GreaterThan,
+        )?;
+        Ok(roll.map(|generic| generic.0))
+    }
+
+    pub fn without_generics(&self) -> Self {
+        Type::new(self.parts.clone(), None, self.area)
+    }
+}
+
+/// A single generic within a list of generics; see
+/// [`Type::parse_generics`].
+struct Generic(Type);
+
+impl Node for Generic {
+    fn parse(stream: &mut TokenStream) -> Result<Generic, Error> {
+        let mut kind = Type::parse(stream)?;
+        if stream.peek_one(TokenKind::Colon) {
+            kind.area |= stream.expect_one(TokenKind::Colon)?.span();
+            loop {
+                let bound = Type::parse(stream)?;
+                kind.area |= bound.span();
+                kind.bounds.push(bound);
+                if !stream.peek_one(TokenKind::Plus) {
+                    break;
+                }
+                kind.area |= stream.expect_one(TokenKind::Plus)?.span();
+            }
+        }
+
+        if stream.peek_one(TokenKind::Equals) {
+            kind.area |= stream.expect_one(TokenKind::Equals)?.span();
+            let default = Type::parse(stream)?;
+            kind.area |= default.span();
+            kind.default = Some(Box::new(default));
+        }
+
+        Ok(Generic(kind))
+    }
+}
+
+impl BasicNode for Generic {
+    fn span(&self) -> Span {
+        self.0.span()
+    }
+}
+
+impl Node for Type {
+    fn parse(stream: &mut TokenStream) -> Result<Type, Error> {
+        Type::parse_with(stream, false).map(|(kind, _)| kind)
     }
 }
 
@@ -2,6 +2,8 @@ use crate::diag::Span;
 use crate::error::*;
 use crate::stream::{Token, TokenKind, TokenStream};
 
+mod attribute;
+mod const_;
 mod enum_;
 pub mod function;
 pub mod item;
@@ -13,6 +15,8 @@ mod struct_;
 mod unit;
 mod use_;
 
+pub use self::attribute::{Attribute, AttributeName};
+pub use self::const_::Const;
 pub use self::enum_::{Enum, EnumVariant};
 pub use self::function::Function;
 pub use self::item::Item;
@@ -22,7 +26,7 @@ pub use self::roll::Roll;
 pub use self::root::Root;
 pub use self::struct_::{Struct, StructElement};
 pub use self::unit::Unit;
-pub use self::use_::{Use, UseTrail};
+pub use self::use_::{Import, Use, UseTree};
 
 pub trait BasicNode: Sized {
     fn span(&self) -> Span;
@@ -32,6 +36,25 @@ pub trait Node: BasicNode {
     fn parse(stream: &mut TokenStream) -> Result<Self, Error>;
 }
 
+/// Parses the `export` keyword in front of an item or a field, if it is
+/// there, returning its span.
+fn export(stream: &mut TokenStream) -> Result<Option<Span>, Error> {
+    if stream.peek_one(TokenKind::Export) {
+        Ok(Some(stream.expect_one(TokenKind::Export)?.span()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Parses the attributes in front of an item, if there are any.
+fn attributes(stream: &mut TokenStream) -> Result<Vec<Attribute>, Error> {
+    let mut attributes = vec![];
+    while stream.peek_one(TokenKind::Pound) {
+        attributes.push(Attribute::parse(stream)?);
+    }
+    Ok(attributes)
+}
+
 pub fn of(source: &str) -> Result<Root, Error> {
     let set = crate::diag::DiagnosticSync::default();
     let file = set.push("(implicit)", Some(source));
@@ -62,4 +85,189 @@ module Some::Program {
     fn test_basic_parse() {
         let _ = of(BASIC_SOURCE).unwrap();
     }
+
+    const CONDITION_SOURCE: &str = r#"
+fn some_func(a: Slip::Array, m: Slip::Bool) {
+    if a[0] { }
+    while m { }
+}"#;
+
+    #[test]
+    fn test_condition_parse() {
+        use self::function::expression::{Atom, Expression};
+        use self::function::statement::Statement;
+
+        let root = of(CONDITION_SOURCE).unwrap();
+        let body = match &root.items()[0] {
+            Item::Function(func) => func.body().as_ref().unwrap(),
+            _ => panic!("expected a function"),
+        };
+        assert_eq!(body.len(), 2);
+
+        match &body.statements()[0] {
+            Statement::If(if_) => {
+                assert_eq!(if_.conditions().len(), 1);
+                assert!(if_.conditions()[0].body().is_empty());
+                match if_.conditions()[0].condition() {
+                    Some(Expression::Index(index)) => {
+                        assert!(matches!(index.base(), Expression::Atom(Atom::Ident(_))));
+                        assert_eq!(index.arguments().len(), 1);
+                    }
+                    other => panic!("expected an index, got {:?}", other),
+                }
+            }
+            other => panic!("expected an if, got {:?}", other),
+        }
+
+        match &body.statements()[1] {
+            Statement::While(while_) => {
+                assert!(while_.body().is_empty());
+                assert!(matches!(
+                    while_.condition(),
+                    Expression::Atom(Atom::Ident(_))
+                ));
+            }
+            other => panic!("expected a while, got {:?}", other),
+        }
+    }
+
+    const TERMINATOR_SOURCE: &str = r#"
+fn some_func(a: i32): i32 {
+    let b = a;
+    let c: i32 = b + 1;
+    if c { b; };
+    while a { }
+    c
+}
+
+fn other_func(a: i32) {
+    return;
+}"#;
+
+    #[test]
+    fn test_terminator_parse() {
+        use self::function::expression::Expression;
+        use self::function::statement::Statement;
+
+        let root = of(TERMINATOR_SOURCE).unwrap();
+        let body = |index: usize| match &root.items()[index] {
+            Item::Function(func) => func.body().as_ref().unwrap(),
+            _ => panic!("expected a function"),
+        };
+
+        let statements = body(0).statements();
+        assert_eq!(statements.len(), 4);
+        assert!(matches!(statements[0], Statement::Let(_)));
+        assert!(matches!(statements[1], Statement::Let(_)));
+        assert!(matches!(statements[2], Statement::If(_)));
+        assert!(matches!(statements[3], Statement::While(_)));
+        assert!(matches!(body(0).tail(), Some(Expression::Atom(_))));
+
+        assert!(matches!(body(1).statements(), [Statement::Return(_)]));
+        assert!(body(1).tail().is_none());
+
+        assert!(of("fn f() { let a = 1 a }").is_err());
+        assert!(of("fn f() { a b }").is_err());
+    }
+
+    const GENERIC_SOURCE: &str = r#"
+struct Map<K: Hash + Equal, V = i32> {
+    key: K,
+    value: V
+}
+
+fn sort<T: Compare>(list: List<T>) { }"#;
+
+    #[test]
+    fn test_generic_parse() {
+        let root = of(GENERIC_SOURCE).unwrap();
+        let generics = root.items()[0].generics().unwrap();
+        let key = &generics.value()[0];
+        assert_eq!(key.to_string(), "K");
+        let bounds = key.bounds().iter().map(Type::to_string).collect::<Vec<_>>();
+        assert_eq!(bounds, ["Hash", "Equal"]);
+        assert!(key.default().is_none());
+        let value = &generics.value()[1];
+        assert!(value.bounds().is_empty());
+        assert_eq!(value.default().unwrap().to_string(), "i32");
+
+        let generics = root.items()[1].generics().unwrap();
+        assert_eq!(generics.value()[0].bounds()[0].to_string(), "Compare");
+    }
+
+    const USE_SOURCE: &str = r#"
+use A::B;
+use A::{B::{C, D as E}, F::*, self};
+use G::H as I;
+use J::*;"#;
+
+    #[test]
+    fn test_use_parse() {
+        let root = of(USE_SOURCE).unwrap();
+        let imports = root
+            .items()
+            .iter()
+            .flat_map(|item| match item {
+                Item::Use(use_) => use_.imports(),
+                _ => panic!("expected a use"),
+            })
+            .map(|import| (import.path().join("::"), import.name()))
+            .collect::<Vec<_>>();
+        let expected = [
+            ("A::B", Some("B")),
+            ("A::B::C", Some("C")),
+            ("A::B::D", Some("E")),
+            ("A::F", None),
+            ("A", Some("A")),
+            ("G::H", Some("I")),
+            ("J", None),
+        ];
+        let expected = expected
+            .iter()
+            .map(|(path, name)| (path.to_string(), *name))
+            .collect::<Vec<_>>();
+        assert_eq!(imports, expected);
+
+        // `self` only makes sense within a group.
+        assert!(of("use self;").is_err());
+    }
+
+    const ATTRIBUTE_SOURCE: &str = r#"
+#[allow(unused-local, type-trace.possible)]
+#[deny(unused-import,)]
+export module A {
+    #[warn(type-trace)]
+    use B;
+}"#;
+
+    #[test]
+    fn test_attribute_parse() {
+        let root = of(ATTRIBUTE_SOURCE).unwrap();
+        let module = &root.items()[0];
+        assert!(module.export());
+        let attributes = module
+            .attributes()
+            .iter()
+            .map(|attribute| {
+                let arguments = attribute.arguments().iter().map(ToString::to_string);
+                let arguments = arguments.collect::<Vec<_>>().join(", ");
+                format!("{}({})", attribute.name().value().unwrap(), arguments)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            attributes,
+            [
+                "allow(unused-local, type-trace.possible)",
+                "deny(unused-import)"
+            ]
+        );
+
+        let use_ = match module {
+            Item::Module(module) => &module.items()[0],
+            _ => panic!("expected a module"),
+        };
+        assert_eq!(use_.attributes()[0].arguments()[0].parts().len(), 1);
+
+        assert!(of("#[allow(unused-local)]").is_err());
+    }
 }
@@ -1,16 +1,26 @@
-use super::{BasicNode, Item, Node, Type};
+use super::{Attribute, BasicNode, Item, Node, Type};
 use crate::diag::Span;
 use crate::error::*;
 use crate::stream::{TokenKind, TokenStream};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Module {
+    attributes: Vec<Attribute>,
+    export: bool,
     name: Type,
     items: Vec<Item>,
     area: Span,
 }
 
 impl Module {
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn export(&self) -> bool {
+        self.export
+    }
+
     pub fn kind(&self) -> &Type {
         &self.name
     }
@@ -30,7 +40,22 @@ impl Module {
 
 impl Node for Module {
     fn parse(stream: &mut TokenStream) -> Result<Module, Error> {
+        let attributes = super::attributes(stream)?;
+        let export = super::export(stream)?;
+        Module::parse_export(stream, attributes, export)
+    }
+}
+
+impl Module {
+    pub(super) fn parse_export(
+        stream: &mut TokenStream,
+        attributes: Vec<Attribute>,
+        export: Option<Span>,
+    ) -> Result<Module, Error> {
         let mut span = stream.expect_one(TokenKind::Module)?.span();
+        if let Some(s) = export {
+            span |= s;
+        }
         let kind = Type::parse(stream)?;
         span |= kind.span();
         span |= stream.expect_one(TokenKind::LeftBrace)?.span();
@@ -45,6 +70,8 @@ impl Node for Module {
 
         span |= stream.expect_one(TokenKind::RightBrace)?.span();
         Ok(Module {
+            attributes,
+            export: export.is_some(),
             name: kind,
             items: contents,
             area: span,
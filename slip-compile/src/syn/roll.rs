@@ -130,6 +130,14 @@ impl<T: Node> Roll<T> {
     pub fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &mut T> + 'a {
         self.contents.iter_mut()
     }
+
+    /// Converts every element of the roll, keeping the area of the roll.
+    pub fn map<U: Node, F: FnMut(T) -> U>(self, func: F) -> Roll<U> {
+        Roll {
+            contents: self.contents.into_iter().map(func).collect(),
+            area: self.area,
+        }
+    }
 }
 
 impl<T: Node + Clone> Roll<T> {
@@ -1,16 +1,25 @@
-use super::{BasicNode, Node, Roll, Type};
+use super::{Attribute, BasicNode, Node, Roll, Type};
 use crate::diag::Span;
 use crate::error::*;
 use crate::stream::{Token, TokenKind, TokenStream};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Struct {
+    attributes: Vec<Attribute>,
+    export: bool,
     name: Type,
     elements: Roll<StructElement>,
     area: Span,
 }
 
 impl Struct {
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn export(&self) -> bool {
+        self.export
+    }
     pub fn kind(&self) -> &Type {
         &self.name
     }
@@ -21,7 +30,22 @@ impl Struct {
 
 impl Node for Struct {
     fn parse(stream: &mut TokenStream) -> Result<Struct, Error> {
+        let attributes = super::attributes(stream)?;
+        let export = super::export(stream)?;
+        Struct::parse_export(stream, attributes, export)
+    }
+}
+
+impl Struct {
+    pub(super) fn parse_export(
+        stream: &mut TokenStream,
+        attributes: Vec<Attribute>,
+        export: Option<Span>,
+    ) -> Result<Struct, Error> {
         let mut span = stream.expect_one(TokenKind::Struct)?.span();
+        if let Some(s) = export {
+            span |= s;
+        }
         let kind = Type::parse(stream)?;
         span |= kind.span();
         let contents = Roll::with_terminate_trail_once(
@@ -33,6 +57,8 @@ impl Node for Struct {
         span |= contents.span();
 
         Ok(Struct {
+            attributes,
+            export: export.is_some(),
             name: kind,
             elements: contents,
             area: span,
@@ -48,12 +74,16 @@ impl BasicNode for Struct {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StructElement {
+    export: bool,
     name: Token,
     kind: Type,
     area: Span,
 }
 
 impl StructElement {
+    pub fn export(&self) -> bool {
+        self.export
+    }
     pub fn value(&self) -> &Token {
         &self.name
     }
@@ -64,12 +94,17 @@ impl StructElement {
 
 impl Node for StructElement {
     fn parse(stream: &mut TokenStream) -> Result<StructElement, Error> {
+        let export = super::export(stream)?;
         let name = stream.expect_one(TokenKind::Identifier)?;
         let colon = stream.expect_one(TokenKind::Colon)?;
         let kind = Type::parse(stream)?;
-        let span = name.span() | colon.span() | kind.span();
+        let mut span = name.span() | colon.span() | kind.span();
+        if let Some(s) = export {
+            span |= s;
+        }
 
         Ok(StructElement {
+            export: export.is_some(),
             name,
             kind,
             area: span,
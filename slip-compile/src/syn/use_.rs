@@ -1,21 +1,32 @@
-use super::{BasicNode, Node, Roll, Type};
+use super::{Attribute, BasicNode, Node, Roll, Type};
 use crate::diag::Span;
 use crate::error::*;
 use crate::stream::{Token, TokenKind, TokenStream};
 
+/// An import of one or more items into the current scope.  The items to
+/// import are given as a tree; see [`UseTree`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Use {
-    prefix: Type,
-    trails: Roll<UseTrail>,
+    attributes: Vec<Attribute>,
+    tree: UseTree,
     area: Span,
 }
 
 impl Use {
-    pub fn prefix(&self) -> &Type {
-        &self.prefix
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
     }
-    pub fn trails(&self) -> &[UseTrail] {
-        self.trails.value()
+
+    pub fn tree(&self) -> &UseTree {
+        &self.tree
+    }
+
+    /// Every import in the tree, flattened out; e.g. `use A::{B, C as D};`
+    /// imports `A::B` as `B`, and `A::C` as `D`.
+    pub fn imports(&self) -> Vec<Import<'_>> {
+        let mut result = vec![];
+        self.tree.flatten(&[], &mut result);
+        result
     }
 }
 
@@ -46,41 +57,34 @@ where
     Ok((content, value, span))
 }
 
-fn prefix_basic(stream: &mut TokenStream) -> Result<(Vec<Token>, Span), Error> {
-    prefix(stream, |stream, _| {
-        stream
-            .error_from(&[TokenKind::ModuleName])
-            .map(|_| unimplemented!())
-    })
-    .map(|(a, _, b)| (a, b))
-}
-
 impl Node for Use {
     fn parse(stream: &mut TokenStream) -> Result<Use, Error> {
-        let span = stream.expect_one(TokenKind::Use)?.span();
-
-        let (prefix, content, inspan) = prefix(stream, |stream, span| {
-            let roll = Roll::with_terminate_trail_once(
-                stream,
-                TokenKind::LeftBrace,
-                TokenKind::Comma,
-                TokenKind::RightBrace,
-            )?;
-            *span |= roll.span();
-            Ok(roll)
-        })?;
+        let attributes = super::attributes(stream)?;
+        Use::parse_attributes(stream, attributes)
+    }
+}
 
-        let prespan = prefix
-            .iter()
-            .fold(Span::identity(), |acc, el| acc | el.span());
+impl Use {
+    /// Parses the use, with the attributes in front of it.  This is used by
+    /// [`crate::syn::Item`], which has to parse the attributes before it
+    /// knows which item follows them.
+    pub(super) fn parse_attributes(
+        stream: &mut TokenStream,
+        attributes: Vec<Attribute>,
+    ) -> Result<Use, Error> {
+        let span = stream.expect_one(TokenKind::Use)?.span();
+        // `self` only makes sense within a group, as it refers to the prefix
+        // of the group.
+        if stream.peek_one(TokenKind::This) {
+            stream.error_from(&[TokenKind::ModuleName, TokenKind::Star, TokenKind::LeftBrace])?;
+        }
 
-        let kind = Type::new(prefix, None, prespan);
+        let tree = UseTree::parse(stream)?;
         let tok = stream.expect_one(TokenKind::Semicolon)?.span();
-        let content = content.unwrap_or_else(Roll::empty);
         Ok(Use {
-            prefix: kind,
-            trails: content,
-            area: span | inspan | tok,
+            attributes,
+            area: span | tree.span() | tok,
+            tree,
         })
     }
 }
@@ -91,82 +95,205 @@ impl BasicNode for Use {
     }
 }
 
+/// The items imported by a `use`.  The tree can nest to any depth, e.g.
+/// `use A::{B::{C, D as E}, F::*, self};`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
 #[serde(rename_all = "kebab-case")]
-pub enum UseTrail {
+pub enum UseTree {
+    /// A single item, e.g. `A::B`; it is imported under its own name.
     Static(Type, Span),
-    Rename(Type, Type, Span),
-    Star(Span),
+    /// A single item imported under another name, e.g. `A::B as C`.
+    Rename(Type, Token, Span),
+    /// Everything within a module, e.g. `A::*`.  The module may be empty
+    /// within a group, e.g. the `*` in `A::{*}`.
+    Star(Type, Span),
+    /// A group of trees, all under the same module, e.g. `A::{B, C}`.  The
+    /// module may be empty within a group.
+    Group(Type, Roll<UseTree>, Span),
+    /// The module that a group is under, e.g. the `self` in `A::{self, B}`,
+    /// which imports `A` itself; it can be renamed, e.g. `self as C`.
+    This(Option<Token>, Span),
 }
 
-impl UseTrail {
-    pub fn base(&self) -> Option<&Type> {
-        match self {
-            UseTrail::Static(v, _) => Some(&v),
-            UseTrail::Rename(v, _, _) => Some(&v),
-            UseTrail::Star(_) => None,
+/// What can follow the `::` at the end of a path in a use tree.
+enum Rest {
+    Star,
+    Group(Roll<UseTree>),
+}
+
+impl Rest {
+    fn parse(stream: &mut TokenStream, span: &mut Span) -> Result<Rest, Error> {
+        if stream.peek_one(TokenKind::Star) {
+            *span |= stream.expect_one(TokenKind::Star)?.span();
+            Ok(Rest::Star)
+        } else if stream.peek_one(TokenKind::LeftBrace) {
+            let roll = Roll::with_terminate_trail_once(
+                stream,
+                TokenKind::LeftBrace,
+                TokenKind::Comma,
+                TokenKind::RightBrace,
+            )?;
+            *span |= roll.span();
+            Ok(Rest::Group(roll))
+        } else {
+            stream.error_from(&[TokenKind::ModuleName, TokenKind::Star, TokenKind::LeftBrace])?
         }
     }
 
-    pub fn name(&self) -> Option<&[Token]> {
+    fn with(self, path: Type, span: Span) -> UseTree {
         match self {
-            UseTrail::Static(v, _) => Some(&v.parts()[(v.parts().len() - 2)..]),
-            UseTrail::Rename(_, v, _) => Some(v.parts()),
-            UseTrail::Star(_) => None,
+            Rest::Star => UseTree::Star(path, span),
+            Rest::Group(roll) => UseTree::Group(path, roll, span),
         }
     }
+}
 
-    pub fn applies(&self, ty: &super::Type) -> bool {
-        match self.name() {
-            None => true,
-            Some(part) if part == ty.parts() => true,
-            _ => false,
-        }
+fn alias(stream: &mut TokenStream, span: &mut Span) -> Result<Option<Token>, Error> {
+    if stream.peek_one(TokenKind::As) {
+        *span |= stream.expect_one(TokenKind::As)?.span();
+        let alias = stream.expect_one(TokenKind::ModuleName)?;
+        *span |= alias.span();
+        Ok(Some(alias))
+    } else {
+        Ok(None)
     }
+}
+
+impl UseTree {
+    fn flatten<'s>(&'s self, prefix: &[&'s str], result: &mut Vec<Import<'s>>) {
+        let join = |path: &'s Type| {
+            let parts = path.parts().iter().flat_map(Token::value);
+            prefix.iter().cloned().chain(parts).collect::<Vec<_>>()
+        };
 
-    pub fn combine<'s>(&'s self, prefix: &'s Type, current: &'s Type) -> Vec<&'s Type> {
         match self {
-            UseTrail::Static(_, _) | UseTrail::Star(_) => vec![prefix, current],
-            UseTrail::Rename(from, _, _) => vec![prefix, from],
+            UseTree::Static(path, span) => {
+                let path = join(path);
+                let name = path.last().cloned();
+                result.push(Import::new(path, name, *span));
+            }
+            UseTree::Rename(path, alias, span) => {
+                result.push(Import::new(join(path), alias.value(), *span));
+            }
+            UseTree::Star(path, span) => result.push(Import::new(join(path), None, *span)),
+            UseTree::Group(path, trees, _) => {
+                let path = join(path);
+                for tree in trees.iter() {
+                    tree.flatten(&path, result);
+                }
+            }
+            // `self` can't import anything if the group isn't under a
+            // module, e.g. `use {self};`.
+            UseTree::This(alias, span) if !prefix.is_empty() => {
+                let name = alias.as_ref().and_then(Token::value);
+                let name = name.or_else(|| prefix.last().cloned());
+                result.push(Import::new(prefix.to_vec(), name, *span));
+            }
+            UseTree::This(_, _) => {}
         }
     }
 }
 
-impl Node for UseTrail {
-    fn parse(stream: &mut TokenStream) -> Result<UseTrail, Error> {
+impl Node for UseTree {
+    fn parse(stream: &mut TokenStream) -> Result<UseTree, Error> {
         match stream.peek_kind() {
             Some(TokenKind::ModuleName) => {
-                let (val, mut span) = prefix_basic(stream)?;
-                let val = Type::new(val, None, span);
-
-                if stream.peek_one(TokenKind::As) {
-                    span |= stream.expect_one(TokenKind::As)?.span();
-                    let (alias, alspan) = prefix_basic(stream)?;
-                    let alias = Type::new(alias, None, alspan);
-                    span |= alspan;
-                    Ok(UseTrail::Rename(val, alias, span))
-                } else {
-                    Ok(UseTrail::Static(val, span))
+                let (parts, rest, mut span) = prefix(stream, Rest::parse)?;
+                let prespan = parts
+                    .iter()
+                    .fold(Span::identity(), |acc, el| acc | el.span());
+                let path = Type::new(parts, None, prespan);
+
+                match rest {
+                    Some(rest) => Ok(rest.with(path, span)),
+                    None => match alias(stream, &mut span)? {
+                        Some(alias) => Ok(UseTree::Rename(path, alias, span)),
+                        None => Ok(UseTree::Static(path, span)),
+                    },
                 }
             }
-            Some(TokenKind::Star) => {
-                let span = stream.expect_one(TokenKind::Star)?.span();
-                Ok(UseTrail::Star(span))
+            Some(TokenKind::Star) | Some(TokenKind::LeftBrace) => {
+                let mut span = stream.span();
+                let path = Type::new(vec![], None, span);
+                let rest = Rest::parse(stream, &mut span)?;
+                Ok(rest.with(path, span))
+            }
+            Some(TokenKind::This) => {
+                let mut span = stream.expect_one(TokenKind::This)?.span();
+                let alias = alias(stream, &mut span)?;
+                Ok(UseTree::This(alias, span))
             }
             _ => stream
-                .error_from(&[TokenKind::ModuleName, TokenKind::Star])
+                .error_from(&[
+                    TokenKind::ModuleName,
+                    TokenKind::Star,
+                    TokenKind::LeftBrace,
+                    TokenKind::This,
+                ])
                 .map(|_| unreachable!()),
         }
     }
 }
 
-impl BasicNode for UseTrail {
+impl BasicNode for UseTree {
     fn span(&self) -> Span {
         match self {
-            UseTrail::Rename(_, _, span) => *span,
-            UseTrail::Static(_, span) => *span,
-            UseTrail::Star(span) => *span,
+            UseTree::Static(_, span) => *span,
+            UseTree::Rename(_, _, span) => *span,
+            UseTree::Star(_, span) => *span,
+            UseTree::Group(_, _, span) => *span,
+            UseTree::This(_, span) => *span,
+        }
+    }
+}
+
+/// A single import, flattened out of a use tree; see [`Use::imports`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import<'s> {
+    path: Vec<&'s str>,
+    name: Option<&'s str>,
+    area: Span,
+}
+
+impl<'s> Import<'s> {
+    fn new(path: Vec<&'s str>, name: Option<&'s str>, area: Span) -> Import<'s> {
+        Import { path, name, area }
+    }
+
+    /// The full path of what is imported.  For a glob import, this is the
+    /// module that everything is imported from.
+    pub fn path(&self) -> &[&'s str] {
+        &self.path[..]
+    }
+
+    /// The name that the import is known by within the scope; e.g. for
+    /// `use A::B as C;`, this is `C`.  Glob imports have no name.
+    pub fn name(&self) -> Option<&'s str> {
+        self.name
+    }
+
+    pub fn is_glob(&self) -> bool {
+        self.name.is_none()
+    }
+
+    pub fn span(&self) -> Span {
+        self.area
+    }
+
+    /// The full path that the given type would refer to through this
+    /// import, if it can refer to anything through it.  A type refers to
+    /// something through a named import if it starts with the name, e.g.
+    /// with `use A::B;`, `B::C` refers to `A::B::C`; through a glob import,
+    /// any type can refer to something within the module.
+    pub fn resolve(&self, kind: &'s Type) -> Option<Vec<&'s str>> {
+        let mut parts = kind.parts().iter().flat_map(Token::value);
+        if let Some(name) = self.name {
+            if parts.next()? != name {
+                return None;
+            }
         }
+
+        Some(self.path.iter().cloned().chain(parts).collect())
     }
 }
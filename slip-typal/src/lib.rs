@@ -10,6 +10,7 @@ extern crate serde;
 extern crate failure;
 
 pub mod module;
+pub mod mono;
 mod runtime;
 pub mod spec;
 pub mod version;
@@ -0,0 +1,14 @@
+use super::{Constant, Name, TypeReference};
+
+/// A named value, computed while the module is built.  References to the
+/// value are replaced with the value itself, so constants don't exist at
+/// runtime; they're kept so that they can be referenced from other modules.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Const {
+    pub name: Name,
+    pub kind: TypeReference,
+    pub value: Constant,
+    /// Whether or not the constant is a part of the public interface of the
+    /// module.
+    pub export: bool,
+}
@@ -1,14 +1,40 @@
-use super::{Name, Operation, TypeId};
+use super::{FunctionId, Name, Operation, TypeReference};
 use std::collections::BTreeMap;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
 pub struct BlockId(u64);
 
+impl BlockId {
+    pub fn next(&self) -> BlockId {
+        BlockId(self.0 + 1)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Function {
     pub name: Name,
     pub generics: Vec<Name>,
-    pub parameters: Vec<TypeId>,
-    pub retval: Option<TypeId>,
+    pub parameters: Vec<TypeReference>,
+    pub retval: Option<TypeReference>,
+    /// The types of every local in the function, indexed by the local.  The
+    /// parameters are always the first locals.
+    pub locals: Vec<TypeReference>,
     pub blocks: BTreeMap<BlockId, Vec<Operation>>,
+    /// Whether or not the function is a part of the public interface of the
+    /// module.
+    pub export: bool,
+    /// Where the function was imported from, if it's a stub of a function
+    /// that's compiled into a dependency, rather than one of our own.
+    pub origin: Option<Origin>,
+}
+
+/// The function in a dependency that an imported function stands in for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Origin {
+    /// The name of the requirement that the function is defined in.
+    pub requirement: String,
+    /// The id of the function within that requirement.
+    pub id: FunctionId,
 }
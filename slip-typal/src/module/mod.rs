@@ -1,14 +1,17 @@
+mod const_;
 mod funcs;
 mod name;
 mod operation;
 mod type_;
 
+pub use self::const_::Const;
 pub use self::funcs::*;
 pub use self::name::Name;
-pub use self::operation::Operation;
+pub use self::operation::{Builtin, Constant, Local, Operation};
 pub use self::type_::*;
 use crate::version::Version;
 use std::collections::BTreeMap;
+use std::io::{Read, Write};
 
 #[derive(
     Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
@@ -16,7 +19,7 @@ use std::collections::BTreeMap;
 pub struct TypeId(u64);
 
 impl TypeId {
-    fn next(&self) -> TypeId {
+    pub(crate) fn next(&self) -> TypeId {
         TypeId(self.0 + 1)
     }
 }
@@ -26,6 +29,23 @@ impl TypeId {
 )]
 pub struct FunctionId(u64);
 
+impl FunctionId {
+    pub(crate) fn next(&self) -> FunctionId {
+        FunctionId(self.0 + 1)
+    }
+}
+
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct ConstId(u64);
+
+impl ConstId {
+    pub(crate) fn next(&self) -> ConstId {
+        ConstId(self.0 + 1)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TypeReference {
     #[serde(rename = "abs")]
@@ -36,6 +56,45 @@ pub enum TypeReference {
     Mix(TypeId, Vec<TypeReference>),
 }
 
+impl TypeReference {
+    /// Replaces the generic references in this type with the given
+    /// generics, e.g. the type of a field in a generic struct.  Generics
+    /// without a replacement are left as they are.
+    pub fn substitute(&self, generics: &[TypeReference]) -> TypeReference {
+        match self {
+            TypeReference::Generic(i) => generics
+                .get(*i as usize)
+                .cloned()
+                .unwrap_or_else(|| self.clone()),
+            TypeReference::Mix(id, inner) => {
+                TypeReference::Mix(*id, inner.iter().map(|k| k.substitute(generics)).collect())
+            }
+            TypeReference::Absolute(_) => self.clone(),
+        }
+    }
+
+    /// Infers the generics used in this type from the given type, which
+    /// this type is matched against; the inverse of
+    /// [`TypeReference::substitute`].  Generics that have already been
+    /// inferred are kept, and any part of the types that doesn't match is
+    /// skipped.
+    pub fn infer(&self, from: &TypeReference, generics: &mut [Option<TypeReference>]) {
+        match (self, from) {
+            (TypeReference::Generic(i), _) => {
+                if let Some(slot @ None) = generics.get_mut(*i as usize) {
+                    *slot = Some(from.clone());
+                }
+            }
+            (TypeReference::Mix(a, inner), TypeReference::Mix(b, other)) if a == b => {
+                for (kind, from) in inner.iter().zip(other) {
+                    kind.infer(from, generics);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Module {
     pub name: String,
@@ -43,9 +102,43 @@ pub struct Module {
     pub requirements: Vec<Requirement>,
     pub types: BTreeMap<TypeId, Type>,
     pub funcs: BTreeMap<FunctionId, Function>,
+    pub consts: BTreeMap<ConstId, Const>,
 }
 
+/// The bytes that every written module starts with.
+const MAGIC: [u8; 5] = *b"typal";
+
+/// The version of the layout modules are written in, written after the
+/// magic.  Bincode has no way to skip or default missing fields, so this
+/// has to be bumped whenever the layout of a module changes.
+pub const FORMAT: u32 = 3;
+
 impl Module {
+    /// Reads a module that was written with [`Module::write`].  Modules
+    /// written in any other format, including those from before the format
+    /// was versioned, are an error.
+    pub fn read(mut reader: impl Read) -> Result<Module, bincode::Error> {
+        let mut magic = [0; 5];
+        reader.read_exact(&mut magic)?;
+        if magic == MAGIC && bincode::deserialize_from::<_, u32>(&mut reader)? == FORMAT {
+            return bincode::deserialize_from(reader);
+        }
+
+        let message = format!(
+            "module isn't in typal format {}; it needs to be compiled again",
+            FORMAT
+        );
+        Err(Box::new(bincode::ErrorKind::Custom(message)))
+    }
+
+    /// Writes the module out, so that it can be loaded again - e.g. as a
+    /// dependency of another module - without being compiled again.
+    pub fn write(&self, mut writer: impl Write) -> Result<(), bincode::Error> {
+        writer.write_all(&MAGIC)?;
+        bincode::serialize_into(&mut writer, &FORMAT)?;
+        bincode::serialize_into(writer, self)
+    }
+
     pub fn next_type_id(&self) -> TypeId {
         self.types
             .range(..)
@@ -53,6 +146,41 @@ impl Module {
             .map(|(i, _)| i.next())
             .unwrap_or_default()
     }
+
+    /// The types that are exported from the module.  These, along with the
+    /// exported functions, are the public interface of the module when it is
+    /// loaded as a plugin.
+    pub fn exported_types(&self) -> impl Iterator<Item = (&TypeId, &Type)> {
+        self.types.iter().filter(|(_, t)| t.export)
+    }
+
+    /// The functions that are exported from the module.  See
+    /// [`Module::exported_types`].
+    pub fn exported_funcs(&self) -> impl Iterator<Item = (&FunctionId, &Function)> {
+        self.funcs.iter().filter(|(_, f)| f.export)
+    }
+
+    pub fn next_function_id(&self) -> FunctionId {
+        self.funcs
+            .range(..)
+            .next_back()
+            .map(|(i, _)| i.next())
+            .unwrap_or_default()
+    }
+
+    /// The constants that are exported from the module.  See
+    /// [`Module::exported_types`].
+    pub fn exported_consts(&self) -> impl Iterator<Item = (&ConstId, &Const)> {
+        self.consts.iter().filter(|(_, c)| c.export)
+    }
+
+    pub fn next_const_id(&self) -> ConstId {
+        self.consts
+            .range(..)
+            .next_back()
+            .map(|(i, _)| i.next())
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
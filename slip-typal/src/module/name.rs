@@ -1,8 +1,22 @@
+use std::fmt;
 use std::iter::FromIterator;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Name(Vec<String>);
 
+impl Name {
+    /// Appends the given suffix to the last part of the name; e.g. the
+    /// generic arguments of an instance of a generic type.
+    pub fn suffixed(&self, suffix: &str) -> Name {
+        let mut parts = self.0.clone();
+        match parts.last_mut() {
+            Some(last) => last.push_str(suffix),
+            None => parts.push(suffix.to_string()),
+        }
+        Name(parts)
+    }
+}
+
 impl From<String> for Name {
     fn from(v: String) -> Self {
         Name(vec![v])
@@ -52,3 +66,9 @@ impl<'a> Extend<&'a str> for Name {
         self.0.extend(iter.into_iter().map(str::to_string))
     }
 }
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.join("::").fmt(f)
+    }
+}
@@ -1,2 +1,114 @@
+use super::{BlockId, FunctionId};
+
+/// A local slot within a function.  The parameters of the function occupy
+/// the first slots, in order; every other slot is introduced by the function
+/// body, either as a named binding or as a temporary.
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct Local(u64);
+
+impl Local {
+    pub(crate) fn from_index(index: usize) -> Local {
+        Local(index as u64)
+    }
+
+    pub fn next(&self) -> Local {
+        Local(self.0 + 1)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum Operation {}
+#[serde(rename_all = "kebab-case")]
+pub enum Operation {
+    /// Loads a constant into the given local.
+    Constant(Local, Constant),
+    /// Copies the value of the second local into the first.
+    Copy(Local, Local),
+    /// Performs a builtin operation on primitive values, storing the result
+    /// in the first local.
+    Builtin(Local, Builtin, Vec<Local>),
+    /// Calls the given function with the given arguments, storing the return
+    /// value in the first local.
+    Call(Local, FunctionId, Vec<Local>),
+    /// Reads the named field of the second local into the first.
+    Field(Local, Local, String),
+    /// Writes the last local into the named field of the first.
+    SetField(Local, String, Local),
+    /// Builds a struct of the type of the given local, with the given value
+    /// for each of its fields.
+    Construct(Local, Vec<(String, Local)>),
+    /// Builds the named variant of the enum type of the given local, with
+    /// the given payload.
+    Variant(Local, String, Vec<Local>),
+    /// Jumps to the first block if the local is true, otherwise the second.
+    Branch(Local, BlockId, BlockId),
+    /// Unconditionally jumps to the given block.
+    Jump(BlockId),
+    /// Returns from the function, with the value, if any.
+    Return(Option<Local>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Constant {
+    Integer(u64),
+    Float(f64),
+    String(String),
+    Bool(bool),
+}
+
+/// The operations that are built into the primitive types.  Any operator
+/// applied to a non-primitive type is instead resolved to a function call.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Builtin {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    ShiftLeft,
+    ShiftRight,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseNot,
+    LogicalAnd,
+    LogicalOr,
+    LogicalNot,
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanEqual,
+    GreaterThan,
+    GreaterThanEqual,
+    Compare,
+    Negate,
+    Increment,
+    Decrement,
+    Index,
+}
+
+impl Builtin {
+    /// Whether or not the result of the operation is a boolean, rather than
+    /// the type of the operands.
+    pub fn is_predicate(&self) -> bool {
+        matches!(
+            self,
+            Builtin::LogicalAnd
+                | Builtin::LogicalOr
+                | Builtin::LogicalNot
+                | Builtin::Equal
+                | Builtin::NotEqual
+                | Builtin::LessThan
+                | Builtin::LessThanEqual
+                | Builtin::GreaterThan
+                | Builtin::GreaterThanEqual
+        )
+    }
+}
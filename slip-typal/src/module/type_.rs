@@ -1,31 +1,59 @@
 use super::{Name, TypeReference};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Type {
     pub name: Name,
     pub generics: Vec<Name>,
     pub definition: TypeDefinition,
+    /// Whether or not the type is a part of the public interface of the
+    /// module.
+    pub export: bool,
 }
 
 impl Type {}
 
+impl TypeDefinition {
+    /// Whether or not the type is built in.  Operators on primitive types
+    /// are performed directly, instead of through a function.
+    pub fn is_primitive(&self) -> bool {
+        matches!(
+            self,
+            TypeDefinition::Primitive(_)
+                | TypeDefinition::PrimitiveFloat(_)
+                | TypeDefinition::PrimitiveSize
+                | TypeDefinition::PrimitivePtr
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum TypeDefinition {
-    Struct(HashMap<String, TypeReference>),
+    Struct(BTreeMap<String, Field>),
     Enum(Enum),
     Alias(TypeReference),
     Primitive(u64),
+    PrimitiveFloat(u64),
     PrimitiveSize,
     PrimitivePtr,
     Stub,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field {
+    pub kind: TypeReference,
+    /// Whether or not the field can be accessed from outside of the module
+    /// the type is defined in.
+    pub export: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum Enum {
     Simple(Vec<String>),
-    Value(Vec<(String, ())>),
+    /// Variants with a discriminant, e.g. `Red = 1`; variants without one
+    /// are one more than the variant before them.
+    Value(Vec<(String, u64)>),
     Unit(Vec<(String, Vec<TypeReference>)>),
 }
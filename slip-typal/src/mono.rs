@@ -0,0 +1,477 @@
+//! Monomorphization.  Generic types and functions in a module are templates;
+//! every `Mix` reference to a generic type, and every call to a generic
+//! function, is an instantiation of that template with a list of arguments.
+//! This replaces each instantiation with a concrete copy of the template, so
+//! that nothing generic is left - which is what code is generated from.
+//!
+//! Instantiation starts at the exported functions of the module, and walks
+//! everything reachable from them; functions that aren't reachable from an
+//! exported function are left out.  Every instance is keyed by its template
+//! and its argument list, so each is only built once.
+//!
+//! Instantiation doesn't have to terminate; e.g. a generic function that
+//! calls itself with `Box<T>` for `T` would need infinitely many instances.
+//! So instances can only be nested so deeply (see [`Monomorphize::limit`])
+//! before monomorphization gives up with [`MonoError::DepthLimit`].
+
+use crate::module::{
+    Enum, Field, Function, FunctionId, Local, Module, Name, Operation, Type, TypeDefinition,
+    TypeId, TypeReference,
+};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// The default limit on how deeply instances can be nested.
+pub const DEFAULT_LIMIT: usize = 64;
+
+#[derive(Debug, Fail, Clone)]
+pub enum MonoError {
+    #[fail(
+        display = "instantiating {} exceeded the depth limit of {}; its instantiation likely never terminates",
+        _0, _1
+    )]
+    DepthLimit(Name, usize),
+    #[fail(display = "could not infer the generic {} of {} from its call", _1, _0)]
+    Uninferred(Name, Name),
+}
+
+/// Monomorphizes a module with the default depth limit.  See
+/// [`Monomorphize`].
+pub fn monomorphize(module: &Module) -> Result<Module, MonoError> {
+    Monomorphize::new(module).run()
+}
+
+#[derive(Debug)]
+pub struct Monomorphize<'m> {
+    module: &'m Module,
+    limit: usize,
+    output: Module,
+    types: HashMap<(TypeId, Vec<TypeReference>), TypeId>,
+    funcs: HashMap<(FunctionId, Vec<TypeReference>), FunctionId>,
+    /// The instances that have been given an id, but have yet to be built.
+    queue: VecDeque<Instance>,
+    next_type: TypeId,
+    next_func: FunctionId,
+}
+
+#[derive(Debug)]
+enum Instance {
+    Type(TypeId, TypeId, Vec<TypeReference>, usize),
+    Function(FunctionId, FunctionId, Vec<TypeReference>, usize),
+}
+
+impl<'m> Monomorphize<'m> {
+    pub fn new(module: &'m Module) -> Monomorphize<'m> {
+        let output = Module {
+            name: module.name.clone(),
+            version: module.version.clone(),
+            requirements: module.requirements.clone(),
+            types: Default::default(),
+            funcs: Default::default(),
+            // Constants can't be generic, so they keep their types as is.
+            consts: module.consts.clone(),
+        };
+
+        Monomorphize {
+            module,
+            limit: DEFAULT_LIMIT,
+            output,
+            types: HashMap::new(),
+            funcs: HashMap::new(),
+            queue: VecDeque::new(),
+            next_type: module.next_type_id(),
+            next_func: module.next_function_id(),
+        }
+    }
+
+    /// Sets how deeply instances can be nested; an instance created while
+    /// building another instance is one level deeper than it.
+    pub fn limit(mut self, limit: usize) -> Monomorphize<'m> {
+        self.limit = limit;
+        self
+    }
+
+    /// Builds the monomorphized module.  Types and functions that aren't
+    /// generic keep their ids; instances are given new ones.
+    pub fn run(mut self) -> Result<Module, MonoError> {
+        let module = self.module;
+        // Every type that isn't generic is kept as is.  Primitive types are
+        // kept even if they are generic, as they are built in.
+        for (id, type_) in &module.types {
+            if type_.generics.is_empty() || type_.definition.is_primitive() {
+                self.types.insert((*id, vec![]), *id);
+                self.queue.push_back(Instance::Type(*id, *id, vec![], 0));
+            }
+        }
+
+        for (id, _) in module
+            .exported_funcs()
+            .filter(|(_, f)| f.generics.is_empty())
+        {
+            self.function(*id, vec![], 0)?;
+        }
+
+        while let Some(instance) = self.queue.pop_front() {
+            match instance {
+                Instance::Type(template, id, args, depth) => {
+                    self.build_type(template, id, &args, depth)?
+                }
+                Instance::Function(template, id, args, depth) => {
+                    self.build_function(template, id, &args, depth)?
+                }
+            }
+        }
+
+        Ok(self.output)
+    }
+
+    /// The id of the instance of the given type with the given arguments,
+    /// queueing it to be built if it hasn't been already.
+    fn type_(
+        &mut self,
+        template: TypeId,
+        args: Vec<TypeReference>,
+        depth: usize,
+    ) -> Result<TypeId, MonoError> {
+        if let Some(id) = self.types.get(&(template, args.clone())) {
+            return Ok(*id);
+        } else if args.is_empty() {
+            return Ok(template);
+        }
+
+        if depth > self.limit {
+            let name = self.module.types[&template].name.clone();
+            return Err(MonoError::DepthLimit(self.name(&name, &args), self.limit));
+        }
+
+        let id = self.next_type;
+        self.next_type = id.next();
+        self.types.insert((template, args.clone()), id);
+        self.queue
+            .push_back(Instance::Type(template, id, args, depth));
+        Ok(id)
+    }
+
+    /// The id of the instance of the given function with the given
+    /// arguments, queueing it to be built if it hasn't been already.
+    fn function(
+        &mut self,
+        template: FunctionId,
+        args: Vec<TypeReference>,
+        depth: usize,
+    ) -> Result<FunctionId, MonoError> {
+        if let Some(id) = self.funcs.get(&(template, args.clone())) {
+            return Ok(*id);
+        }
+
+        if depth > self.limit {
+            let name = self.module.funcs[&template].name.clone();
+            return Err(MonoError::DepthLimit(self.name(&name, &args), self.limit));
+        }
+
+        // Functions that aren't generic keep their id.
+        let id = if args.is_empty() {
+            template
+        } else {
+            let id = self.next_func;
+            self.next_func = id.next();
+            id
+        };
+        self.funcs.insert((template, args.clone()), id);
+        self.queue
+            .push_back(Instance::Function(template, id, args, depth));
+        Ok(id)
+    }
+
+    fn build_type(
+        &mut self,
+        template: TypeId,
+        id: TypeId,
+        args: &[TypeReference],
+        depth: usize,
+    ) -> Result<(), MonoError> {
+        let module = self.module;
+        let type_ = &module.types[&template];
+        let definition = match &type_.definition {
+            TypeDefinition::Struct(fields) => TypeDefinition::Struct(
+                fields
+                    .iter()
+                    .map(|(name, field)| {
+                        let field = Field {
+                            kind: self.instance(&field.kind, args, depth)?,
+                            export: field.export,
+                        };
+                        Ok((name.clone(), field))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            TypeDefinition::Enum(Enum::Unit(variants)) => TypeDefinition::Enum(Enum::Unit(
+                variants
+                    .iter()
+                    .map(|(name, payload)| {
+                        let payload = payload
+                            .iter()
+                            .map(|kind| self.instance(kind, args, depth))
+                            .collect::<Result<_, _>>()?;
+                        Ok((name.clone(), payload))
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            TypeDefinition::Alias(kind) => TypeDefinition::Alias(self.instance(kind, args, depth)?),
+            definition => definition.clone(),
+        };
+
+        let generics = if args.is_empty() {
+            type_.generics.clone()
+        } else {
+            vec![]
+        };
+        let type_ = Type {
+            name: self.name(&type_.name, args),
+            generics,
+            definition,
+            export: type_.export,
+        };
+        self.output.types.insert(id, type_);
+        Ok(())
+    }
+
+    fn build_function(
+        &mut self,
+        template: FunctionId,
+        id: FunctionId,
+        args: &[TypeReference],
+        depth: usize,
+    ) -> Result<(), MonoError> {
+        let module = self.module;
+        let func = &module.funcs[&template];
+        let locals = func
+            .locals
+            .iter()
+            .map(|kind| kind.substitute(args))
+            .collect::<Vec<_>>();
+
+        let mut blocks = BTreeMap::new();
+        for (block, operations) in &func.blocks {
+            let operations = operations
+                .iter()
+                .map(|operation| match operation {
+                    Operation::Call(result, callee, arguments) => {
+                        let callee = self.callee(*callee, *result, arguments, &locals, depth)?;
+                        Ok(Operation::Call(*result, callee, arguments.clone()))
+                    }
+                    operation => Ok(operation.clone()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            blocks.insert(*block, operations);
+        }
+
+        let parameters = func
+            .parameters
+            .iter()
+            .map(|kind| self.instance(kind, args, depth))
+            .collect::<Result<_, _>>()?;
+        let retval = func
+            .retval
+            .as_ref()
+            .map(|kind| self.instance(kind, args, depth))
+            .transpose()?;
+        let locals = locals
+            .iter()
+            .map(|kind| self.lower(kind, depth))
+            .collect::<Result<_, _>>()?;
+
+        let func = Function {
+            name: self.name(&func.name, args),
+            generics: vec![],
+            parameters,
+            retval,
+            locals,
+            blocks,
+            export: func.export && args.is_empty(),
+            origin: func.origin.clone(),
+        };
+        self.output.funcs.insert(id, func);
+        Ok(())
+    }
+
+    /// The instance of the function called with the given arguments, from
+    /// a function with the given (concrete) locals.  The arguments of the
+    /// instance are inferred from the types of the arguments and of the
+    /// result.
+    fn callee(
+        &mut self,
+        callee: FunctionId,
+        result: Local,
+        arguments: &[Local],
+        locals: &[TypeReference],
+        depth: usize,
+    ) -> Result<FunctionId, MonoError> {
+        let module = self.module;
+        let func = match module.funcs.get(&callee) {
+            Some(func) => func,
+            None => return Ok(callee),
+        };
+
+        if func.generics.is_empty() {
+            return self.function(callee, vec![], depth);
+        }
+
+        let mut inferred = vec![None; func.generics.len()];
+        for (parameter, argument) in func.parameters.iter().zip(arguments) {
+            parameter.infer(&locals[argument.index()], &mut inferred);
+        }
+        if let Some(retval) = &func.retval {
+            retval.infer(&locals[result.index()], &mut inferred);
+        }
+
+        let args = inferred
+            .into_iter()
+            .zip(&func.generics)
+            .map(|(kind, generic)| {
+                kind.ok_or_else(|| MonoError::Uninferred(func.name.clone(), generic.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.function(callee, args, depth + 1)
+    }
+
+    /// Substitutes the arguments into the given type, and lowers the result;
+    /// see [`Monomorphize::lower`].
+    fn instance(
+        &mut self,
+        kind: &TypeReference,
+        args: &[TypeReference],
+        depth: usize,
+    ) -> Result<TypeReference, MonoError> {
+        self.lower(&kind.substitute(args), depth)
+    }
+
+    /// Replaces every instantiation of a generic type in the given type with
+    /// a reference to its instance.
+    fn lower(&mut self, kind: &TypeReference, depth: usize) -> Result<TypeReference, MonoError> {
+        match kind {
+            TypeReference::Mix(template, args) => {
+                let primitive = self
+                    .module
+                    .types
+                    .get(template)
+                    .is_none_or(|t| t.definition.is_primitive());
+                if primitive {
+                    let args = args
+                        .iter()
+                        .map(|kind| self.lower(kind, depth))
+                        .collect::<Result<_, _>>()?;
+                    Ok(TypeReference::Mix(*template, args))
+                } else {
+                    let id = self.type_(*template, args.clone(), depth + 1)?;
+                    Ok(TypeReference::Absolute(id))
+                }
+            }
+            _ => Ok(kind.clone()),
+        }
+    }
+
+    /// The name of the instance of the template with the given name, e.g.
+    /// `Box<i32>`.
+    fn name(&self, name: &Name, args: &[TypeReference]) -> Name {
+        if args.is_empty() {
+            return name.clone();
+        }
+
+        let args = args
+            .iter()
+            .map(|kind| self.describe(kind))
+            .collect::<Vec<_>>();
+        name.suffixed(&format!("<{}>", args.join(", ")))
+    }
+
+    fn describe(&self, kind: &TypeReference) -> String {
+        match kind {
+            TypeReference::Absolute(id) => self.describe_id(*id),
+            TypeReference::Mix(id, args) => {
+                let args = args
+                    .iter()
+                    .map(|kind| self.describe(kind))
+                    .collect::<Vec<_>>();
+                format!("{}<{}>", self.describe_id(*id), args.join(", "))
+            }
+            TypeReference::Generic(i) => format!("(generic {})", i),
+        }
+    }
+
+    fn describe_id(&self, id: TypeId) -> String {
+        self.module
+            .types
+            .get(&id)
+            .map(|t| t.name.to_string())
+            .unwrap_or_else(|| "(unknown)".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::module::BlockId;
+    use crate::spec::ModuleSpec;
+
+    fn function(name: &str, generics: Vec<Name>, locals: Vec<TypeReference>) -> Function {
+        Function {
+            name: name.into(),
+            generics,
+            parameters: locals.clone(),
+            retval: None,
+            locals,
+            blocks: BTreeMap::new(),
+            export: false,
+            origin: None,
+        }
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        // `Nest<T>` holds a `Nest<Nest<T>>`, so every instance needs a
+        // deeper one.
+        let mut spec = ModuleSpec::new("nest", "1.0.0".parse().unwrap());
+        let i32_ = spec.primitive_type(32);
+        let nest = spec.stub_type(vec!["Nest"], vec!["T".into()]);
+        let inner = TypeReference::Mix(nest, vec![TypeReference::Generic(0)]);
+        let field = Field {
+            kind: TypeReference::Mix(nest, vec![inner]),
+            export: false,
+        };
+        spec.update_type(nest, |kind| {
+            kind.definition =
+                TypeDefinition::Struct(vec![("inner".to_string(), field)].into_iter().collect())
+        });
+        let parameter = TypeReference::Mix(nest, vec![TypeReference::Absolute(i32_)]);
+        let mut start = function("start", vec![], vec![parameter]);
+        start.export = true;
+        spec.function_push(start);
+
+        let result = Monomorphize::new(spec.module()).limit(4).run();
+        assert!(matches!(result, Err(MonoError::DepthLimit(_, 4))));
+    }
+
+    #[test]
+    fn test_uninferred() {
+        // Nothing that `make` is called with mentions its `T`.
+        let mut spec = ModuleSpec::new("make", "1.0.0".parse().unwrap());
+        let void = TypeReference::Absolute(spec.void_type());
+        let make = spec.function_push(function("make", vec!["T".into()], vec![]));
+        let mut start = function("start", vec![], vec![]);
+        start.locals = vec![void];
+        start.blocks.insert(
+            BlockId::default(),
+            vec![Operation::Call(Local::from_index(0), make, vec![])],
+        );
+        start.export = true;
+        spec.function_push(start);
+
+        match monomorphize(spec.module()) {
+            Err(MonoError::Uninferred(func, generic)) => {
+                assert_eq!(func, Name::from("make"));
+                assert_eq!(generic, Name::from("T"));
+            }
+            result => panic!("expected an uninferred generic, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::module::{BlockId, Function, Local, Operation, TypeReference};
+use std::collections::BTreeMap;
+
+/// Builds up the body of a single function.  The parameters of the function
+/// are the first locals, and the first block created is the entry block.
+/// Once the body is complete, it is applied to the function stub that was
+/// created for it in the [`super::ModuleSpec`].
+#[derive(Debug, Clone)]
+pub struct FunctionSpec {
+    locals: Vec<TypeReference>,
+    blocks: BTreeMap<BlockId, Vec<Operation>>,
+    next: BlockId,
+}
+
+impl FunctionSpec {
+    pub fn new(parameters: &[TypeReference]) -> FunctionSpec {
+        let entry = BlockId::default();
+        let mut blocks = BTreeMap::new();
+        blocks.insert(entry, vec![]);
+        FunctionSpec {
+            locals: parameters.to_vec(),
+            blocks,
+            next: entry.next(),
+        }
+    }
+
+    /// The block that the function starts executing in.
+    pub fn entry(&self) -> BlockId {
+        BlockId::default()
+    }
+
+    /// The local that holds the parameter at the given position.
+    pub fn parameter(&self, position: usize) -> Local {
+        Local::from_index(position)
+    }
+
+    /// Creates a new local of the given type.
+    pub fn local(&mut self, kind: TypeReference) -> Local {
+        let local = Local::from_index(self.locals.len());
+        self.locals.push(kind);
+        local
+    }
+
+    /// The type of the given local.  This panics if the local was not created
+    /// by this spec.
+    pub fn type_of(&self, local: Local) -> &TypeReference {
+        &self.locals[local.index()]
+    }
+
+    /// Changes the type of the given local.  This is used for literals, whose
+    /// type depends on where they are used.
+    pub fn retype(&mut self, local: Local, kind: TypeReference) {
+        self.locals[local.index()] = kind;
+    }
+
+    /// Creates a new, empty block.
+    pub fn block(&mut self) -> BlockId {
+        let id = self.next;
+        self.next = id.next();
+        self.blocks.insert(id, vec![]);
+        id
+    }
+
+    pub fn push(&mut self, block: BlockId, operation: Operation) {
+        self.blocks.entry(block).or_default().push(operation);
+    }
+
+    /// Whether or not the given block already ends in a jump, branch, or
+    /// return; any operation pushed after that is unreachable.
+    pub fn is_terminated(&self, block: BlockId) -> bool {
+        matches!(
+            self.blocks.get(&block).and_then(|ops| ops.last()),
+            Some(Operation::Jump(_))
+                | Some(Operation::Branch(_, _, _))
+                | Some(Operation::Return(_))
+        )
+    }
+
+    /// Whether or not the given block can be run; that is, whether it is the
+    /// entry block, or any other block jumps or branches to it.
+    pub fn is_reachable(&self, block: BlockId) -> bool {
+        block == self.entry()
+            || self.blocks.values().flatten().any(|op| match op {
+                Operation::Jump(target) => *target == block,
+                Operation::Branch(_, then, other) => *then == block || *other == block,
+                _ => false,
+            })
+    }
+
+    /// Moves the locals and blocks built up into the given function.
+    pub fn apply(self, func: &mut Function) {
+        func.locals = self.locals;
+        func.blocks = self.blocks;
+    }
+}
@@ -1,13 +1,22 @@
-use crate::module::{Module, Name, Type, TypeDefinition, TypeId, TypeReference};
+use crate::module::{
+    Const, ConstId, Field, Function, FunctionId, Module, Name, Requirement, Type, TypeDefinition,
+    TypeId, TypeReference,
+};
 use crate::version::Version;
 use std::collections::HashMap;
 
+mod function;
+
+pub use self::function::FunctionSpec;
+
 #[derive(Debug, Clone)]
 pub struct ModuleSpec {
     module: Module,
     primitives: HashMap<u64, TypeId>,
+    floats: HashMap<u64, TypeId>,
     pointer: Option<TypeId>,
     size: Option<TypeId>,
+    error: Option<TypeId>,
 }
 
 impl ModuleSpec {
@@ -18,12 +27,15 @@ impl ModuleSpec {
             requirements: vec![],
             types: Default::default(),
             funcs: Default::default(),
+            consts: Default::default(),
         };
         ModuleSpec {
             module: mod_,
             primitives: HashMap::new(),
+            floats: HashMap::new(),
             pointer: None,
             size: None,
+            error: None,
         }
     }
 
@@ -34,6 +46,13 @@ impl ModuleSpec {
             .unwrap_or_else(|| self.generate_primitive(size))
     }
 
+    pub fn float_type(&mut self, size: u64) -> TypeId {
+        self.floats
+            .get(&size)
+            .cloned()
+            .unwrap_or_else(|| self.generate_float(size))
+    }
+
     pub fn type_push(&mut self, type_: Type) -> TypeId {
         let id = self.module.next_type_id();
         self.module.types.insert(id, type_);
@@ -52,6 +71,22 @@ impl ModuleSpec {
         self.size.unwrap_or_else(|| self.generate_size())
     }
 
+    /// The type of values that failed to build, e.g. a reference to an
+    /// undefined local.  It is only generated once something fails, and
+    /// stands in for any type, so that one mistake is only reported once.
+    pub fn error_type(&mut self) -> TypeId {
+        self.error.unwrap_or_else(|| self.generate_error())
+    }
+
+    /// Whether or not the given type is the error type, or is built on it;
+    /// see [`error_type()`].
+    pub fn is_error(&self, kind: &TypeReference) -> bool {
+        match kind {
+            TypeReference::Absolute(id) | TypeReference::Mix(id, _) => self.error == Some(*id),
+            TypeReference::Generic(_) => false,
+        }
+    }
+
     pub fn stub_type<N, G>(&mut self, name: N, generics: G) -> TypeId
     where
         N: Into<Name>,
@@ -61,6 +96,7 @@ impl ModuleSpec {
             name: name.into(),
             generics: generics.into_iter().collect(),
             definition: TypeDefinition::Stub,
+            export: false,
         };
 
         self.type_push(type_)
@@ -75,16 +111,94 @@ impl ModuleSpec {
         }
     }
 
+    pub fn get_type(&self, id: TypeId) -> Option<&Type> {
+        self.module.types.get(&id)
+    }
+
+    pub fn function_push(&mut self, func: Function) -> FunctionId {
+        let id = self.module.next_function_id();
+        self.module.funcs.insert(id, func);
+        id
+    }
+
+    pub fn stub_function<N, G, P>(
+        &mut self,
+        name: N,
+        generics: G,
+        parameters: P,
+        retval: Option<TypeReference>,
+    ) -> FunctionId
+    where
+        N: Into<Name>,
+        G: IntoIterator<Item = Name>,
+        P: IntoIterator<Item = TypeReference>,
+    {
+        let parameters = parameters.into_iter().collect::<Vec<_>>();
+        let func = Function {
+            name: name.into(),
+            generics: generics.into_iter().collect(),
+            locals: parameters.clone(),
+            parameters,
+            retval,
+            blocks: Default::default(),
+            export: false,
+            origin: None,
+        };
+
+        self.function_push(func)
+    }
+
+    pub fn update_function<F>(&mut self, id: FunctionId, f: F)
+    where
+        F: FnOnce(&mut Function),
+    {
+        if let Some(func) = self.module.funcs.get_mut(&id) {
+            f(func);
+        }
+    }
+
+    pub fn get_function(&self, id: FunctionId) -> Option<&Function> {
+        self.module.funcs.get(&id)
+    }
+
+    pub fn const_push(&mut self, const_: Const) -> ConstId {
+        let id = self.module.next_const_id();
+        self.module.consts.insert(id, const_);
+        id
+    }
+
+    pub fn get_const(&self, id: ConstId) -> Option<&Const> {
+        self.module.consts.get(&id)
+    }
+
+    /// Records that the module requires the given module, e.g. because it
+    /// references items defined in it.  Each module is only recorded once.
+    pub fn require(&mut self, requirement: Requirement) {
+        let requirements = &mut self.module.requirements;
+        if !requirements.iter().any(|r| r.name == requirement.name) {
+            requirements.push(requirement);
+        }
+    }
+
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    pub fn into_module(self) -> Module {
+        self.module
+    }
+
     pub fn struct_type<N, G, E>(&mut self, name: N, generics: G, elements: E) -> TypeId
     where
         N: Into<Name>,
         G: IntoIterator<Item = Name>,
-        E: IntoIterator<Item = (String, TypeReference)>,
+        E: IntoIterator<Item = (String, Field)>,
     {
         let type_ = Type {
             name: name.into(),
             generics: generics.into_iter().collect(),
             definition: TypeDefinition::Struct(elements.into_iter().collect()),
+            export: false,
         };
 
         self.type_push(type_)
@@ -95,6 +209,7 @@ impl ModuleSpec {
             name: format!("i{}", size).into(),
             generics: vec![],
             definition: TypeDefinition::Primitive(size),
+            export: false,
         };
 
         let id = self.type_push(type_);
@@ -102,11 +217,25 @@ impl ModuleSpec {
         id
     }
 
+    fn generate_float(&mut self, size: u64) -> TypeId {
+        let type_ = Type {
+            name: format!("f{}", size).into(),
+            generics: vec![],
+            definition: TypeDefinition::PrimitiveFloat(size),
+            export: false,
+        };
+
+        let id = self.type_push(type_);
+        self.floats.insert(size, id);
+        id
+    }
+
     fn generate_pointer(&mut self) -> TypeId {
         let type_ = Type {
             name: ["$slip", "ptr"].iter().cloned().collect(),
             generics: vec!["T".into()],
             definition: TypeDefinition::PrimitivePtr,
+            export: false,
         };
 
         let id = self.type_push(type_);
@@ -119,10 +248,24 @@ impl ModuleSpec {
             name: "isize".into(),
             generics: vec![],
             definition: TypeDefinition::PrimitiveSize,
+            export: false,
         };
 
         let id = self.type_push(type_);
         self.size = Some(id);
         id
     }
+
+    fn generate_error(&mut self) -> TypeId {
+        let type_ = Type {
+            name: ["$slip", "error"].iter().cloned().collect(),
+            generics: vec![],
+            definition: TypeDefinition::Stub,
+            export: false,
+        };
+
+        let id = self.type_push(type_);
+        self.error = Some(id);
+        id
+    }
 }